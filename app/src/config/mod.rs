@@ -4,3 +4,5 @@ pub use app::*;
 mod clerk;
 mod db;
 pub use db::DatabaseSettings;
+mod upload;
+pub use upload::UploadSettings;
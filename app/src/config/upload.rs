@@ -0,0 +1,29 @@
+use serde::Deserialize;
+
+use crate::constants::{
+    DEFAULT_EPSG_UPLOAD_LIMIT_BYTES, DEFAULT_FEATURE_UPLOAD_LIMIT_BYTES,
+    DEFAULT_GEOJSON_UPLOAD_LIMIT_BYTES,
+};
+
+/// Maximum request body sizes for the upload endpoints, in bytes.
+/// Larger requests are rejected with `413 Payload Too Large`.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct UploadSettings {
+    /// Multipart file uploads to the project features endpoints
+    pub feature_upload_limit_bytes: usize,
+    /// GeoJSON request bodies to the project features endpoint
+    pub geojson_upload_limit_bytes: usize,
+    /// `.shz` uploads to the EPSG detection endpoint
+    pub epsg_upload_limit_bytes: usize,
+}
+
+impl Default for UploadSettings {
+    fn default() -> Self {
+        Self {
+            feature_upload_limit_bytes: DEFAULT_FEATURE_UPLOAD_LIMIT_BYTES,
+            geojson_upload_limit_bytes: DEFAULT_GEOJSON_UPLOAD_LIMIT_BYTES,
+            epsg_upload_limit_bytes: DEFAULT_EPSG_UPLOAD_LIMIT_BYTES,
+        }
+    }
+}
@@ -5,6 +5,9 @@ pub const OPEN_API_JSON: &str = "application/vnd.oai.openapi+json;version=3.0";
 pub const USER_AUTH_ID_COLUMN: &str = "clerk_id";
 pub const GIS_DATA_SCHEMA: &str = "gis_data";
 pub const SITE_BOUNDARIES_COLLECTION_NAME: &str = "site boundaries";
+pub const DEFAULT_FEATURE_UPLOAD_LIMIT_BYTES: usize = 1024 * 1024 * 1024;
+pub const DEFAULT_GEOJSON_UPLOAD_LIMIT_BYTES: usize = 50 * 1024 * 1024;
+pub const DEFAULT_EPSG_UPLOAD_LIMIT_BYTES: usize = 1024 * 1024 * 1024;
 
 pub mod db_constraints {
     pub const PROJECT_NAME_UNIQUE: &str = "projects_name_key";
@@ -1,6 +1,7 @@
 use actix_web::{ResponseError, http::StatusCode};
 use domain::{FeatureId, ProjectCollectionId, ProjectFeatureId, ProjectId, TableName};
 use geo::{shapefile_processor::ProcessingError, virtual_shapefile::ShapefileError};
+use ogc::features::filtering::cql2::Cql2Error;
 use thiserror::Error;
 use utils::error_chain_fmt;
 
@@ -9,6 +10,7 @@ use crate::{
         PROJECT_COLLECTION_SLUG_UNIQUE, PROJECT_COLLECTIONS_TITLE_UNIQUE, PROJECT_CRS_ID_FKEY,
         PROJECT_NAME_UNIQUE, PROJECT_SLUG_UNIQUE,
     },
+    postgres::SortError,
     repo::{
         RepositoryError,
         error::{CheckKey, ForeignKey, UniqueKey},
@@ -68,6 +70,26 @@ pub enum ApiError {
     DatabaseForeignKeyViolation(ForeignKey),
     #[error("Invalid collection title: {0}")]
     InvalidCollectionTitle(String),
+    #[error("Invalid GeoJSON: {0}")]
+    InvalidGeoJson(String),
+    #[error("Upload is larger than the {0} byte limit for this endpoint")]
+    PayloadTooLarge(usize),
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(#[from] Cql2Error),
+    #[error("Invalid sortby: {0}")]
+    InvalidSortBy(#[from] SortError),
+    #[error("Unknown property '{0}'")]
+    UnknownProperty(String),
+    #[error("Authentication required")]
+    Unauthenticated,
+    #[error("The feature has changed since it was read")]
+    PreconditionFailed,
+    #[error("An If-Match header with the feature's entity tag is required")]
+    PreconditionRequired,
+    #[error("A replaced feature needs {0}")]
+    MissingFeatureMember(&'static str),
+    #[error("An upload needs a '{0}' field")]
+    MissingUploadField(&'static str),
 }
 
 impl From<RepositoryError> for ApiError {
@@ -126,6 +148,16 @@ impl ResponseError for ApiError {
             ApiError::DatabaseUniqueViolation(_) => StatusCode::CONFLICT,
             ApiError::DatabaseForeignKeyViolation(_) => StatusCode::UNPROCESSABLE_ENTITY,
             ApiError::InvalidCollectionTitle(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::InvalidGeoJson(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::InvalidFilter(_) => StatusCode::BAD_REQUEST,
+            ApiError::InvalidSortBy(_) => StatusCode::BAD_REQUEST,
+            ApiError::UnknownProperty(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthenticated => StatusCode::UNAUTHORIZED,
+            ApiError::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+            ApiError::PreconditionRequired => StatusCode::PRECONDITION_REQUIRED,
+            ApiError::MissingFeatureMember(_) => StatusCode::BAD_REQUEST,
+            ApiError::MissingUploadField(_) => StatusCode::BAD_REQUEST,
         }
     }
 
@@ -133,6 +165,16 @@ impl ResponseError for ApiError {
         let error_response = ErrorResponse {
             message: self.to_string(),
             long_message: format!("{:?}", self),
+            layers: match self {
+                ApiError::Shapefile(ShapefileError::MultipleShapefiles(layers))
+                | ApiError::ShapefileProcessing(ProcessingError::MultipleLayers(layers)) => {
+                    Some(layers.clone())
+                }
+                ApiError::Shapefile(ShapefileError::CadLayersNotFound { available, .. }) => {
+                    Some(available.clone())
+                }
+                _ => None,
+            },
         };
         actix_web::HttpResponse::build(self.status_code()).json(error_response)
     }
@@ -1,11 +1,12 @@
 use actix_multipart::form::{MultipartForm, tempfile::TempFile};
 use actix_web::{post, web::Json};
 use anyhow::Context;
-use gdal::{vector::LayerAccess, vsi};
-use geo::virtual_shapefile::{ShapefileError, get_epsg_from_prj};
+use gdal::vector::LayerAccess;
+use geo::{
+    upload::UploadDir,
+    virtual_shapefile::{ShapefileError, get_epsg_from_prj},
+};
 use serde::{Deserialize, Serialize};
-use std::io::Read;
-use uuid::Uuid;
 
 use crate::errors::ApiError;
 
@@ -40,19 +41,9 @@ pub struct ShzEpsgPayload {
 pub async fn post_epsg_from_shz(
     payload: MultipartForm<ShzEpsgPayload>,
 ) -> Result<Json<EpsgResponse>, ApiError> {
-    let mut shz = payload.into_inner().shz;
-    let mut bytes = Vec::new();
-    shz.file
-        .read_to_end(&mut bytes)
-        .context("failed to read shz file")
-        .map_err(ShapefileError::UnexpectedError)?;
-    let path = format!("/vsimem/{}.shz", Uuid::new_v4());
-    vsi::create_mem_file(&path, bytes)
-        .context("failed to create virtual shz file")
-        .map_err(ShapefileError::UnexpectedError)?;
-    let ds = gdal::Dataset::open(&path)
-        .context("failed to open shz dataset")
-        .map_err(ShapefileError::UnexpectedError)?;
+    let dir = UploadDir::new()?;
+    let path = dir.add(payload.into_inner().shz, "shz")?;
+    let ds = dir.open(&path, &[])?;
     let layer = ds
         .layers()
         .next()
@@ -67,6 +58,5 @@ pub async fn post_epsg_from_shz(
         .context("failed to retrieve spatial ref auth code")
         .map_err(ShapefileError::InvalidData)?;
     let name = srs.name();
-    let _ = vsi::unlink_mem_file(&path);
     Ok(Json(EpsgResponse { srid, name }))
 }
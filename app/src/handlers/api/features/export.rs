@@ -0,0 +1,117 @@
+use actix_files::NamedFile;
+use actix_web::{
+    get,
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    web,
+};
+use anyhow::Context;
+use domain::{FeatureId, ProjectCollectionId, ProjectFeature, ProjectFeatureId, ProjectId};
+use geo::export::{KML_SRID, write_kmz, write_shapefile};
+use ogcapi_types::common::Crs;
+
+use crate::{
+    AuthenticatedUser,
+    errors::ApiError,
+    handlers::api::projects::{KMZ_CONTENT_TYPE, export_download, write_streamed_export},
+    postgres::{ExportScope, PostgresRepo},
+    repo::project_features,
+};
+
+/// Download every active feature of a project collection as a single shapefile layer in
+/// the project's CRS, zipped as a `.shz` or as a plain `.zip` for older desktop GIS.
+#[get("/{projectId}/{collectionId}/export.{extension:shz|zip}")]
+#[tracing::instrument(skip(repo, _user))]
+pub async fn get_collection_shapefile(
+    repo: web::Data<PostgresRepo>,
+    path: web::Path<(ProjectId, ProjectCollectionId, String)>,
+    _user: web::ReqData<AuthenticatedUser>,
+) -> Result<NamedFile, ApiError> {
+    let (project_id, collection_id, extension) = path.into_inner();
+    let export = repo
+        .select_streamed_export(
+            project_id,
+            &ExportScope {
+                collection_id: Some(collection_id),
+                ..Default::default()
+            },
+        )
+        .await?
+        .ok_or(ApiError::ProjectNotFound(project_id))?;
+    let layer_name = export
+        .layer_names()
+        .next()
+        .ok_or(ApiError::ProjectCollectionNotFound(collection_id))?
+        .to_string();
+
+    let download_filename = format!(
+        "{}-{}-{}.{extension}",
+        export.project_slug,
+        layer_name,
+        chrono::Utc::now().format("%Y%m%d")
+    );
+    // GDAL picks the archive format from the extension
+    let export_filename = match extension.as_str() {
+        "zip" => format!("{layer_name}.shp.zip"),
+        _ => format!("{layer_name}.shz"),
+    };
+    let srid = export.srid as u32;
+    let file = write_streamed_export(&repo, &export, &export_filename, move |path, items| {
+        write_shapefile(path, srid, items)
+    })
+    .await?;
+
+    let file = NamedFile::from_file(file, &download_filename)
+        .context("failed to read shapefile export")?
+        .set_content_disposition(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(download_filename)],
+        });
+    Ok(file)
+}
+
+/// Download a single active feature as a KMZ for Google Earth.
+#[get("/{projectId}/{collectionId}/{featureId}/export.kmz")]
+#[tracing::instrument(skip(repo, _user))]
+pub async fn get_feature_kmz(
+    repo: web::Data<PostgresRepo>,
+    path: web::Path<(ProjectId, ProjectCollectionId, FeatureId)>,
+    _user: web::ReqData<AuthenticatedUser>,
+) -> Result<NamedFile, ApiError> {
+    let (project_id, collection_id, feature_id) = path.into_inner();
+    let export = repo
+        .select_streamed_export(
+            project_id,
+            &ExportScope {
+                collection_id: Some(collection_id),
+                feature_id: Some(feature_id),
+                srid: Some(KML_SRID as i32),
+            },
+        )
+        .await?
+        .ok_or(ApiError::ProjectNotFound(project_id))?;
+    let Some(collection_slug) = export.layer_names().next() else {
+        return Err(ApiError::FeatureNotFound(feature_id));
+    };
+    let id = ProjectFeatureId {
+        collection_id,
+        feature_id,
+    };
+    let params = project_features::SelectOneParams {
+        project_id,
+        crs: &Crs::default(),
+    };
+    let feature = repo
+        .select_one_with_params::<ProjectFeature, _>(&id, &params)
+        .await?
+        .ok_or(ApiError::FeatureNotFound(feature_id))?;
+    let download_filename = format!(
+        "{}-{}{:05}-{}.kmz",
+        export.project_slug,
+        collection_slug,
+        feature_id.0,
+        slug::slugify(&feature.properties.name)
+    );
+    // GDAL only writes a KMZ through `/vsizip/` to a path with its extension
+    let file = write_streamed_export(&repo, &export, "export.kmz", write_kmz).await?;
+    export_download(file, download_filename, KMZ_CONTENT_TYPE)
+}
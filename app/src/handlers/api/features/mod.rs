@@ -1,3 +1,5 @@
+pub mod export;
 pub mod get;
 pub mod patch;
 pub mod post;
+pub mod validate;
@@ -1,23 +1,47 @@
 use actix_multipart::form::{MultipartForm, tempfile::TempFile};
 use actix_web::{
+    guard::GuardContext,
+    http::header::ContentType,
     post,
     web::{self, Json},
 };
 use anyhow::Context;
-use domain::{FeatureId, FeatureInputDTO, ProjectCollectionId, ProjectId};
+use domain::{
+    FeatureId, FeatureInputDTO, ProjectCollectionId, ProjectId, UserId, enums::CoordinateDimension,
+};
 use gdal::{
     Dataset,
-    vector::{LayerAccess, OGRwkbGeometryType},
-    vsi,
+    vector::{Layer, LayerAccess, OGRwkbGeometryType},
 };
 use geo::{
-    shapefile_processor::merge_geometries,
-    virtual_shapefile::{ShapefileData, ShapefileError, ShapefileForm},
+    attributes::{AttributePolicy, merge_layer_attributes},
+    dxf::{ClosedPolylines, DxfOptions, open_dxf},
+    gpx::open_gpx,
+    kml::{open_kml, open_kmz},
+    shapefile_processor::{
+        RepairReport, SplitFeature, conform_dimension, merge_layer_geometries,
+        repair_layer_geometries, select_layer_for_type, split_layer_geometries,
+    },
+    tabular::{PointColumns, open_csv, open_xlsx},
+    upload::{UploadDir, UploadedDataset},
+    virtual_shapefile::{ShapefileError, ShapefileForm, ZippedShapefile, encoding_open_options},
+};
+use geojson::GeoJson;
+use ogcapi_types::common::{
+    Crs,
+    media_type::{GEO_JSON, JSON},
 };
-use std::io::Read;
-use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::str::FromStr;
 
-use crate::{AuthenticatedUser, errors::ApiError, postgres::PostgresRepo};
+use crate::{
+    AuthenticatedUser,
+    config::UploadSettings,
+    errors::ApiError,
+    handlers::upload::{dataset_from_bytes, read_body},
+    postgres::PostgresRepo,
+};
 
 #[derive(MultipartForm)]
 pub struct FeatureInputPayload {
@@ -25,109 +49,548 @@ pub struct FeatureInputPayload {
     pub dbf: Option<TempFile>,
     pub shx: Option<TempFile>,
     pub prj: Option<TempFile>,
+    /// Optional code page of the `.dbf`
+    pub cpg: Option<TempFile>,
     pub shz: Option<TempFile>,
-    pub name: actix_multipart::form::text::Text<String>,
+    pub gpkg: Option<TempFile>,
+    pub kml: Option<TempFile>,
+    pub kmz: Option<TempFile>,
+    /// Zip archive holding a shapefile, possibly in nested folders
+    pub zip: Option<TempFile>,
+    /// CAD drawing, which needs an `srid` as DXF has no CRS
+    pub dxf: Option<TempFile>,
+    /// GPS exchange file. Waypoints go to point collections, tracks and routes to line
+    /// collections, unless `layer` picks `waypoints` or `tracks`.
+    pub gpx: Option<TempFile>,
+    /// Table of points, which needs `x_field`, `y_field` and an `srid`
+    pub csv: Option<TempFile>,
+    /// Workbook of points, read like `csv` from the sheet picked by `layer`
+    pub xlsx: Option<TempFile>,
+    /// Column of a `csv` or `xlsx` upload holding the x coordinate (easting or longitude)
+    pub x_field: Option<actix_multipart::form::text::Text<String>>,
+    /// Column of a `csv` or `xlsx` upload holding the y coordinate (northing or latitude)
+    pub y_field: Option<actix_multipart::form::text::Text<String>>,
+    /// Name of the layer to import when the file holds more than one.
+    /// For zip archives this selects the shapefile by name or path in the archive.
+    pub layer: Option<actix_multipart::form::text::Text<String>>,
+    /// DXF layers to import, repeat the field to select several. All layers when omitted.
+    pub cad_layer: Vec<actix_multipart::form::text::Text<String>>,
+    /// Whether closed DXF polylines are imported as polygons (default) or lines
+    pub closed_polylines: Option<actix_multipart::form::text::Text<ClosedPolylines>>,
+    /// SRID of the input, required when the file has no (recognised) CRS. Replaces the
+    /// `.prj` of separately uploaded shapefile components.
+    pub srid: Option<actix_multipart::form::text::Text<i32>>,
+    /// Use `srid` even when the file has a different CRS
+    pub force_srid: Option<actix_multipart::form::text::Text<bool>>,
+    /// Name of the new feature, required unless the upload is only validated
+    pub name: Option<actix_multipart::form::text::Text<String>>,
     pub primary: Option<actix_multipart::form::text::Text<bool>>,
+    /// How attributes are combined when the layer holds more than one feature
+    pub attributes: Option<actix_multipart::form::text::Text<AttributePolicy>>,
+    /// DBF encoding of shapefile uploads (e.g. `CP1252`), overriding the `.cpg` file
+    pub encoding: Option<actix_multipart::form::text::Text<String>>,
+    /// Repair invalid geometries instead of rejecting the upload
+    pub repair: Option<actix_multipart::form::text::Text<bool>>,
+    /// Whether the layer becomes one feature or one feature per row
+    pub mode: Option<actix_multipart::form::text::Text<UploadMode>>,
+    /// With `mode=split`, the field each new feature is named after
+    pub name_field: Option<actix_multipart::form::text::Text<String>>,
 }
 
-fn dataset_from_shz(mut shz: TempFile) -> Result<Dataset, ShapefileError> {
-    let mut bytes = Vec::new();
-    shz.file
-        .read_to_end(&mut bytes)
-        .context("failed to read shz file")
-        .map_err(ShapefileError::UnexpectedError)?;
-    let path = format!("/vsimem/{}.shz", Uuid::new_v4());
-    vsi::create_mem_file(&path, bytes)
-        .context("failed to create virtual shz file")
-        .map_err(ShapefileError::UnexpectedError)?;
-    let ds = Dataset::open(&path)
-        .context("failed to open shz dataset")
-        .map_err(ShapefileError::UnexpectedError)?;
-    let layer = ds
-        .layers()
-        .next()
-        .context("no layers in shz file")
-        .map_err(ShapefileError::UnexpectedError)?;
-    // Force GDAL to read the spatial ref before unlinking
-    let _srs = layer.spatial_ref();
-    let _ = vsi::unlink_mem_file(&path);
-    Ok(ds)
+/// How the features of an uploaded layer are stored.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UploadMode {
+    /// Merge every feature into a single project feature
+    #[default]
+    Merge,
+    /// Insert one project feature per input feature, all in one transaction
+    Split,
 }
 
-fn dataset_from_parts(
-    shp: TempFile,
-    dbf: TempFile,
-    shx: TempFile,
-    prj: TempFile,
-) -> Result<Dataset, ShapefileError> {
-    let shapefile = ShapefileForm { shp, dbf, shx, prj };
-    let shapefile_data: ShapefileData = shapefile
-        .try_into()
-        .context("Unable to create shapefile data")
-        .map_err(ShapefileError::UnexpectedError)?;
-    let ds: Dataset = shapefile_data
-        .try_into()
-        .context("Unable to create GDAL dataset")
-        .map_err(ShapefileError::UnexpectedError)?;
-    Ok(ds)
+/// The id of the new feature, plus what was changed when the upload used `repair=true`.
+/// Uploads with `mode=split` return the ids of every new feature in layer order.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum FeatureUploadResponse {
+    Repaired {
+        id: FeatureId,
+        repair: RepairReport,
+    },
+    Split {
+        ids: Vec<FeatureId>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        repair: Option<RepairReport>,
+    },
+    Created(FeatureId),
 }
 
-#[tracing::instrument(skip(repo, payload))]
-#[post("{projectId}/{collectionId}")]
-pub async fn post_project_feature_shapefile(
-    repo: web::Data<PostgresRepo>,
-    payload: MultipartForm<FeatureInputPayload>,
-    user: web::ReqData<AuthenticatedUser>,
-    path: web::Path<(ProjectId, ProjectCollectionId)>,
-) -> Result<Json<FeatureId>, ApiError> {
-    let (project_id, collection_id) = path.into_inner();
-    let projcet_srid = repo.get_project_srid(project_id).await?;
-    let FeatureInputPayload {
+/// Format specific options for opening an uploaded file
+struct FileOptions {
+    layer: Option<String>,
+    encoding: Option<String>,
+    dxf: DxfOptions,
+    columns: Option<PointColumns>,
+}
+
+/// Open a single-file dataset (e.g. `.shz`, `.gpkg`, `.kml`) straight from the uploaded
+/// temp file. The extension tells GDAL which driver to use.
+fn dataset_from_file(
+    file: TempFile,
+    extension: &str,
+    options: &FileOptions,
+) -> Result<UploadedDataset, ShapefileError> {
+    let dir = UploadDir::new()?;
+    let path = dir.add(file, extension)?;
+    let path_str = path.to_string_lossy();
+    let layer = options.layer.as_deref();
+    let encoding = options.encoding.as_deref();
+    let columns = || {
+        options.columns.as_ref().ok_or_else(|| {
+            ShapefileError::IncorrectFiles(format!(
+                "x_field and y_field are required for {extension} uploads"
+            ))
+        })
+    };
+    match extension {
+        "kml" => open_kml(&path_str).map(UploadedDataset::from),
+        "kmz" => open_kmz(&path_str).map(UploadedDataset::from),
+        "dxf" => open_dxf(&path_str, &options.dxf).map(UploadedDataset::from),
+        "gpx" => open_gpx(&path_str).map(UploadedDataset::from),
+        "csv" => open_csv(&path_str, columns()?).map(UploadedDataset::from),
+        "xlsx" => open_xlsx(&path_str, layer, columns()?).map(UploadedDataset::from),
+        "zip" => {
+            let ds = ZippedShapefile::from_path(&path_str)?.open(layer, encoding)?;
+            Ok(UploadedDataset::new(ds, Some(dir)))
+        }
+        "shz" => dir.open(&path, &encoding_open_options(encoding)?),
+        _ => dir.open(&path, &[]),
+    }
+}
+
+/// Open separately uploaded shapefile components from disk
+fn dataset_from_parts(
+    shapefile: ShapefileForm,
+    encoding: Option<&str>,
+) -> Result<UploadedDataset, ShapefileError> {
+    let ShapefileForm {
         shp,
         dbf,
         shx,
         prj,
-        shz,
-        name,
-        primary,
-    } = payload.into_inner();
-    let ds = match (shz, shp, dbf, shx, prj) {
-        (Some(shz), None, None, None, None) => dataset_from_shz(shz)?,
-        (None, Some(shp), Some(dbf), Some(shx), Some(prj)) => {
-            dataset_from_parts(shp, dbf, shx, prj)?
-        }
-        _ => {
-            return Err(ShapefileError::IncorrectFiles(
-                "provide either a single .shz file or all four shapefile components (shp, dbf, shx, prj)".to_string(),
-            ))?;
+        cpg,
+    } = shapefile;
+    let dir = UploadDir::new()?;
+    let shp = dir.add(shp, "shp")?;
+    dir.add(dbf, "dbf")?;
+    dir.add(shx, "shx")?;
+    if let Some(prj) = prj {
+        dir.add(prj, "prj")?;
+    }
+    if let Some(cpg) = cpg {
+        dir.add(cpg, "cpg")?;
+    }
+    dir.open(&shp, &encoding_open_options(encoding)?)
+}
+
+/// Upload options shared by every input format
+pub struct FeatureUpload {
+    pub primary: Option<bool>,
+    pub layer: Option<String>,
+    pub attributes: AttributePolicy,
+    /// SRID of the input, assigned when the layer has no recognised spatial reference
+    pub srid: Option<i32>,
+    /// Use `srid` even when the layer's spatial reference differs
+    pub force_srid: bool,
+    pub repair: bool,
+    pub mode: UploadMode,
+    /// Field to name split features after, falling back to `{name} {n}`
+    pub name_field: Option<String>,
+}
+
+/// The SRID of the input.
+///
+/// The layer's spatial reference is matched to an EPSG code, which fails for e.g. some
+/// hand-written Esri WKT. `srid` is used when nothing is detected, and replaces a detected
+/// SRID that differs only when `force` is set.
+pub(crate) fn layer_srid(
+    layer: &Layer,
+    srid: Option<i32>,
+    force: bool,
+) -> Result<i32, ShapefileError> {
+    let detected = layer.spatial_ref().and_then(|mut srs| {
+        srs.auth_code()
+            .or_else(|_| srs.auto_identify_epsg().and_then(|_| srs.auth_code()))
+            .ok()
+    });
+    match (detected, srid) {
+        (Some(detected), Some(srid)) if detected != srid && !force => {
+            Err(ShapefileError::CrsMismatch { detected, srid })
         }
-    };
-    let layer = ds.layers().next().unwrap();
-    let srid = layer
-        .spatial_ref()
-        .context("no spatial reference")
-        .map_err(ShapefileError::InvalidData)?
-        .auth_code()
-        .context("failed to retrive spatial ref auth code")
-        .map_err(ShapefileError::InvalidData)?;
-    let target_srid = projcet_srid.unwrap_or(srid);
+        (_, Some(srid)) => Ok(srid),
+        (Some(detected), None) => Ok(detected),
+        (None, None) => Err(ShapefileError::InvalidData(anyhow::anyhow!(
+            "no recognised spatial reference, an srid is required for this file"
+        ))),
+    }
+}
+
+/// Check an SRID given with an upload is a CRS known to PostGIS
+pub(crate) async fn check_upload_srid(
+    repo: &PostgresRepo,
+    upload: &FeatureUpload,
+) -> Result<(), ApiError> {
+    if let Some(srid) = upload.srid
+        && !repo.srid_exists(srid).await?
+    {
+        return Err(ApiError::InvalidCRSID);
+    }
+    Ok(())
+}
+
+/// Merge the selected layer of `ds` into one geometry, check it against the
+/// collection's geometry type and insert it in the project's CRS, keeping Z values
+/// only if the collection stores them.
+async fn insert_feature_from_dataset(
+    repo: &PostgresRepo,
+    ds: &Dataset,
+    name: String,
+    upload: FeatureUpload,
+    project_id: ProjectId,
+    collection_id: ProjectCollectionId,
+    user_id: UserId,
+) -> Result<FeatureUploadResponse, ApiError> {
+    check_upload_srid(repo, &upload).await?;
+    let project_srid = repo.get_project_srid(project_id).await?;
     let geom_type = repo.get_collection_geom_type(collection_id).await?;
     let expected_type: OGRwkbGeometryType::Type = geom_type.into();
-    let geom = merge_geometries(&ds, expected_type)?;
+    let keep_z = repo
+        .get_collection_coordinate_dimension(collection_id)
+        .await?
+        == CoordinateDimension::Xyz;
+
+    let mut layer = select_layer_for_type(ds, upload.layer.as_deref(), expected_type)?;
+    let srid = layer_srid(&layer, upload.srid, upload.force_srid)?;
+    let target_srid = project_srid.unwrap_or(srid);
+    if upload.mode == UploadMode::Split {
+        let mut report = upload.repair.then(RepairReport::default);
+        let features = split_layer_geometries(
+            &mut layer,
+            expected_type,
+            upload.name_field.as_deref(),
+            report.as_mut(),
+        )?;
+        let input_dtos = features
+            .into_iter()
+            .enumerate()
+            .map(|(n, feature)| {
+                let SplitFeature {
+                    name: field_name,
+                    geometry,
+                    properties,
+                    ..
+                } = feature;
+                Ok(FeatureInputDTO {
+                    name: field_name.unwrap_or_else(|| format!("{name} {}", n + 1)),
+                    // Only one feature per collection can be primary
+                    primary: if n == 0 { upload.primary } else { None },
+                    geom_wkb: conform_dimension(&geometry, keep_z)?
+                        .wkb()
+                        .context("failed to create WKB")
+                        .map_err(ShapefileError::UnexpectedError)?,
+                    srid,
+                    target_srid,
+                    properties,
+                })
+            })
+            .collect::<Result<Vec<_>, ApiError>>()?;
+        let items: Vec<_> = input_dtos
+            .iter()
+            .map(|dto| (dto, project_id, collection_id, user_id))
+            .collect();
+        let ids = repo.insert_many(&items).await?;
+        return Ok(FeatureUploadResponse::Split {
+            ids,
+            repair: report,
+        });
+    }
+    let (geom, repair) = if upload.repair {
+        let (geom, report) = repair_layer_geometries(&mut layer, expected_type)?;
+        (geom, Some(report))
+    } else {
+        (merge_layer_geometries(&mut layer, expected_type)?, None)
+    };
+    let properties = merge_layer_attributes(&mut layer, upload.attributes)?;
 
     let input_dto = FeatureInputDTO {
-        name: name.0,
-        primary: primary.map(|p| p.0),
-        geom_wkb: geom
+        name,
+        primary: upload.primary,
+        geom_wkb: conform_dimension(&geom, keep_z)?
             .wkb()
             .context("failed to create WKB")
             .map_err(ShapefileError::UnexpectedError)?,
         srid,
         target_srid,
+        properties,
     };
     let feature_id = repo
-        .insert(&(&input_dto, project_id, collection_id, user.id))
+        .insert(&(&input_dto, project_id, collection_id, user_id))
         .await?;
+    Ok(match repair {
+        Some(repair) => FeatureUploadResponse::Repaired {
+            id: feature_id,
+            repair,
+        },
+        None => FeatureUploadResponse::Created(feature_id),
+    })
+}
+
+/// Open the uploaded file(s) of a multipart upload, returning the dataset, the upload
+/// options and the detected format (the file extension, `shp` for separate components).
+pub(crate) fn dataset_from_payload(
+    payload: FeatureInputPayload,
+) -> Result<(UploadedDataset, FeatureUpload, &'static str), ApiError> {
+    let FeatureInputPayload {
+        shp,
+        dbf,
+        shx,
+        prj,
+        cpg,
+        shz,
+        gpkg,
+        kml,
+        kmz,
+        zip,
+        dxf,
+        gpx,
+        csv,
+        xlsx,
+        x_field,
+        y_field,
+        layer,
+        cad_layer,
+        closed_polylines,
+        srid,
+        force_srid,
+        name: _,
+        primary,
+        attributes,
+        encoding,
+        repair,
+        mode,
+        name_field,
+    } = payload;
+    let mut layer = layer.map(|l| l.0);
+    let encoding = encoding.map(|e| e.0);
+    let srid = srid.map(|s| s.0);
+    let mut single_files: Vec<(&str, TempFile)> = [
+        ("shz", shz),
+        ("gpkg", gpkg),
+        ("kml", kml),
+        ("kmz", kmz),
+        ("zip", zip),
+        ("dxf", dxf),
+        ("gpx", gpx),
+        ("csv", csv),
+        ("xlsx", xlsx),
+    ]
+    .into_iter()
+    .filter_map(|(extension, file)| file.map(|file| (extension, file)))
+    .collect();
+    let (ds, format) = match (
+        single_files.pop(),
+        single_files.is_empty(),
+        shp,
+        dbf,
+        shx,
+        prj,
+    ) {
+        (Some((extension, file)), true, None, None, None, None) => {
+            let options = FileOptions {
+                layer: layer.clone(),
+                encoding: encoding.clone(),
+                dxf: DxfOptions {
+                    cad_layers: cad_layer.into_iter().map(|l| l.0).collect(),
+                    closed_polylines: closed_polylines.map(|c| c.0).unwrap_or_default(),
+                },
+                columns: x_field.zip(y_field).map(|(x, y)| PointColumns {
+                    x_field: x.0,
+                    y_field: y.0,
+                }),
+            };
+            let ds = dataset_from_file(file, extension, &options)?;
+            // The layer name picked the shapefile inside the archive or the sheet of the
+            // workbook, the opened dataset only has one layer
+            if matches!(extension, "zip" | "xlsx") {
+                layer = None;
+            }
+            (ds, extension)
+        }
+        (None, _, Some(shp), Some(dbf), Some(shx), prj) if prj.is_some() || srid.is_some() => {
+            let ds = dataset_from_parts(
+                ShapefileForm {
+                    shp,
+                    dbf,
+                    shx,
+                    prj,
+                    cpg,
+                },
+                encoding.as_deref(),
+            )?;
+            (ds, "shp")
+        }
+        _ => {
+            return Err(ShapefileError::IncorrectFiles(
+                concat!(
+                    "provide either a single .shz, .gpkg, .kml, .kmz, .zip, .dxf, .gpx, .csv ",
+                    "or .xlsx file or the shapefile components (shp, dbf, shx and either a prj ",
+                    "or an srid)"
+                )
+                .to_string(),
+            ))?;
+        }
+    };
+    let upload = FeatureUpload {
+        primary: primary.map(|p| p.0),
+        layer,
+        attributes: attributes.map(|a| a.0).unwrap_or_default(),
+        srid,
+        force_srid: force_srid.is_some_and(|f| f.0),
+        repair: repair.is_some_and(|r| r.0),
+        mode: mode.map(|m| m.0).unwrap_or_default(),
+        name_field: name_field.map(|f| f.0),
+    };
+    Ok((ds, upload, format))
+}
+
+#[tracing::instrument(skip(repo, payload))]
+#[post("{projectId}/{collectionId}")]
+pub async fn post_project_feature_shapefile(
+    repo: web::Data<PostgresRepo>,
+    payload: MultipartForm<FeatureInputPayload>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(ProjectId, ProjectCollectionId)>,
+) -> Result<Json<FeatureUploadResponse>, ApiError> {
+    let (project_id, collection_id) = path.into_inner();
+    let mut payload = payload.into_inner();
+    let name = payload
+        .name
+        .take()
+        .ok_or(ApiError::MissingUploadField("name"))?;
+    let (ds, upload, _format) = dataset_from_payload(payload)?;
+    let response = insert_feature_from_dataset(
+        &repo,
+        &ds,
+        name.0,
+        upload,
+        project_id,
+        collection_id,
+        user.id,
+    )
+    .await?;
+
+    Ok(Json(response))
+}
+
+/// Matches `application/geo+json` and `application/json` request bodies
+fn geo_json_guard(ctx: &GuardContext) -> bool {
+    ctx.header::<ContentType>()
+        .is_some_and(|ct| ct.essence_str() == GEO_JSON || ct.essence_str() == JSON)
+}
+
+/// Read the `crs` member of an upload: an SRID, a CRS URI or a legacy named CRS object.
+fn srid_from_crs_member(crs: &Value) -> Option<i32> {
+    let name = match crs {
+        Value::Number(srid) => return srid.as_i64().and_then(|srid| i32::try_from(srid).ok()),
+        Value::String(name) => name.as_str(),
+        Value::Object(object) => object.get("properties")?.get("name")?.as_str()?,
+        _ => return None,
+    };
+    if let Ok(crs) = Crs::from_str(name) {
+        return Some(crs.as_srid());
+    }
+    name.rsplit([':', '/']).next()?.parse().ok()
+}
+
+/// Upload a GeoJSON Geometry, Feature or FeatureCollection as a project feature.
+///
+/// `name`, `primary`, `crs`, `attributes`, `repair`, `mode` and `name_field` are read from
+/// top-level members of the document. Coordinates are assumed to be WGS 84 (EPSG:4326)
+/// when no `crs` is given.
+#[tracing::instrument(skip(repo, payload, upload_settings))]
+#[post("{projectId}/{collectionId}", guard = "geo_json_guard")]
+pub async fn post_project_feature_geojson(
+    repo: web::Data<PostgresRepo>,
+    payload: web::Payload,
+    upload_settings: web::Data<UploadSettings>,
+    user: web::ReqData<AuthenticatedUser>,
+    path: web::Path<(ProjectId, ProjectCollectionId)>,
+) -> Result<Json<FeatureUploadResponse>, ApiError> {
+    let (project_id, collection_id) = path.into_inner();
+    let body = read_body(payload, upload_settings.geojson_upload_limit_bytes).await?;
+    let mut document: Map<String, Value> =
+        serde_json::from_slice(&body).map_err(|e| ApiError::InvalidGeoJson(e.to_string()))?;
+
+    let name = match document.remove("name") {
+        Some(Value::String(name)) => name,
+        _ => {
+            return Err(ApiError::InvalidGeoJson(
+                "a 'name' member is required".to_string(),
+            ));
+        }
+    };
+    let primary = document.remove("primary").and_then(|p| p.as_bool());
+    let srid = match document.remove("crs") {
+        Some(crs) => srid_from_crs_member(&crs)
+            .ok_or_else(|| ApiError::InvalidGeoJson(format!("unrecognised crs: {crs}")))?,
+        None => 4326,
+    };
+    let attributes = document
+        .remove("attributes")
+        .map(serde_json::from_value::<AttributePolicy>)
+        .transpose()
+        .map_err(|e| ApiError::InvalidGeoJson(e.to_string()))?
+        .unwrap_or_default();
+    let repair = document
+        .remove("repair")
+        .and_then(|r| r.as_bool())
+        .unwrap_or_default();
+    let mode = document
+        .remove("mode")
+        .map(serde_json::from_value::<UploadMode>)
+        .transpose()
+        .map_err(|e| ApiError::InvalidGeoJson(e.to_string()))?
+        .unwrap_or_default();
+    let name_field = match document.remove("name_field") {
+        Some(Value::String(field)) => Some(field),
+        Some(field) => {
+            return Err(ApiError::InvalidGeoJson(format!(
+                "name_field must be a string, found {field}"
+            )));
+        }
+        None => None,
+    };
+
+    // Check the document is GeoJSON before handing it to GDAL
+    GeoJson::from_json_object(document.clone())
+        .map_err(|e| ApiError::InvalidGeoJson(e.to_string()))?;
+    let bytes = serde_json::to_vec(&document).context("failed to serialise GeoJSON")?;
+    let ds = dataset_from_bytes(bytes, "geojson")?;
+
+    let upload = FeatureUpload {
+        primary,
+        layer: None,
+        attributes,
+        srid: Some(srid),
+        // GDAL reads every GeoJSON document as WGS 84, the crs member is authoritative
+        force_srid: true,
+        repair,
+        mode,
+        name_field,
+    };
+    let response =
+        insert_feature_from_dataset(&repo, &ds, name, upload, project_id, collection_id, user.id)
+            .await?;
 
-    Ok(Json(feature_id))
+    Ok(Json(response))
 }
@@ -0,0 +1,243 @@
+use actix_multipart::form::MultipartForm;
+use actix_web::{
+    post,
+    web::{self, Json},
+};
+use anyhow::Context;
+use domain::{ProjectCollectionId, ProjectId, enums::CoordinateDimension};
+use gdal::vector::{Geometry, LayerAccess, OGRwkbGeometryType};
+use geo::{
+    shapefile_processor::{
+        FeatureProblem, ProcessingError, RepairReport, conform_dimension, has_m, has_z,
+        merge_layer_geometries, repair_layer_geometries, select_layer_for_type,
+        split_layer_geometries, validate_layer_geometries,
+    },
+    virtual_shapefile::ShapefileError,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::ApiError,
+    handlers::api::features::post::{
+        FeatureInputPayload, UploadMode, check_upload_srid, dataset_from_payload, layer_srid,
+    },
+    postgres::PostgresRepo,
+};
+
+/// Result of a dry run of a feature upload
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ValidationReport {
+    /// Whether the upload would be accepted
+    pub valid: bool,
+    /// Detected format: the file extension, or `shp` for separate shapefile components
+    pub format: String,
+    /// Layer that was validated, if one could be selected
+    pub layer: Option<String>,
+    /// Layers to pick from with `layer` or `cad_layer` when none could be selected
+    pub layers: Option<Vec<String>>,
+    pub feature_count: u64,
+    /// SRID detected from the input, if any
+    pub srid: Option<i32>,
+    /// SRID the feature would be stored in
+    pub target_srid: Option<i32>,
+    /// Whether the input has Z values, which are only stored if the collection is XYZ
+    pub has_z: bool,
+    /// Whether the input has M values, which are never stored
+    pub has_m: bool,
+    /// Every feature (or layer-wide) problem that would fail the upload
+    pub problems: Vec<FeatureProblem>,
+    /// `[minx, miny, maxx, maxy]` of the merged geometry in the target CRS
+    pub bbox: Option<Vec<f64>>,
+    /// Geodesic area of the merged geometry in square metres
+    pub area: Option<f64>,
+    /// Geodesic length of the merged geometry in metres
+    pub length: Option<f64>,
+}
+
+impl ValidationReport {
+    fn new(format: &str) -> Self {
+        ValidationReport {
+            valid: false,
+            format: format.to_string(),
+            layer: None,
+            layers: None,
+            feature_count: 0,
+            srid: None,
+            target_srid: None,
+            has_z: false,
+            has_m: false,
+            problems: Vec::new(),
+            bbox: None,
+            area: None,
+            length: None,
+        }
+    }
+
+    /// A report of an upload whose layer couldn't be selected, listing the `layers` to
+    /// pick from
+    fn without_layer(format: &str, reason: impl ToString, layers: Vec<String>) -> Self {
+        ValidationReport {
+            layers: Some(layers),
+            problems: vec![FeatureProblem {
+                index: None,
+                reason: reason.to_string(),
+            }],
+            ..ValidationReport::new(format)
+        }
+    }
+
+    /// Note the dimensions of `geom` and drop those the collection doesn't store, as an
+    /// import does, reporting a geometry that can't be converted as a problem of `index`
+    fn conformed(
+        &mut self,
+        geom: &Geometry,
+        keep_z: bool,
+        index: Option<usize>,
+    ) -> Option<Geometry> {
+        self.has_z |= has_z(geom.geometry_type());
+        self.has_m |= has_m(geom.geometry_type());
+        conform_dimension(geom, keep_z)
+            .map_err(|e| {
+                self.problems.push(FeatureProblem {
+                    index,
+                    reason: e.to_string(),
+                })
+            })
+            .ok()
+    }
+}
+
+/// Run an upload through the whole import pipeline without inserting anything,
+/// reporting every problem found instead of failing on the first one.
+///
+/// A layer that can't be selected, e.g. because the file holds several and none was
+/// named, is reported as a problem along with the layers to pick from.
+#[tracing::instrument(skip(repo, payload))]
+#[post("{projectId}/{collectionId}/validate")]
+pub async fn post_project_feature_validate(
+    repo: web::Data<PostgresRepo>,
+    payload: MultipartForm<FeatureInputPayload>,
+    path: web::Path<(ProjectId, ProjectCollectionId)>,
+) -> Result<Json<ValidationReport>, ApiError> {
+    let (project_id, collection_id) = path.into_inner();
+    let (ds, upload, format) = match dataset_from_payload(payload.into_inner()) {
+        Ok(opened) => opened,
+        // Both fail while opening the file, so report the only format they come from
+        Err(ApiError::Shapefile(ShapefileError::CadLayersNotFound { missing, available })) => {
+            let reason = ShapefileError::CadLayersNotFound {
+                missing,
+                available: available.clone(),
+            };
+            return Ok(Json(ValidationReport::without_layer(
+                "dxf", reason, available,
+            )));
+        }
+        Err(ApiError::Shapefile(ShapefileError::MultipleShapefiles(candidates))) => {
+            let reason = ShapefileError::MultipleShapefiles(candidates.clone());
+            return Ok(Json(ValidationReport::without_layer(
+                "zip", reason, candidates,
+            )));
+        }
+        Err(e) => return Err(e),
+    };
+    check_upload_srid(&repo, &upload).await?;
+    let project_srid = repo.get_project_srid(project_id).await?;
+    let geom_type = repo.get_collection_geom_type(collection_id).await?;
+    let expected_type: OGRwkbGeometryType::Type = geom_type.into();
+    let keep_z = repo
+        .get_collection_coordinate_dimension(collection_id)
+        .await?
+        == CoordinateDimension::Xyz;
+
+    let mut layer = match select_layer_for_type(&ds, upload.layer.as_deref(), expected_type) {
+        Ok(layer) => layer,
+        Err(e @ (ProcessingError::MultipleLayers(_) | ProcessingError::LayerNotFound(_))) => {
+            let layers = ds.layers().map(|layer| layer.name()).collect();
+            return Ok(Json(ValidationReport::without_layer(format, e, layers)));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let mut report = ValidationReport {
+        layer: Some(layer.name()),
+        feature_count: layer.feature_count(),
+        ..ValidationReport::new(format)
+    };
+    report.srid = match layer_srid(&layer, upload.srid, upload.force_srid) {
+        Ok(srid) => Some(srid),
+        Err(e) => {
+            report.problems.push(FeatureProblem {
+                index: None,
+                reason: format!("could not determine CRS: {e}"),
+            });
+            None
+        }
+    };
+    report.target_srid = project_srid.or(report.srid);
+    if !upload.repair {
+        report
+            .problems
+            .extend(validate_layer_geometries(&mut layer, expected_type)?);
+    }
+    if !report.problems.is_empty() {
+        return Ok(Json(report));
+    }
+
+    if upload.mode == UploadMode::Split {
+        // Each row becomes its own feature, so there is no merged geometry to measure
+        let mut repair = RepairReport::default();
+        let split = split_layer_geometries(
+            &mut layer,
+            expected_type,
+            upload.name_field.as_deref(),
+            upload.repair.then_some(&mut repair),
+        );
+        match split {
+            Ok(features) => {
+                for feature in &features {
+                    report.conformed(&feature.geometry, keep_z, Some(feature.index));
+                }
+            }
+            Err(e) => report.problems.push(FeatureProblem {
+                index: None,
+                reason: e.to_string(),
+            }),
+        }
+        report.valid = report.problems.is_empty();
+        return Ok(Json(report));
+    }
+
+    let merged = if upload.repair {
+        repair_layer_geometries(&mut layer, expected_type).map(|(geom, _)| geom)
+    } else {
+        merge_layer_geometries(&mut layer, expected_type)
+    };
+    let geom = match merged {
+        Ok(geom) => geom,
+        Err(e) => {
+            report.problems.push(FeatureProblem {
+                index: None,
+                reason: e.to_string(),
+            });
+            return Ok(Json(report));
+        }
+    };
+    let Some(geom) = report.conformed(&geom, keep_z, None) else {
+        return Ok(Json(report));
+    };
+    if let (Some(srid), Some(target_srid)) = (report.srid, report.target_srid) {
+        let wkb = geom.wkb().context("failed to create WKB")?;
+        let measurements = repo.measure_geometry(&wkb, srid, target_srid).await?;
+        if !measurements.is_valid {
+            report.problems.push(FeatureProblem {
+                index: None,
+                reason: format!("geometry is invalid after transforming to EPSG:{target_srid}"),
+            });
+        }
+        report.bbox = Some(measurements.bbox);
+        report.area = Some(measurements.area);
+        report.length = Some(measurements.length);
+    }
+    report.valid = report.problems.is_empty();
+
+    Ok(Json(report))
+}
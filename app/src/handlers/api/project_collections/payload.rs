@@ -1,4 +1,8 @@
-use domain::{ProjectCollectionInputDto, enums::GeometryType, name::NameInputDTO};
+use domain::{
+    ProjectCollectionInputDto,
+    enums::{CoordinateDimension, GeometryType},
+    name::NameInputDTO,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -6,6 +10,9 @@ pub struct CollectionReqPayload {
     pub title: String,
     pub geometry_type: GeometryType,
     pub description: Option<String>,
+    /// Whether Z values of uploaded geometries are kept, 2D (`XY`) by default
+    #[serde(default)]
+    pub coordinate_dimension: CoordinateDimension,
 }
 
 impl Default for CollectionReqPayload {
@@ -14,6 +21,7 @@ impl Default for CollectionReqPayload {
             title: uuid::Uuid::new_v4().to_string(),
             geometry_type: GeometryType::Point,
             description: Default::default(),
+            coordinate_dimension: Default::default(),
         }
     }
 }
@@ -24,6 +32,7 @@ impl TryFrom<CollectionReqPayload> for ProjectCollectionInputDto {
             title,
             geometry_type,
             description,
+            coordinate_dimension,
         } = value;
         let slug = slug::slugify(&title);
         Ok(ProjectCollectionInputDto {
@@ -31,6 +40,7 @@ impl TryFrom<CollectionReqPayload> for ProjectCollectionInputDto {
             slug,
             description,
             geometry_type,
+            coordinate_dimension,
         })
     }
 
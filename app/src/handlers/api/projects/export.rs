@@ -0,0 +1,280 @@
+use actix_files::NamedFile;
+use actix_web::{
+    get,
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    mime::Mime,
+    web,
+};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use domain::{
+    ProjectCollection, ProjectId, UserId,
+    enums::GeometryType,
+    project::{Project, Properties},
+};
+use futures::{
+    channel::mpsc,
+    executor::{self, BlockingStream},
+};
+use geo::{
+    bundle::write_bundle,
+    export::{ExportItem, KML_SRID, write_dxf, write_geopackage, write_kmz, write_temp_file},
+};
+use ogcapi_types::common::Crs;
+use serde::Serialize;
+use std::{fs::File, path::Path};
+
+use crate::{
+    AuthenticatedUser,
+    errors::ApiError,
+    postgres::{ExportScope, PostgresRepo, StreamedExport},
+    repo::{project, project_collections},
+};
+
+/// Download every active feature of a project as a GeoPackage, with one layer per
+/// collection in the project's CRS.
+#[get("/{id}/export.gpkg")]
+#[tracing::instrument(skip(repo, _user))]
+pub async fn get_project_gpkg(
+    repo: web::Data<PostgresRepo>,
+    id: web::Path<ProjectId>,
+    _user: web::ReqData<AuthenticatedUser>,
+) -> Result<NamedFile, ApiError> {
+    let project_id = id.into_inner();
+    let export = repo
+        .select_streamed_export(project_id, &ExportScope::default())
+        .await?
+        .ok_or(ApiError::ProjectNotFound(project_id))?;
+
+    let download_filename = format!(
+        "{}-{}.gpkg",
+        export.project_slug,
+        chrono::Utc::now().format("%Y%m%d")
+    );
+    let srid = export.srid as u32;
+    let file = write_streamed_export(&repo, &export, "export.gpkg", move |path, items| {
+        write_geopackage(path, srid, items)
+    })
+    .await?;
+
+    let file = NamedFile::from_file(file, &download_filename)
+        .context("failed to read geopackage export")?
+        .set_content_disposition(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(download_filename)],
+        });
+    Ok(file)
+}
+
+/// Download a project's data bundle: a zip of its features as a GeoPackage and as one
+/// shapefile per collection, each with a QGIS style, and a `manifest.json` describing the
+/// project and what was exported.
+#[get("/{id}/export.zip")]
+#[tracing::instrument(skip(repo, user))]
+pub async fn get_project_bundle(
+    repo: web::Data<PostgresRepo>,
+    id: web::Path<ProjectId>,
+    user: web::ReqData<AuthenticatedUser>,
+) -> Result<NamedFile, ApiError> {
+    let project_id = id.into_inner();
+    let project = repo
+        .select_one_with_params::<Project, _>(
+            project_id,
+            &project::SelectOneParams {
+                crs: &Crs::default(),
+            },
+        )
+        .await?
+        .ok_or(ApiError::ProjectNotFound(project_id))?;
+    let export = repo
+        .select_streamed_export(project_id, &ExportScope::default())
+        .await?
+        .ok_or(ApiError::ProjectNotFound(project_id))?;
+    let (collections, _) = repo
+        .select_all_with_params::<ProjectCollection>(&project_collections::SelectAllParams {
+            project_id,
+            status: None,
+        })
+        .await?;
+
+    let exported_at = chrono::Utc::now();
+    let exported_by = ManifestUser {
+        id: user.id,
+        name: format!("{} {}", user.first_name, user.last_name),
+    };
+    let download_filename = format!(
+        "{}-{}.zip",
+        export.project_slug,
+        exported_at.format("%Y%m%d")
+    );
+    let project_slug = export.project_slug.clone();
+    let srid = export.srid as u32;
+    let file = write_streamed_export(&repo, &export, "bundle.zip", move |path, items| {
+        // The manifest is made last, so feature counts are those of the features written
+        write_bundle(path, &project_slug, srid, items, |layers| {
+            let manifest = BundleManifest {
+                project: &project.properties,
+                crs: format!("EPSG:{srid}"),
+                exported_at,
+                exported_by,
+                collections: layers
+                    .iter()
+                    .map(|layer| {
+                        let collection = collections
+                            .iter()
+                            .find(|collection| collection.slug == layer.name);
+                        ManifestCollection {
+                            slug: &layer.name,
+                            title: collection.map(|collection| collection.title.as_str()),
+                            description: collection
+                                .and_then(|collection| collection.description.as_deref()),
+                            geometry_type: collection.map(|collection| &collection.geometry_type),
+                            feature_count: layer.feature_count,
+                        }
+                    })
+                    .collect(),
+            };
+            serde_json::to_vec_pretty(&manifest).context("failed to write manifest")
+        })
+    })
+    .await?;
+
+    let file = NamedFile::from_file(file, &download_filename)
+        .context("failed to read bundle export")?
+        .set_content_disposition(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(download_filename)],
+        });
+    Ok(file)
+}
+
+/// `manifest.json` of a project data bundle
+#[derive(Serialize)]
+struct BundleManifest<'a> {
+    project: &'a Properties,
+    /// CRS of every layer in the bundle
+    crs: String,
+    exported_at: DateTime<Utc>,
+    exported_by: ManifestUser,
+    collections: Vec<ManifestCollection<'a>>,
+}
+
+#[derive(Serialize)]
+struct ManifestUser {
+    id: UserId,
+    name: String,
+}
+
+/// A collection exported as a layer of the GeoPackage and as `shapefiles/{slug}.shp`
+#[derive(Serialize)]
+struct ManifestCollection<'a> {
+    slug: &'a str,
+    title: Option<&'a str>,
+    description: Option<&'a str>,
+    geometry_type: Option<&'a GeometryType>,
+    feature_count: usize,
+}
+
+/// Download every active feature of a project as a KMZ for Google Earth, with one folder
+/// per collection.
+#[get("/{id}/export.kmz")]
+#[tracing::instrument(skip(repo, _user))]
+pub async fn get_project_kmz(
+    repo: web::Data<PostgresRepo>,
+    id: web::Path<ProjectId>,
+    _user: web::ReqData<AuthenticatedUser>,
+) -> Result<NamedFile, ApiError> {
+    let project_id = id.into_inner();
+    let export = repo
+        .select_streamed_export(
+            project_id,
+            &ExportScope {
+                srid: Some(KML_SRID as i32),
+                ..Default::default()
+            },
+        )
+        .await?
+        .ok_or(ApiError::ProjectNotFound(project_id))?;
+    let download_filename = format!(
+        "{}-{}.kmz",
+        export.project_slug,
+        chrono::Utc::now().format("%Y%m%d")
+    );
+    // GDAL only writes a KMZ through `/vsizip/` to a path with its extension
+    let file = write_streamed_export(&repo, &export, "export.kmz", write_kmz).await?;
+    export_download(file, download_filename, KMZ_CONTENT_TYPE)
+}
+
+/// Download every active feature of a project as a DXF for CAD, with one CAD layer per
+/// collection in the project's CRS.
+#[get("/{id}/export.dxf")]
+#[tracing::instrument(skip(repo, _user))]
+pub async fn get_project_dxf(
+    repo: web::Data<PostgresRepo>,
+    id: web::Path<ProjectId>,
+    _user: web::ReqData<AuthenticatedUser>,
+) -> Result<NamedFile, ApiError> {
+    let project_id = id.into_inner();
+    let export = repo
+        .select_streamed_export(project_id, &ExportScope::default())
+        .await?
+        .ok_or(ApiError::ProjectNotFound(project_id))?;
+    let download_filename = format!(
+        "{}-{}.dxf",
+        export.project_slug,
+        chrono::Utc::now().format("%Y%m%d")
+    );
+    let file = write_streamed_export(&repo, &export, "export.dxf", write_dxf).await?;
+    export_download(file, download_filename, "image/vnd.dxf")
+}
+
+/// Media type of KMZ downloads
+pub(crate) const KMZ_CONTENT_TYPE: &str = "application/vnd.google-earth.kmz";
+
+/// Stream a written export back as a download of `content_type` named
+/// `download_filename`.
+pub(crate) fn export_download(
+    file: File,
+    download_filename: String,
+    content_type: &str,
+) -> Result<NamedFile, ApiError> {
+    let content_type: Mime = content_type
+        .parse()
+        .context("failed to parse export content type")?;
+    let file = NamedFile::from_file(file, &download_filename)
+        .context("failed to read export")?
+        .set_content_type(content_type)
+        .set_content_disposition(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(download_filename)],
+        });
+    Ok(file)
+}
+
+/// Features read ahead of the writer of a streamed export
+const EXPORT_BUFFER: usize = 64;
+
+/// Write a streamed export to a temporary file named `filename` with `write` on a blocking
+/// thread, handing it each layer and feature as it's read from the database.
+///
+/// If reading fails, the writer sees the export end early and the read error is returned
+/// instead of the file.
+pub(crate) async fn write_streamed_export(
+    repo: &PostgresRepo,
+    export: &StreamedExport,
+    filename: &str,
+    write: impl FnOnce(&Path, BlockingStream<mpsc::Receiver<ExportItem>>) -> anyhow::Result<()>
+    + Send
+    + 'static,
+) -> Result<File, ApiError> {
+    let (sender, receiver) = mpsc::channel(EXPORT_BUFFER);
+    let filename = filename.to_string();
+    let writing = web::block(move || {
+        write_temp_file(&filename, |path| {
+            write(path, executor::block_on_stream(receiver))
+        })
+    });
+    let (written, read) = futures::join!(writing, repo.send_streamed_export(export, sender));
+    read?;
+    Ok(written.context("failed to run export")??)
+}
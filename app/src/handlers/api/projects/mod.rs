@@ -1,3 +1,6 @@
+mod export;
+pub(crate) use export::{KMZ_CONTENT_TYPE, export_download, write_streamed_export};
+pub use export::{get_project_bundle, get_project_dxf, get_project_gpkg, get_project_kmz};
 mod post;
 pub use post::post_project;
 mod payloads;
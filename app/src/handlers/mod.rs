@@ -1,4 +1,5 @@
 pub mod api;
 pub mod ogc_api;
+mod upload;
 pub use crate::errors::{ApiError, ProjectValidationError};
 pub mod webhooks;
@@ -7,7 +7,6 @@ use domain::{
     enums::{CollectionId, Status},
     project::ProjectName,
 };
-use ogcapi_types::common::Link;
 use serde::Deserialize;
 use serde_with::{StringWithSeparator, formats::CommaSeparator};
 
@@ -223,7 +222,7 @@ pub async fn get_project_collection(
 }
 
 fn project_collection(collections_url: &str) -> ogcapi_types::common::Collection {
-    let mut projects = ProjectCollection {
+    ProjectCollection {
         id: CollectionId::Projects,
         storage_crs: None,
         title: "Projects".to_string(),
@@ -232,15 +231,7 @@ fn project_collection(collections_url: &str) -> ogcapi_types::common::Collection
         description: None,
         supported_crs: SupportedCrs::new(None),
         geometry_type: domain::enums::GeometryType::MultiPolygon,
+        coordinate_dimension: Default::default(),
     }
-    .into_ogc_collection(collections_url);
-    let queryables_link = Link::new(
-        format!("{collections_url}/{}/queryables", CollectionId::Projects),
-        "http://www.opengis.net/def/rel/ogc/1.0/queryables",
-    )
-    .title("Queryables")
-    .mediatype("application/schema+json");
-
-    projects.links.push(queryables_link);
-    projects
+    .into_ogc_collection(collections_url)
 }
@@ -1,18 +1,26 @@
 use crate::{
-    handlers::ApiError,
-    postgres::PostgresRepo,
-    repo::{self, project},
+    handlers::{ApiError, ogc_api::features::Query, upload::dataset_from_bytes},
+    postgres::{FilterTarget, PostgresRepo, SortTarget, SqlFilter, SqlSort},
+    repo::{self, RepositoryError, project},
 };
 use actix_web::{
-    HttpResponse,
-    http::header::{HeaderName, HeaderValue},
+    HttpRequest, HttpResponse,
+    http::header::{ETAG, HeaderName, HeaderValue, IF_MATCH},
 };
-
+use anyhow::Context;
+use chrono::{DateTime, Utc};
 use domain::{
-    FeatureId, IntoOGCFeature, ProjectFeature, ProjectFeatureId, ProjectId, enums::CollectionId,
+    FeatureEditDTO, FeatureId, IntoOGCFeature, ProjectCollectionId, ProjectFeature,
+    ProjectFeatureId, ProjectId, SYSTEM_PROPERTIES, UserId,
+    enums::{CollectionId, CoordinateDimension, Status},
     project::Project,
 };
+use gdal::vector::OGRwkbGeometryType;
+use geo::shapefile_processor::{conform_dimension, merge_layer_geometries, select_layer_for_type};
+use ogc::features::filtering::cql2;
 use ogcapi_types::common::Crs;
+use serde_json::{Map, Value, json};
+use std::str::FromStr;
 
 pub async fn retrieve_feature_from_database<'a>(
     repo: &PostgresRepo,
@@ -53,3 +61,199 @@ pub fn append_crs_header(response: &mut HttpResponse, crs: &Crs) {
         HeaderValue::from_str(&format!("<{}>", &crs.to_string())).unwrap(),
     );
 }
+
+/// The request's `filter`, compiled to SQL for the rows of `target`
+pub fn compile_filter(
+    query: &Query,
+    target: FilterTarget<'_>,
+) -> Result<Option<SqlFilter>, ApiError> {
+    let Some(filter) = &query.filter else {
+        return Ok(None);
+    };
+    let expression = cql2::parse(filter, query.filter_lang.unwrap_or_default())?;
+    let filter_srid = query.filter_crs.clone().unwrap_or_default().as_srid();
+    Ok(Some(SqlFilter::new(&expression, target, filter_srid)?))
+}
+
+/// The request's `sortby`, compiled to SQL for the rows of `target`
+pub fn compile_sort(query: &Query, target: SortTarget<'_>) -> Result<SqlSort, ApiError> {
+    let sortby = query.sortby.as_deref().unwrap_or_default();
+    Ok(SqlSort::new(sortby, target)?)
+}
+
+/// Fail with `UnknownProperty` for the first of the request's `properties` that is not
+/// `known`
+pub fn check_properties(query: &Query, known: impl Fn(&str) -> bool) -> Result<(), ApiError> {
+    match query
+        .properties
+        .iter()
+        .flatten()
+        .find(|property| !known(property))
+    {
+        Some(unknown) => Err(ApiError::UnknownProperty(unknown.clone())),
+        None => Ok(()),
+    }
+}
+
+/// Entity tag of the version of a project feature last updated at `last_updated`
+pub fn feature_etag(last_updated: DateTime<Utc>) -> String {
+    format!("\"{}\"", last_updated.timestamp_micros())
+}
+
+/// Fail unless the request has an `If-Match` header that matches `etag`, so that edits
+/// are always made to the version of the feature the client last read
+pub fn check_if_match(req: &HttpRequest, etag: &str) -> Result<(), ApiError> {
+    let Some(if_match) = req.headers().get(IF_MATCH) else {
+        return Err(ApiError::PreconditionRequired);
+    };
+    let matches = if_match.to_str().is_ok_and(|if_match| {
+        if_match
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag == etag)
+    });
+    match matches {
+        true => Ok(()),
+        false => Err(ApiError::PreconditionFailed),
+    }
+}
+
+/// CRS of the geometry in a request body, from its `Content-Crs` header, CRS84 by default
+pub fn content_crs(req: &HttpRequest) -> Result<Crs, ApiError> {
+    let Some(header) = req.headers().get("content-crs") else {
+        return Ok(Crs::default());
+    };
+    header
+        .to_str()
+        .ok()
+        .map(|uri| uri.trim().trim_start_matches('<').trim_end_matches('>'))
+        .and_then(|uri| Crs::from_str(uri).ok())
+        .ok_or_else(|| ApiError::InvalidGeoJson(format!("unrecognised Content-Crs {header:?}")))
+}
+
+/// A project feature of the project, failing with `404 Not Found` when there is none or
+/// it has been deleted
+pub async fn select_project_feature(
+    repo: &PostgresRepo,
+    project_id: ProjectId,
+    id: ProjectFeatureId,
+    crs: &Crs,
+) -> Result<ProjectFeature, ApiError> {
+    let params = repo::project_features::SelectOneParams { project_id, crs };
+    repo.select_one_with_params::<ProjectFeature, _>(&id, &params)
+        .await?
+        .filter(|feature| feature.properties.status != Status::Deleted)
+        .ok_or(ApiError::ProjectFeatureNotFound(id))
+}
+
+/// Update a project feature, provided it is still the version last updated at
+/// `last_updated`, responding with the entity tag of the new version
+pub async fn update_project_feature(
+    repo: &PostgresRepo,
+    dto: &FeatureEditDTO,
+    user_id: UserId,
+    project_id: ProjectId,
+    id: ProjectFeatureId,
+    last_updated: DateTime<Utc>,
+) -> Result<HttpResponse, ApiError> {
+    let last_updated = match repo
+        .update(&(dto, user_id, project_id, id, last_updated))
+        .await
+    {
+        Ok(last_updated) => last_updated,
+        // Another request updated the feature after it was read
+        Err(RepositoryError::RowNotFound) => return Err(ApiError::PreconditionFailed),
+        Err(error) => return Err(error.into()),
+    };
+    Ok(HttpResponse::NoContent()
+        .insert_header((ETAG, feature_etag(last_updated)))
+        .finish())
+}
+
+/// The members of a GeoJSON feature in a request body that are written to a project
+/// feature. Members that are left out are `None` or missing from `properties`.
+pub struct FeatureBody {
+    pub name: Option<String>,
+    pub primary: Option<bool>,
+    pub geometry: Option<geojson::Geometry>,
+    /// User defined properties, without the read only system properties
+    pub properties: Map<String, Value>,
+}
+
+impl FeatureBody {
+    pub fn parse(body: &[u8]) -> Result<Self, ApiError> {
+        let invalid = ApiError::InvalidGeoJson;
+        let mut document: Map<String, Value> =
+            serde_json::from_slice(body).map_err(|e| invalid(e.to_string()))?;
+        if let Some(kind) = document.get("type")
+            && kind != "Feature"
+        {
+            return Err(invalid(format!("expected a Feature, found {kind}")));
+        }
+        let geometry = match document.remove("geometry") {
+            None => None,
+            Some(Value::Null) => {
+                return Err(invalid("project features need a geometry".to_string()));
+            }
+            Some(geometry) => {
+                Some(serde_json::from_value(geometry).map_err(|e| invalid(e.to_string()))?)
+            }
+        };
+        let mut properties = match document.remove("properties") {
+            None | Some(Value::Null) => Map::new(),
+            Some(Value::Object(properties)) => properties,
+            Some(other) => {
+                return Err(invalid(format!(
+                    "properties must be an object, found {other}"
+                )));
+            }
+        };
+        let name = match properties.remove("name") {
+            None => None,
+            Some(Value::String(name)) => Some(name),
+            Some(other) => return Err(invalid(format!("name must be a string, found {other}"))),
+        };
+        let primary = match properties.remove("is_primary") {
+            None => None,
+            Some(Value::Bool(primary)) => Some(primary),
+            Some(other) => {
+                return Err(invalid(format!(
+                    "is_primary must be a boolean, found {other}"
+                )));
+            }
+        };
+        for property in SYSTEM_PROPERTIES {
+            properties.remove(property);
+        }
+        Ok(Self {
+            name,
+            primary,
+            geometry,
+            properties,
+        })
+    }
+}
+
+/// WKB of a request geometry once checked against the collection's geometry type the
+/// same way uploads are merged, keeping Z values only if the collection stores them
+pub async fn collection_geometry_wkb(
+    repo: &PostgresRepo,
+    collection_id: ProjectCollectionId,
+    geometry: &geojson::Geometry,
+) -> Result<Vec<u8>, ApiError> {
+    let expected_type: OGRwkbGeometryType::Type =
+        repo.get_collection_geom_type(collection_id).await?.into();
+    let keep_z = repo
+        .get_collection_coordinate_dimension(collection_id)
+        .await?
+        == CoordinateDimension::Xyz;
+    let document = json!({"type": "Feature", "properties": {}, "geometry": geometry});
+    let bytes = serde_json::to_vec(&document).context("failed to serialise geometry")?;
+    let ds = dataset_from_bytes(bytes, "geojson")?;
+    let mut layer = select_layer_for_type(&ds, None, expected_type)?;
+    let geom = merge_layer_geometries(&mut layer, expected_type)?;
+    let wkb = conform_dimension(&geom, keep_z)?
+        .wkb()
+        .context("failed to create WKB")?;
+    Ok(wkb)
+}
@@ -0,0 +1,43 @@
+use actix_web::{HttpRequest, HttpResponse, delete, web};
+use domain::{FeatureId, ProjectCollectionId, ProjectFeatureId, ProjectId, enums::Status};
+use ogcapi_types::common::Crs;
+
+use crate::{
+    AuthenticatedUser,
+    handlers::{
+        ApiError,
+        ogc_api::features::common::{check_if_match, feature_etag, select_project_feature},
+    },
+    postgres::PostgresRepo,
+    repo::RepositoryError,
+};
+
+/// Delete a feature of a project collection, which keeps it with a `DELETED` status
+#[delete("/{collectionId}/items/{featureId}")]
+#[tracing::instrument(skip(req, repo, path, user))]
+pub async fn delete_project_feature(
+    req: HttpRequest,
+    repo: web::Data<PostgresRepo>,
+    path: web::Path<(ProjectId, ProjectCollectionId, FeatureId)>,
+    user: Option<web::ReqData<AuthenticatedUser>>,
+) -> Result<HttpResponse, ApiError> {
+    let (project_id, collection_id, feature_id) = path.into_inner();
+    let user = user.ok_or(ApiError::Unauthenticated)?;
+    let id = ProjectFeatureId {
+        collection_id,
+        feature_id,
+    };
+    let current = select_project_feature(&repo, project_id, id, &Crs::default()).await?;
+    let last_updated = current.properties.last_updated;
+    check_if_match(&req, &feature_etag(last_updated))?;
+
+    match repo
+        .update(&(Status::Deleted, user.id, project_id, id, last_updated))
+        .await
+    {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        // Another request updated the feature after it was read
+        Err(RepositoryError::RowNotFound) => Err(ApiError::PreconditionFailed),
+        Err(error) => Err(error.into()),
+    }
+}
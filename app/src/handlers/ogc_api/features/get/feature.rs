@@ -46,7 +46,7 @@ pub async fn get_feature(
         base_url, URLS.ogc_api.base, collection_id
     );
 
-    let Query { crs, .. } = query.into_inner();
+    let crs = query.crs.clone();
     let mut response = match collection_id {
         CollectionId::Projects => {
             let params = project_features::SelectOneParams {
@@ -54,7 +54,7 @@ pub async fn get_feature(
                 crs: &crs,
             };
 
-            let feature = retrieve_feature_from_database(
+            let mut feature = retrieve_feature_from_database(
                 &repo,
                 collection_id,
                 feature_id,
@@ -62,6 +62,7 @@ pub async fn get_feature(
                 &params,
             )
             .await?;
+            query.select_members(&mut feature);
             HttpResponse::Ok().json(feature)
         }
 
@@ -81,7 +82,8 @@ pub async fn get_feature(
                 .select_one_with_params(feature_id, &params)
                 .await?
                 .ok_or_else(|| ApiError::FeatureNotFound(feature_id))?;
-            let ogc_feature = feature.into_ogc_feature(collection_url);
+            let mut ogc_feature = feature.into_ogc_feature(collection_url);
+            query.select_members(&mut ogc_feature);
             HttpResponse::Ok().json(ogc_feature)
         }
         _ => return Err(ApiError::CollectionNotFound),
@@ -6,10 +6,13 @@ use crate::{
     constants::GIS_DATA_SCHEMA,
     handlers::{
         ApiError,
-        ogc_api::features::{Query, common::append_crs_header},
+        ogc_api::features::{
+            Query,
+            common::{append_crs_header, check_properties, compile_filter, compile_sort},
+        },
     },
     helpers::get_base_url,
-    postgres::PostgresRepo,
+    postgres::{FilterTarget, PostgresRepo, SortTarget, TableColumn},
     repo::{features, project},
     streaming::ogc_feature_collection_byte_stream,
 };
@@ -23,6 +26,7 @@ use domain::{
     enums::{CollectionId, Status},
     project::Project,
 };
+use ogc::features::filtering::cql2::Cql2Error;
 use ogcapi_types::common::media_type::GEO_JSON;
 
 #[utoipa::path(
@@ -56,23 +60,35 @@ pub async fn get_features(
     response_builder.content_type(GEO_JSON);
     let mut response = match collection_id {
         CollectionId::Projects => {
+            if query.filter.is_some() {
+                return Err(Cql2Error("the projects collection cannot be filtered".into()).into());
+            }
             let status: Option<Vec<Status>> = query.status.as_ref().map(|statuses| {
                 statuses
                     .iter()
                     .filter_map(|s| Status::from_str(s).ok())
                     .collect()
             });
+            check_properties(&query, |property| {
+                domain::project::PROPERTIES.contains(&property)
+            })?;
             let params = project::SelectAllParams {
                 limit: query.limit,
                 crs: &query.crs,
                 _bbox: query.bbox.as_ref(),
                 _bbox_crs: query.bbox_crs.as_ref(),
                 status,
+                sort: compile_sort(&query, SortTarget::Projects)?,
+                skip_geometry: query.skips_geometry(),
             };
             let (projects, _) = repo.select_all_with_params::<Project>(params).await?;
             let features: Vec<ogc::Feature> = projects
                 .into_iter()
-                .map(|p| p.into_ogc_feature(collection_url.clone()))
+                .map(|p| {
+                    let mut feature = p.into_ogc_feature(collection_url.clone());
+                    query.select_members(&mut feature);
+                    feature
+                })
                 .collect();
             let collection =
                 ogc::FeatureCollection::new(&collection_url, collection_id.to_string(), features);
@@ -83,6 +99,9 @@ pub async fn get_features(
                 .select_one(table.clone())
                 .await?
                 .ok_or(ApiError::CollectionNotFound)?;
+            let columns = repo.select_table_columns(GIS_DATA_SCHEMA, &table).await?;
+            let filter = compile_filter(&query, FilterTarget::Table(&columns))?;
+            let sort = compile_sort(&query, SortTarget::Table(&columns))?;
 
             let params = features::SelectAllParams {
                 schema: GIS_DATA_SCHEMA,
@@ -92,6 +111,10 @@ pub async fn get_features(
                 bbox_crs: query.bbox_crs.clone(),
                 crs: query.crs.clone(),
                 offset: query.offset,
+                filter,
+                sort,
+                properties: property_columns(&columns, query.properties.as_deref())?,
+                skip_geometry: query.skips_geometry(),
             };
             let features = repo.select_all_with_params_streaming::<domain::Feature>(params);
             let bytes = ogc_feature_collection_byte_stream(
@@ -108,3 +131,27 @@ pub async fn get_features(
     append_crs_header(&mut response, &request_crs);
     Ok(response)
 }
+
+/// Columns of a `gis_data` table to select as its features' properties: those named by
+/// a `properties` parameter, or every column besides the id and geometries
+fn property_columns(
+    columns: &[TableColumn],
+    properties: Option<&[String]>,
+) -> Result<Vec<String>, ApiError> {
+    let property_columns = columns
+        .iter()
+        .filter(|column| column.name != "gid" && column.data_type != "geometry")
+        .map(|column| column.name.clone());
+    let Some(properties) = properties else {
+        return Ok(property_columns.collect());
+    };
+    if let Some(unknown) = properties
+        .iter()
+        .find(|property| !columns.iter().any(|column| &column.name == *property))
+    {
+        return Err(ApiError::UnknownProperty(unknown.clone()));
+    }
+    Ok(property_columns
+        .filter(|column| properties.contains(column))
+        .collect())
+}
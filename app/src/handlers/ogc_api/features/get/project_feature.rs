@@ -4,7 +4,10 @@ use crate::{
         ApiError,
         ogc_api::features::{
             Query,
-            common::{append_crs_header, retrieve_feature_from_database},
+            common::{
+                append_crs_header, feature_etag, retrieve_feature_from_database,
+                select_project_feature,
+            },
         },
     },
     helpers::get_base_url,
@@ -13,10 +16,14 @@ use crate::{
 };
 use actix_web::{
     HttpRequest, HttpResponse, get,
+    http::header::ETAG,
     web::{self},
 };
 
-use domain::{FeatureId, ProjectId, enums::CollectionId, project::ProjectName};
+use domain::{
+    FeatureId, IntoOGCFeature, ProjectFeatureId, ProjectId, enums::CollectionId,
+    project::ProjectName,
+};
 
 #[get("/{collectionId}/items/{featureId}")]
 #[tracing::instrument(skip(repo, req, path, query))]
@@ -32,7 +39,7 @@ pub async fn get_project_feature(
         .await?
         .ok_or_else(|| ApiError::ProjectNotFound(project_id))?;
 
-    let Query { crs, .. } = query.into_inner();
+    let crs = query.crs.clone();
 
     let base_url = get_base_url(&req);
     let collection_url = format!(
@@ -44,10 +51,34 @@ pub async fn get_project_feature(
         crs: &crs,
     };
 
-    let feature =
-        retrieve_feature_from_database(&repo, collection_id, feature_id, collection_url, &params)
+    let mut response = match collection_id {
+        // Project features carry an entity tag so they can be edited with `If-Match`
+        CollectionId::ProjectCollection(collection_id) => {
+            let id = ProjectFeatureId {
+                collection_id,
+                feature_id,
+            };
+            let feature = select_project_feature(&repo, project_id, id, &crs).await?;
+            let etag = feature_etag(feature.properties.last_updated);
+            let mut ogc_feature = feature.into_ogc_feature(collection_url);
+            query.select_members(&mut ogc_feature);
+            HttpResponse::Ok()
+                .insert_header((ETAG, etag))
+                .json(ogc_feature)
+        }
+        collection_id => {
+            let mut feature = retrieve_feature_from_database(
+                &repo,
+                collection_id,
+                feature_id,
+                collection_url,
+                &params,
+            )
             .await?;
-    let mut response = HttpResponse::Ok().json(feature);
+            query.select_members(&mut feature);
+            HttpResponse::Ok().json(feature)
+        }
+    };
     append_crs_header(&mut response, &crs);
     Ok(response)
 }
@@ -4,10 +4,13 @@ use crate::{
     URLS,
     handlers::{
         ApiError,
-        ogc_api::features::{Query, common::append_crs_header},
+        ogc_api::features::{
+            Query,
+            common::{append_crs_header, check_properties, compile_filter, compile_sort},
+        },
     },
     helpers::get_base_url,
-    postgres::PostgresRepo,
+    postgres::{FilterTarget, PostgresRepo, SortTarget},
     repo::{project_collections, project_features::SelectAllParams},
     streaming::ogc_feature_collection_byte_stream,
 };
@@ -17,8 +20,8 @@ use actix_web::{
 };
 
 use domain::{
-    ProjectCollection, ProjectCollectionId, ProjectFeature, ProjectId, enums::Status,
-    project::ProjectName,
+    ProjectCollection, ProjectCollectionId, ProjectFeature, ProjectId, SYSTEM_PROPERTIES,
+    enums::Status, project::ProjectName,
 };
 
 use ogcapi_types::common::media_type::GEO_JSON;
@@ -60,6 +63,26 @@ pub async fn get_project_features(
         base_url, URLS.ogc_api.base, URLS.ogc_api.project, project_id, collection_id
     );
 
+    // Features can only be sorted by or select user defined properties some feature has
+    let properties_keys: Vec<String> = match query.sortby.is_some() || query.properties.is_some() {
+        true => repo
+            .select_properties_keys(project_id, collection_id)
+            .await?
+            .into_iter()
+            .map(|properties_key| properties_key.key)
+            .collect(),
+        false => Vec::new(),
+    };
+    check_properties(&query, |property| {
+        SYSTEM_PROPERTIES.contains(&property) || properties_keys.iter().any(|key| key == property)
+    })?;
+    let properties = query.properties.as_ref().map(|properties| {
+        properties
+            .iter()
+            .filter(|property| !SYSTEM_PROPERTIES.contains(&property.as_str()))
+            .cloned()
+            .collect()
+    });
     let params = SelectAllParams {
         limit: query.limit,
         collection_id,
@@ -69,6 +92,10 @@ pub async fn get_project_features(
         bbox_crs: query.bbox_crs.clone(),
         offset: query.offset,
         status,
+        filter: compile_filter(&query, FilterTarget::ProjectFeatures)?,
+        sort: compile_sort(&query, SortTarget::ProjectFeatures(&properties_keys))?,
+        properties,
+        skip_geometry: query.skips_geometry(),
     };
 
     let features = repo.select_all_with_params_streaming::<ProjectFeature>(params);
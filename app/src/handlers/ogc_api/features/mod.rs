@@ -1,4 +1,8 @@
 mod common;
+pub mod delete;
 pub mod get;
+pub mod patch;
+pub mod post;
+pub mod put;
 mod query;
 pub use query::Query;
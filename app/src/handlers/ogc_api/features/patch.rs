@@ -0,0 +1,69 @@
+use actix_web::{HttpRequest, HttpResponse, patch, web};
+use domain::{FeatureEditDTO, FeatureId, ProjectCollectionId, ProjectFeatureId, ProjectId};
+use ogcapi_types::common::Crs;
+use serde_json::Value;
+
+use crate::{
+    AuthenticatedUser, UploadSettings,
+    handlers::{
+        ApiError,
+        ogc_api::features::common::{
+            FeatureBody, check_if_match, collection_geometry_wkb, content_crs, feature_etag,
+            select_project_feature, update_project_feature,
+        },
+        upload::read_body,
+    },
+    postgres::PostgresRepo,
+};
+
+/// Update a feature of a project collection from a partial GeoJSON feature, merged as a
+/// JSON merge patch: only the members given change, and properties set to `null` are
+/// removed.
+#[patch("/{collectionId}/items/{featureId}")]
+#[tracing::instrument(skip(req, repo, path, user, payload, upload_settings))]
+pub async fn patch_project_feature(
+    req: HttpRequest,
+    repo: web::Data<PostgresRepo>,
+    path: web::Path<(ProjectId, ProjectCollectionId, FeatureId)>,
+    user: Option<web::ReqData<AuthenticatedUser>>,
+    payload: web::Payload,
+    upload_settings: web::Data<UploadSettings>,
+) -> Result<HttpResponse, ApiError> {
+    let (project_id, collection_id, feature_id) = path.into_inner();
+    let user = user.ok_or(ApiError::Unauthenticated)?;
+    let id = ProjectFeatureId {
+        collection_id,
+        feature_id,
+    };
+    let current = select_project_feature(&repo, project_id, id, &Crs::default()).await?;
+    let last_updated = current.properties.last_updated;
+    check_if_match(&req, &feature_etag(last_updated))?;
+
+    let srid = content_crs(&req)?.as_srid();
+    let body = read_body(payload, upload_settings.geojson_upload_limit_bytes).await?;
+    let FeatureBody {
+        name,
+        primary,
+        geometry,
+        properties: patch,
+    } = FeatureBody::parse(&body)?;
+    let mut properties = current.properties_map;
+    for (key, value) in patch {
+        match value {
+            Value::Null => properties.remove(&key),
+            value => properties.insert(key, value),
+        };
+    }
+    let geom_wkb = match geometry {
+        Some(geometry) => Some(collection_geometry_wkb(&repo, collection_id, &geometry).await?),
+        None => None,
+    };
+    let dto = FeatureEditDTO {
+        name,
+        primary,
+        geom_wkb,
+        srid,
+        properties,
+    };
+    update_project_feature(&repo, &dto, user.id, project_id, id, last_updated).await
+}
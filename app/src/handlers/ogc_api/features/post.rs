@@ -0,0 +1,83 @@
+use actix_web::{HttpRequest, HttpResponse, http::header::LOCATION, post, web};
+use domain::{
+    FeatureId, FeatureInputDTO, ProjectCollection, ProjectCollectionId, ProjectId,
+    project::ProjectName,
+};
+
+use crate::{
+    AuthenticatedUser, URLS, UploadSettings,
+    handlers::{
+        ApiError,
+        ogc_api::features::common::{FeatureBody, collection_geometry_wkb, content_crs},
+        upload::read_body,
+    },
+    helpers::get_base_url,
+    postgres::PostgresRepo,
+    repo::project_collections,
+};
+
+/// Add a feature to a project collection from a GeoJSON feature, whose `name` property
+/// is required. The geometry is in the CRS of the `Content-Crs` header, CRS84 by default.
+#[post("/{collectionId}/items")]
+#[tracing::instrument(skip(req, repo, path, user, payload, upload_settings))]
+pub async fn post_project_feature(
+    req: HttpRequest,
+    repo: web::Data<PostgresRepo>,
+    path: web::Path<(ProjectId, ProjectCollectionId)>,
+    user: Option<web::ReqData<AuthenticatedUser>>,
+    payload: web::Payload,
+    upload_settings: web::Data<UploadSettings>,
+) -> Result<HttpResponse, ApiError> {
+    let (project_id, collection_id) = path.into_inner();
+    let user = user.ok_or(ApiError::Unauthenticated)?;
+    let _project: ProjectName = repo
+        .select_one(project_id)
+        .await?
+        .ok_or(ApiError::ProjectNotFound(project_id))?;
+    repo.select_one_with_params::<ProjectCollection, _>(
+        collection_id,
+        &project_collections::SelectOneParams {
+            project_id,
+            status: None,
+        },
+    )
+    .await?
+    .ok_or(ApiError::ProjectCollectionNotFound(collection_id))?;
+
+    let srid = content_crs(&req)?.as_srid();
+    let body = read_body(payload, upload_settings.geojson_upload_limit_bytes).await?;
+    let FeatureBody {
+        name,
+        primary,
+        geometry,
+        properties,
+    } = FeatureBody::parse(&body)?;
+    let name =
+        name.ok_or_else(|| ApiError::InvalidGeoJson("a 'name' property is required".to_string()))?;
+    let geometry = geometry
+        .ok_or_else(|| ApiError::InvalidGeoJson("project features need a geometry".to_string()))?;
+    let input_dto = FeatureInputDTO {
+        name,
+        primary,
+        geom_wkb: collection_geometry_wkb(&repo, collection_id, &geometry).await?,
+        srid,
+        target_srid: repo.get_project_srid(project_id).await?.unwrap_or(srid),
+        properties,
+    };
+    let feature_id: FeatureId = repo
+        .insert(&(&input_dto, project_id, collection_id, user.id))
+        .await?;
+
+    let location = format!(
+        "{}{}{}/{}/collections/{}/items/{}",
+        get_base_url(&req),
+        URLS.ogc_api.base,
+        URLS.ogc_api.project,
+        project_id,
+        collection_id,
+        feature_id.0
+    );
+    Ok(HttpResponse::Created()
+        .insert_header((LOCATION, location))
+        .finish())
+}
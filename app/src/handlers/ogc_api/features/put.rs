@@ -0,0 +1,66 @@
+use actix_web::{HttpRequest, HttpResponse, put, web};
+use domain::{FeatureEditDTO, FeatureId, ProjectCollectionId, ProjectFeatureId, ProjectId};
+use ogcapi_types::common::Crs;
+
+use crate::{
+    AuthenticatedUser, UploadSettings,
+    handlers::{
+        ApiError,
+        ogc_api::features::common::{
+            FeatureBody, check_if_match, collection_geometry_wkb, content_crs, feature_etag,
+            select_project_feature, update_project_feature,
+        },
+        upload::read_body,
+    },
+    postgres::PostgresRepo,
+};
+
+/// Replace a feature of a project collection with a GeoJSON feature, which needs a
+/// geometry, a `name` and `is_primary`. User defined properties left out are removed.
+#[put("/{collectionId}/items/{featureId}")]
+#[tracing::instrument(skip(req, repo, path, user, payload, upload_settings))]
+pub async fn put_project_feature(
+    req: HttpRequest,
+    repo: web::Data<PostgresRepo>,
+    path: web::Path<(ProjectId, ProjectCollectionId, FeatureId)>,
+    user: Option<web::ReqData<AuthenticatedUser>>,
+    payload: web::Payload,
+    upload_settings: web::Data<UploadSettings>,
+) -> Result<HttpResponse, ApiError> {
+    let (project_id, collection_id, feature_id) = path.into_inner();
+    let user = user.ok_or(ApiError::Unauthenticated)?;
+    let id = ProjectFeatureId {
+        collection_id,
+        feature_id,
+    };
+    let current = select_project_feature(&repo, project_id, id, &Crs::default()).await?;
+    check_if_match(&req, &feature_etag(current.properties.last_updated))?;
+
+    let srid = content_crs(&req)?.as_srid();
+    let body = read_body(payload, upload_settings.geojson_upload_limit_bytes).await?;
+    let FeatureBody {
+        name,
+        primary,
+        geometry,
+        properties,
+    } = FeatureBody::parse(&body)?;
+    let geometry = geometry.ok_or(ApiError::MissingFeatureMember("a geometry"))?;
+    let name = name.ok_or(ApiError::MissingFeatureMember("a name"))?;
+    let primary = primary.ok_or(ApiError::MissingFeatureMember("is_primary"))?;
+    let dto = FeatureEditDTO {
+        name: Some(name),
+        primary: Some(primary),
+        geom_wkb: Some(collection_geometry_wkb(&repo, collection_id, &geometry).await?),
+        srid,
+        properties,
+    };
+    update_project_feature(
+        &repo,
+        &dto,
+        user.id,
+        project_id,
+        id,
+        current.properties.last_updated,
+    )
+    .await
+}
@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use ogc::features::filtering::cql2::FilterLang;
 use ogcapi_types::common::Crs;
 use serde::{Deserialize, Serialize};
 use serde_with::{DisplayFromStr, StringWithSeparator, formats::CommaSeparator};
@@ -57,4 +58,60 @@ pub struct Query {
     pub datetime: Option<DateTime<Utc>>,
     #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, String>>")]
     pub status: Option<Vec<String>>,
+
+    /// A CQL2 expression the selected features must match, encoded as `filter-lang`.
+    ///
+    /// Example: `model = 'V150' AND S_INTERSECTS(geometry, BBOX(-3.2, 51.4, -3.0, 51.6))`
+    #[param(style = Form, required = false)]
+    pub filter: Option<String>,
+
+    /// Encoding of `filter`, `cql2-text` by default
+    #[param(style = Form, required = false)]
+    pub filter_lang: Option<FilterLang>,
+
+    /// CRS of any geometry in `filter`, CRS84 by default
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[schema(value_type = String)]
+    pub filter_crs: Option<Crs>,
+
+    /// Properties to sort the features by, in order, each descending when prefixed
+    /// with `-`. Features with equal sort keys are ordered by id.
+    ///
+    /// Example: `-last_updated,name`
+    #[param(style = Form, explode = false, value_type = Option<String>, required = false)]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, String>>")]
+    pub sortby: Option<Vec<String>>,
+
+    /// Properties to include in each feature, all of them by default
+    ///
+    /// Example: `name,status`
+    #[param(style = Form, explode = false, value_type = Option<String>, required = false)]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, String>>")]
+    pub properties: Option<Vec<String>>,
+
+    /// Leave the geometry of each feature out of the response, as `null`
+    #[param(style = Form, required = false)]
+    #[serde(rename = "skipGeometry")]
+    pub skip_geometry: Option<bool>,
+}
+
+impl Query {
+    pub fn skips_geometry(&self) -> bool {
+        self.skip_geometry.unwrap_or(false)
+    }
+
+    /// Drop the members of a feature left out by `properties` and `skipGeometry` that its
+    /// query could not leave out, such as the system properties of projects and project
+    /// features, which are always selected
+    pub fn select_members(&self, feature: &mut ogc::Feature) {
+        if let Some(properties) = &self.properties {
+            feature
+                .properties
+                .retain(|name, _| properties.iter().any(|property| property == name));
+        }
+        if self.skips_geometry() {
+            feature.geometry = None;
+        }
+    }
 }
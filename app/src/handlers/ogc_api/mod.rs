@@ -8,10 +8,11 @@ pub use collections::{
 };
 pub mod features;
 pub use features::{
-    get::feature::get_feature, get::features::get_features,
+    delete::delete_project_feature, get::feature::get_feature, get::features::get_features,
     get::project_feature::get_project_feature, get::project_features::get_project_features,
+    patch::patch_project_feature, post::post_project_feature, put::put_project_feature,
 };
 mod openapi;
 pub use openapi::get_openapi;
 mod queryables;
-pub use queryables::get_collection_queryables;
+pub use queryables::{get_collection_queryables, get_project_collection_queryables};
@@ -1,14 +1,27 @@
 use actix_web::{HttpRequest, HttpResponse, get, web};
-use domain::enums::CollectionId;
+use domain::{
+    GisDataTable, ProjectCollection, ProjectCollectionId, ProjectId, enums::CollectionId,
+    project::ProjectName,
+};
 use ogc::features::filtering::{QueryableProperty, Queryables};
 use std::collections::HashMap;
 
-use crate::{URLS, errors::ApiError, helpers::get_base_url};
+use crate::{
+    URLS,
+    constants::GIS_DATA_SCHEMA,
+    errors::ApiError,
+    helpers::get_base_url,
+    postgres::{FilterTarget, PostgresRepo, Queryable},
+    repo::project_collections::SelectOneParams,
+};
+
+const JSON_SCHEMA: &str = "https://json-schema.org/draft/2020-12/schema";
 
 #[get("/{collectionId}/queryables")]
-#[tracing::instrument(skip(req, collection_id))]
+#[tracing::instrument(skip(req, repo, collection_id))]
 pub async fn get_collection_queryables(
     req: HttpRequest,
+    repo: web::Data<PostgresRepo>,
     collection_id: web::Path<CollectionId>,
 ) -> Result<HttpResponse, ApiError> {
     let base_url = get_base_url(&req);
@@ -20,39 +33,156 @@ pub async fn get_collection_queryables(
         collection_id.as_ref()
     );
 
-    match collection_id.into_inner() {
+    let queryables = match collection_id.into_inner() {
         CollectionId::Projects => {
             let mut properties = HashMap::new();
 
             // Add status queryable property
             properties.insert(
                 "status".to_string(),
-                QueryableProperty {
-                    title: Some("Project Status".to_string()),
-                    description: Some("The status of the project".to_string()),
-                    r#type: Some("string".to_string()),
-                    r#enum: Some(vec![
-                        "ACTIVE".to_string(),
-                        "ARCHIVED".to_string(),
-                        "DELETED".to_string(),
-                    ]),
-                },
+                status_property("The status of the project"),
             );
 
-            let queryables = Queryables {
-                schema: "https://json-schema.org/draft/2020-12/schema".to_string(),
+            Queryables {
+                schema: JSON_SCHEMA.to_string(),
                 id: queryables_url,
                 r#type: "object".to_string(),
                 title: Some("Projects".to_string()),
                 description: Some("Queryable properties for the Projects collection".to_string()),
                 properties,
                 additional_properties: false,
-            };
+            }
+        }
+        CollectionId::DatabaseTable(table) => {
+            let _table_row: GisDataTable = repo
+                .select_one(table.clone())
+                .await?
+                .ok_or_else(|| ApiError::GisDataTableNotFound(table.clone()))?;
+            let columns = repo.select_table_columns(GIS_DATA_SCHEMA, &table).await?;
+            let properties = FilterTarget::Table(&columns)
+                .queryables()
+                .into_iter()
+                .map(queryable_property)
+                .collect();
 
-            Ok(HttpResponse::Ok()
-                .content_type("application/schema+json")
-                .json(queryables))
+            Queryables {
+                schema: JSON_SCHEMA.to_string(),
+                id: queryables_url,
+                r#type: "object".to_string(),
+                title: Some(table.to_string()),
+                description: Some(format!("Queryable properties for the {table} table")),
+                properties,
+                additional_properties: false,
+            }
         }
-        _ => Err(ApiError::NotFound),
+        CollectionId::ProjectCollection(_) => return Err(ApiError::CollectionNotFound),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/schema+json")
+        .json(queryables))
+}
+
+/// Queryables of a project collection: the system properties of its features and every
+/// key of their user defined properties, typed from a sampled value.
+#[get("/{collectionId}/queryables")]
+#[tracing::instrument(skip(req, repo, path))]
+pub async fn get_project_collection_queryables(
+    req: HttpRequest,
+    repo: web::Data<PostgresRepo>,
+    path: web::Path<(ProjectId, ProjectCollectionId)>,
+) -> Result<HttpResponse, ApiError> {
+    let (project_id, collection_id) = path.into_inner();
+    let _project: ProjectName = repo
+        .select_one(project_id)
+        .await?
+        .ok_or(ApiError::ProjectNotFound(project_id))?;
+    let collection = repo
+        .select_one_with_params::<ProjectCollection, _>(
+            collection_id,
+            &SelectOneParams {
+                project_id,
+                status: None,
+            },
+        )
+        .await?
+        .ok_or(ApiError::ProjectCollectionNotFound(collection_id))?;
+    let base_url = get_base_url(&req);
+    let queryables_url = format!(
+        "{}{}{}/{}{}/{}/queryables",
+        base_url,
+        URLS.ogc_api.base,
+        URLS.ogc_api.project,
+        project_id,
+        URLS.ogc_api.collections,
+        collection_id
+    );
+
+    let mut properties: HashMap<_, _> = FilterTarget::ProjectFeatures
+        .queryables()
+        .into_iter()
+        .map(queryable_property)
+        .collect();
+    properties.insert(
+        "status".to_string(),
+        status_property("The status of the feature"),
+    );
+    for key in repo
+        .select_properties_keys(project_id, collection_id)
+        .await?
+    {
+        let r#type = match key.json_type.as_str() {
+            "null" => None,
+            json_type => Some(json_type.to_string()),
+        };
+        properties.entry(key.key).or_insert(QueryableProperty {
+            title: None,
+            description: None,
+            r#type,
+            format: None,
+            r#enum: None,
+        });
+    }
+
+    let queryables = Queryables {
+        schema: JSON_SCHEMA.to_string(),
+        id: queryables_url,
+        r#type: "object".to_string(),
+        description: Some(format!(
+            "Queryable properties for the {} collection",
+            collection.title
+        )),
+        title: Some(collection.title),
+        properties,
+        // Any key of the features' properties can be filtered on
+        additional_properties: true,
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/schema+json")
+        .json(queryables))
+}
+
+fn queryable_property(queryable: Queryable) -> (String, QueryableProperty) {
+    let property = QueryableProperty {
+        title: None,
+        description: None,
+        r#type: queryable.schema_type.map(str::to_string),
+        format: queryable.format.map(str::to_string),
+        r#enum: None,
+    };
+    (queryable.name, property)
+}
+
+fn status_property(description: &str) -> QueryableProperty {
+    QueryableProperty {
+        title: Some("Status".to_string()),
+        description: Some(description.to_string()),
+        r#type: Some("string".to_string()),
+        format: None,
+        r#enum: Some(vec![
+            "ACTIVE".to_string(),
+            "ARCHIVED".to_string(),
+            "DELETED".to_string(),
+        ]),
     }
 }
@@ -0,0 +1,45 @@
+//! Helpers shared by the handlers that read uploaded request bodies
+use actix_web::web;
+use anyhow::Context;
+use futures::StreamExt;
+use gdal::{Dataset, vsi};
+use geo::virtual_shapefile::ShapefileError;
+use uuid::Uuid;
+
+use crate::errors::ApiError;
+
+/// Read a request body, failing with `413 Payload Too Large` once it exceeds `limit` bytes
+pub async fn read_body(mut payload: web::Payload, limit: usize) -> Result<web::BytesMut, ApiError> {
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.context("failed to read request body")?;
+        if body.len() + chunk.len() > limit {
+            return Err(ApiError::PayloadTooLarge(limit));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Open an in-memory dataset, e.g. a GeoJSON request body, through a virtual file.
+pub fn dataset_from_bytes(bytes: Vec<u8>, extension: &str) -> Result<Dataset, ShapefileError> {
+    let path = format!("/vsimem/{}.{extension}", Uuid::new_v4());
+    vsi::create_mem_file(&path, bytes)
+        .context(format!("failed to create virtual {extension} file"))
+        .map_err(ShapefileError::UnexpectedError)?;
+    let ds = Dataset::open(&path)
+        .context(format!("failed to open {extension} dataset"))
+        .map_err(ShapefileError::InvalidData)?;
+    if ds.layer_count() == 0 {
+        let _ = vsi::unlink_mem_file(&path);
+        return Err(ShapefileError::InvalidData(anyhow::anyhow!(
+            "no layers in {extension} file"
+        )));
+    }
+    // Force GDAL to read the spatial refs before unlinking
+    for layer in ds.layers() {
+        let _srs = layer.spatial_ref();
+    }
+    let _ = vsi::unlink_mem_file(&path);
+    Ok(ds)
+}
@@ -0,0 +1,219 @@
+use chrono::{DateTime, Utc};
+use domain::{
+    FeatureId, ProjectCollectionId, ProjectId,
+    enums::{GeometryType, Status},
+};
+use futures::{SinkExt, TryStreamExt, channel::mpsc};
+use geo::export::{ExportFeature, ExportItem, LayerSchema};
+use serde_json::{Map, Value};
+
+use crate::{postgres::PostgresRepo, repo::RepositoryError};
+
+/// Projects without a CRS are exported in WGS 84
+const DEFAULT_EXPORT_SRID: i32 = 4326;
+
+/// A project's export whose features are read one collection at a time by
+/// [`PostgresRepo::send_streamed_export`], so they're never all held in memory.
+pub struct StreamedExport {
+    pub project_slug: String,
+    /// CRS all geometries are transformed to
+    pub srid: i32,
+    project_id: ProjectId,
+    feature_id: Option<FeatureId>,
+    collections: Vec<ExportCollection>,
+}
+
+impl StreamedExport {
+    /// Slugs of the collections exported as layers, in the order they're sent. There are
+    /// none if the scope's collection doesn't exist, the scope's feature isn't active in
+    /// it, or the project has no active features.
+    pub fn layer_names(&self) -> impl Iterator<Item = &str> {
+        self.collections
+            .iter()
+            .map(|collection| collection.slug.as_str())
+    }
+}
+
+struct ExportCollection {
+    id: i32,
+    slug: String,
+    geometry_type: GeometryType,
+}
+
+/// Which features of a project to export, and in which CRS
+#[derive(Debug, Default)]
+pub struct ExportScope {
+    pub collection_id: Option<ProjectCollectionId>,
+    pub feature_id: Option<FeatureId>,
+    /// CRS to export in instead of the project's, e.g. 4326 for KML
+    pub srid: Option<i32>,
+}
+
+struct ExportFeatureRow {
+    id: i32,
+    name: String,
+    is_primary: bool,
+    status: Status,
+    added_by: String,
+    last_updated: DateTime<Utc>,
+    properties: Value,
+    geom: Vec<u8>,
+}
+
+impl From<ExportFeatureRow> for ExportFeature {
+    fn from(row: ExportFeatureRow) -> Self {
+        ExportFeature {
+            id: row.id,
+            name: row.name,
+            is_primary: row.is_primary,
+            status: row.status.to_string(),
+            added_by: row.added_by,
+            last_updated: row.last_updated,
+            properties: properties_object(row.properties),
+            geom_wkb: row.geom,
+        }
+    }
+}
+
+fn properties_object(properties: Value) -> Map<String, Value> {
+    match properties {
+        Value::Object(properties) => properties,
+        _ => Map::new(),
+    }
+}
+
+impl PostgresRepo {
+    /// Select the collections of a project to export within `scope`, in the project's CRS
+    /// unless the scope gives one. Returns `None` if the project doesn't exist.
+    ///
+    /// A scope's collection is exported even when the project has no features in it,
+    /// otherwise only collections with active features are. A scope's feature is exported
+    /// alone, in its collection's layer.
+    #[tracing::instrument(skip(self))]
+    pub async fn select_streamed_export(
+        &self,
+        project_id: ProjectId,
+        scope: &ExportScope,
+    ) -> Result<Option<StreamedExport>, RepositoryError> {
+        let Some(project) = sqlx::query!(
+            "SELECT slug, crs_srid FROM app.projects WHERE id = $1",
+            project_id.0
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let collections = sqlx::query_as!(
+            ExportCollection,
+            r#"
+            SELECT c.id,
+                   c.slug,
+                   c.geometry_type AS "geometry_type: GeometryType"
+              FROM app.collections c
+             WHERE ($2::int IS NULL OR c.id = $2)
+               AND (
+                   ($2::int IS NOT NULL AND $3::int IS NULL)
+                   OR EXISTS (
+                        SELECT 1
+                          FROM app.project_features f
+                         WHERE f.collection_id = c.id
+                           AND f.project_id = $1
+                           AND f.status = 'ACTIVE'
+                           AND ($3::int IS NULL OR f.id = $3)
+                   )
+               )
+             ORDER BY c.id
+            "#,
+            project_id.0,
+            scope.collection_id.map(|id| id.0),
+            scope.feature_id.map(|id| id.0)
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        Ok(Some(StreamedExport {
+            project_slug: project.slug,
+            srid: scope
+                .srid
+                .or(project.crs_srid)
+                .unwrap_or(DEFAULT_EXPORT_SRID),
+            project_id,
+            feature_id: scope.feature_id,
+            collections,
+        }))
+    }
+
+    /// Send each collection of `export` to `sender` as a layer followed by its active
+    /// features.
+    ///
+    /// A collection's features are read twice: first only their properties and
+    /// dimensions to make the layer's schema, then in full as they're sent. Only the rows
+    /// `sender` buffers are held in memory, and reading stops once the receiver is
+    /// dropped.
+    #[tracing::instrument(skip(self, export, sender))]
+    pub async fn send_streamed_export(
+        &self,
+        export: &StreamedExport,
+        mut sender: mpsc::Sender<ExportItem>,
+    ) -> Result<(), RepositoryError> {
+        for collection in &export.collections {
+            let mut schema = LayerSchema::new(
+                collection.slug.clone(),
+                collection.geometry_type.clone().into(),
+            );
+            let mut rows = sqlx::query!(
+                r#"
+                SELECT properties,
+                       ST_Zmflag(geom) IN (2, 3) AS "has_z!"
+                  FROM app.project_features
+                 WHERE project_id = $1
+                   AND collection_id = $2
+                   AND status = 'ACTIVE'
+                   AND ($3::int IS NULL OR id = $3)
+                "#,
+                export.project_id.0,
+                collection.id,
+                export.feature_id.map(|id| id.0)
+            )
+            .fetch(&self.db_pool);
+            while let Some(row) = rows.try_next().await? {
+                schema.add(&properties_object(row.properties), row.has_z);
+            }
+            if sender.send(ExportItem::Layer(schema)).await.is_err() {
+                return Ok(());
+            }
+
+            let mut rows = sqlx::query_as!(
+                ExportFeatureRow,
+                r#"
+                SELECT f.id,
+                       f.name,
+                       f.is_primary,
+                       f.status AS "status: Status",
+                       concat_ws(' ', u.first_name, u.last_name) AS "added_by!",
+                       f.last_updated,
+                       f.properties,
+                       ST_AsBinary(ST_Transform(f.geom, $3::int)) AS "geom!"
+                  FROM app.project_features f
+                  JOIN app.users u ON u.id = f.added_by
+                 WHERE f.project_id = $1
+                   AND f.collection_id = $2
+                   AND f.status = 'ACTIVE'
+                   AND ($4::int IS NULL OR f.id = $4)
+                 ORDER BY f.id
+                "#,
+                export.project_id.0,
+                collection.id,
+                export.srid,
+                export.feature_id.map(|id| id.0)
+            )
+            .fetch(&self.db_pool);
+            while let Some(row) = rows.try_next().await? {
+                if sender.send(ExportItem::Feature(row.into())).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -2,10 +2,6 @@ use domain::{Feature, FeatureId};
 use futures::StreamExt;
 use serde_json::{Map, Value};
 use sqlx::{prelude::FromRow, types::Json};
-use std::{
-    collections::HashMap,
-    sync::{LazyLock, Mutex},
-};
 
 use crate::repo::{
     RepositoryError, StreamItem,
@@ -18,13 +14,35 @@ const ROWS: &str = r#"gid as id,
                to_jsonb(t) - 'gid' -'geom' as "properties",
                COUNT(*) OVER() as number_matched"#;
 
-static QUERY_CACHE: LazyLock<Mutex<HashMap<(String, String), &'static str>>> =
-    LazyLock::new(|| Mutex::new(HashMap::new()));
+/// Select columns of a feature, with its properties built from only `properties`
+fn select_rows(properties: &[String], skip_geometry: bool) -> String {
+    let geom = match skip_geometry {
+        true => "NULL::json",
+        false => "ST_AsGeoJSON(ST_Transform(geom, $1))::json",
+    };
+    let properties = match properties.is_empty() {
+        true => "'{}'::jsonb".to_string(),
+        false => {
+            let columns = properties
+                .iter()
+                .map(|column| format!("t.\"{}\"", column.replace('"', "\"\"")))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("(SELECT to_jsonb(p) FROM (SELECT {columns}) p)")
+        }
+    };
+    format!(
+        r#"gid as id,
+               {geom} as geom,
+               {properties} as "properties",
+               COUNT(*) OVER() as number_matched"#
+    )
+}
 
 #[derive(FromRow)]
 struct FeatureRow {
     id: i32,
-    geom: Json<geojson::Geometry>,
+    geom: Option<Json<geojson::Geometry>>,
     properties: Json<Map<String, Value>>,
     number_matched: i64,
 }
@@ -39,7 +57,7 @@ impl FeatureRow {
         } = self;
         Feature {
             id,
-            geom: geom.0,
+            geom: geom.map(|geom| geom.0),
             properties: properties.0,
         }
     }
@@ -92,53 +110,53 @@ impl SelectAllWithParamsStreaming for Feature {
             bbox_crs,
             crs,
             offset,
+            filter,
+            sort,
+            properties,
+            skip_geometry,
         } = params;
         let bbox = bbox.map(|bbox| match bbox {
             ogcapi_types::common::Bbox::Bbox2D(bbox) => bbox,
             ogcapi_types::common::Bbox::Bbox3D(bbox) => [bbox[0], bbox[1], bbox[3], bbox[4]],
         });
-        let cache_key = (schema.to_string(), table.as_ref().to_string());
-
-        // ToDo - Here we create the query and cache it so we can use it in the streaming response which is kind of annoying.
-        // We also have an inefficiency where we select the geometry and serialise it go Json and then discard it because we
-        // don't know in advance which columns we require.
-
-        // Check cache first
-        let query: &'static str = {
-            let mut cache = QUERY_CACHE.lock().unwrap();
+        // The sort's parameters come before the filter's, which vary in number
+        let order_by = sort.order_by("gid", 9);
+        let filter_condition = filter
+            .as_ref()
+            .map(|filter| format!("AND ({})", filter.condition(9 + sort.parameter_count())))
+            .unwrap_or_default();
 
-            if let Some(&cached_query) = cache.get(&cache_key) {
-                cached_query
-            } else {
-                // Build and cache the query
-                let query = format!(
-                    r#"
-        SELECT {ROWS}
+        let rows = select_rows(&properties, skip_geometry);
+        let query = format!(
+            r#"
+        SELECT {rows}
           FROM "{}"."{}" t
           WHERE ($2::float IS NULL OR (geom && ST_Transform(ST_MakeEnvelope($2, $3, $4, $5, $6), ST_SRID(geom))))
-          ORDER BY gid
+          {filter_condition}
+          ORDER BY {order_by}
           LIMIT $7
           OFFSET $8
         "#,
-                    schema,
-                    table.as_ref()
-                );
-                // Leak it to make it 'static
-                let leaked_query: &'static str = Box::leak(query.into_boxed_str());
-                cache.insert(cache_key, leaked_query);
-                leaked_query
-            }
-        };
-        sqlx::query_as::<_, FeatureRow>(query)
-            .bind(crs.as_srid())
-            .bind(bbox.map(|bbox| bbox[0]))
-            .bind(bbox.map(|bbox| bbox[1]))
-            .bind(bbox.map(|bbox| bbox[2]))
-            .bind(bbox.map(|bbox| bbox[3]))
-            .bind(bbox_crs.unwrap_or_default().as_srid())
-            .bind(limit.map(|l| l as i64))
-            .bind(offset.unwrap_or_default() as i32)
-            .fetch(executor)
+            schema,
+            table.as_ref()
+        );
+        executor
+            .fetch_owned::<FeatureRow, _>(query, move |query| {
+                let query = query
+                    .bind(crs.as_srid())
+                    .bind(bbox.map(|bbox| bbox[0]))
+                    .bind(bbox.map(|bbox| bbox[1]))
+                    .bind(bbox.map(|bbox| bbox[2]))
+                    .bind(bbox.map(|bbox| bbox[3]))
+                    .bind(bbox_crs.unwrap_or_default().as_srid())
+                    .bind(limit.map(|l| l as i64))
+                    .bind(offset.unwrap_or_default() as i32);
+                let query = sort.bind(query);
+                match filter {
+                    Some(filter) => filter.bind(query),
+                    None => query,
+                }
+            })
             .map(|res| {
                 let row = res?;
                 let number_matched = row.number_matched;
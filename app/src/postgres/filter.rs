@@ -0,0 +1,577 @@
+//! CQL2 filters compiled to SQL conditions on a collection's rows
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use ogc::features::filtering::cql2::{ComparisonOp, Cql2Error, Expression, Operand, SpatialOp};
+use sqlx::{Postgres, postgres::PgArguments, query::QueryAs};
+
+use crate::postgres::{TableColumn, sort::SortExpression};
+
+/// Rows a filter is compiled against
+#[derive(Clone, Copy)]
+pub enum FilterTarget<'a> {
+    /// `app.project_features` aliased as `f`, with user defined properties in its
+    /// `properties` JSONB
+    ProjectFeatures,
+    /// A `gis_data` table aliased as `t`
+    Table(&'a [TableColumn]),
+}
+
+/// Columns of `app.project_features` a filter refers to by property name
+const PROJECT_FEATURE_COLUMNS: [(&str, &str, ValueType); 9] = [
+    ("id", "f.id", ValueType::Number),
+    ("collection_id", "f.collection_id", ValueType::Number),
+    ("project_id", "f.project_id", ValueType::Number),
+    ("name", "f.name", ValueType::Text),
+    ("is_primary", "f.is_primary", ValueType::Boolean),
+    ("status", "f.status::text", ValueType::Text),
+    ("added", "f.added", ValueType::Timestamp),
+    ("last_updated", "f.last_updated", ValueType::Timestamp),
+    ("geometry", "f.geom", ValueType::Geometry),
+];
+
+impl FilterTarget<'_> {
+    /// Properties a filter on the target can refer to, besides the keys of project
+    /// features' user defined properties
+    pub fn queryables(self) -> Vec<Queryable> {
+        match self {
+            FilterTarget::ProjectFeatures => PROJECT_FEATURE_COLUMNS
+                .iter()
+                .map(|(name, _, value_type)| Queryable::new(name, *value_type))
+                .collect(),
+            FilterTarget::Table(columns) => {
+                let geometry = columns.iter().position(|c| c.data_type == "geometry");
+                columns
+                    .iter()
+                    .enumerate()
+                    .map(|(index, column)| {
+                        let value_type = ValueType::of_column(&column.data_type);
+                        // The geometry column is queryable as `geometry` whatever its name
+                        match Some(index) == geometry {
+                            true => Queryable::new("geometry", value_type),
+                            false => Queryable::new(&column.name, value_type),
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// What to sort the target's rows by for a property, unless it is a geometry or
+    /// unknown. A user defined property is only known when it is one of `properties_keys`.
+    pub(super) fn sort_expression(
+        self,
+        name: &str,
+        properties_keys: &[String],
+    ) -> Option<SortExpression> {
+        match self.column(name).ok()? {
+            Column::Sql(_, ValueType::Geometry) => None,
+            Column::Sql(sql, _) => Some(SortExpression::Sql(sql)),
+            Column::PropertiesKey(key) if properties_keys.contains(&key) => {
+                Some(SortExpression::PropertiesKey(key))
+            }
+            Column::PropertiesKey(_) => None,
+        }
+    }
+
+    /// SQL a filter property refers to
+    fn column(self, name: &str) -> Result<Column, Cql2Error> {
+        match self {
+            FilterTarget::ProjectFeatures => {
+                let name = if name == "geom" { "geometry" } else { name };
+                let column = PROJECT_FEATURE_COLUMNS
+                    .iter()
+                    .find(|(property, ..)| *property == name);
+                Ok(match column {
+                    Some((_, sql, value_type)) => Column::Sql(sql.to_string(), *value_type),
+                    None => Column::PropertiesKey(name.to_string()),
+                })
+            }
+            FilterTarget::Table(columns) => {
+                let column = columns
+                    .iter()
+                    .find(|column| column.name == name)
+                    .or_else(|| match name {
+                        "geometry" => columns.iter().find(|column| column.data_type == "geometry"),
+                        "id" => columns.iter().find(|column| column.name == "gid"),
+                        _ => None,
+                    })
+                    .ok_or_else(|| Cql2Error(format!("unknown property '{name}'")))?;
+                let value_type = ValueType::of_column(&column.data_type);
+                let identifier = format!("t.\"{}\"", column.name.replace('"', "\"\""));
+                Ok(match value_type {
+                    ValueType::Text => Column::Sql(format!("{identifier}::text"), value_type),
+                    _ => Column::Sql(identifier, value_type),
+                })
+            }
+        }
+    }
+}
+
+/// A property a filter can refer to, with the JSON Schema type and format of its values
+#[derive(Debug, Clone, PartialEq)]
+pub struct Queryable {
+    pub name: String,
+    pub schema_type: Option<&'static str>,
+    pub format: Option<&'static str>,
+}
+
+impl Queryable {
+    fn new(name: &str, value_type: ValueType) -> Self {
+        let (schema_type, format) = match value_type {
+            ValueType::Text => (Some("string"), None),
+            ValueType::Number => (Some("number"), None),
+            ValueType::Boolean => (Some("boolean"), None),
+            ValueType::Timestamp => (Some("string"), Some("date-time")),
+            ValueType::Date => (Some("string"), Some("date")),
+            ValueType::Geometry => (None, Some("geometry-any")),
+        };
+        Self {
+            name: name.to_string(),
+            schema_type,
+            format,
+        }
+    }
+}
+
+/// A filter as a SQL condition and the values bound to its parameters
+#[derive(Clone, Debug)]
+pub struct SqlFilter {
+    /// SQL before, between and after each parameter
+    parts: Vec<String>,
+    values: Vec<FilterValue>,
+}
+
+#[derive(Clone, Debug)]
+enum FilterValue {
+    Text(String),
+    Number(f64),
+    Boolean(bool),
+    Timestamp(DateTime<Utc>),
+    Date(NaiveDate),
+    Srid(i32),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ValueType {
+    Text,
+    Number,
+    Boolean,
+    Timestamp,
+    Date,
+    Geometry,
+}
+
+impl ValueType {
+    fn name(self) -> &'static str {
+        match self {
+            ValueType::Text => "text",
+            ValueType::Number => "a number",
+            ValueType::Boolean => "a boolean",
+            ValueType::Timestamp => "a timestamp",
+            ValueType::Date => "a date",
+            ValueType::Geometry => "a geometry",
+        }
+    }
+
+    /// Type of a column from its `information_schema` data type
+    fn of_column(data_type: &str) -> Self {
+        match data_type {
+            "smallint" | "integer" | "bigint" | "numeric" | "real" | "double precision" => {
+                ValueType::Number
+            }
+            "boolean" => ValueType::Boolean,
+            "timestamp with time zone" | "timestamp without time zone" => ValueType::Timestamp,
+            "date" => ValueType::Date,
+            "geometry" => ValueType::Geometry,
+            _ => ValueType::Text,
+        }
+    }
+}
+
+/// SQL a filter property refers to
+enum Column {
+    Sql(String, ValueType),
+    /// A key of a project feature's `properties`, whose type varies between features
+    PropertiesKey(String),
+}
+
+impl SqlFilter {
+    /// Compile a filter whose geometries are in `filter_srid`
+    pub fn new(
+        expression: &Expression,
+        target: FilterTarget<'_>,
+        filter_srid: i32,
+    ) -> Result<Self, Cql2Error> {
+        let mut compiler = Compiler {
+            target,
+            filter_srid,
+            parts: vec![String::new()],
+            values: Vec::new(),
+        };
+        compiler.expression(expression)?;
+        Ok(Self {
+            parts: compiler.parts,
+            values: compiler.values,
+        })
+    }
+
+    /// The SQL condition, numbering its parameters from `first_parameter`
+    pub fn condition(&self, first_parameter: usize) -> String {
+        let mut parts = self.parts.iter();
+        let mut condition = parts.next().cloned().unwrap_or_default();
+        for (index, part) in parts.enumerate() {
+            condition.push_str(&format!("${}{part}", first_parameter + index));
+        }
+        condition
+    }
+
+    /// Bind the filter's values, after any parameters bound before the condition
+    pub fn bind<'q, O>(
+        self,
+        mut query: QueryAs<'q, Postgres, O, PgArguments>,
+    ) -> QueryAs<'q, Postgres, O, PgArguments> {
+        for value in self.values {
+            query = match value {
+                FilterValue::Text(value) => query.bind(value),
+                FilterValue::Number(value) => query.bind(value),
+                FilterValue::Boolean(value) => query.bind(value),
+                FilterValue::Timestamp(value) => query.bind(value),
+                FilterValue::Date(value) => query.bind(value),
+                FilterValue::Srid(value) => query.bind(value),
+            };
+        }
+        query
+    }
+}
+
+struct Compiler<'a> {
+    target: FilterTarget<'a>,
+    filter_srid: i32,
+    parts: Vec<String>,
+    values: Vec<FilterValue>,
+}
+
+impl Compiler<'_> {
+    fn sql(&mut self, sql: &str) {
+        self.parts
+            .last_mut()
+            .expect("a filter always has a part after its last parameter")
+            .push_str(sql);
+    }
+
+    fn value(&mut self, value: FilterValue) {
+        self.values.push(value);
+        self.parts.push(String::new());
+    }
+
+    fn expression(&mut self, expression: &Expression) -> Result<(), Cql2Error> {
+        match expression {
+            Expression::And(expressions) | Expression::Or(expressions) => {
+                let separator = match expression {
+                    Expression::And(_) => " AND ",
+                    _ => " OR ",
+                };
+                self.sql("(");
+                for (index, expression) in expressions.iter().enumerate() {
+                    if index > 0 {
+                        self.sql(separator);
+                    }
+                    self.expression(expression)?;
+                }
+                self.sql(")");
+            }
+            Expression::Not(expression) => {
+                self.sql("NOT (");
+                self.expression(expression)?;
+                self.sql(")");
+            }
+            Expression::Literal(value) => self.sql(match value {
+                true => "TRUE",
+                false => "FALSE",
+            }),
+            Expression::IsNull(Operand::Property(name)) => match self.target.column(name)? {
+                Column::Sql(sql, _) => self.sql(&format!("{sql} IS NULL")),
+                Column::PropertiesKey(key) => {
+                    self.sql("COALESCE(jsonb_typeof(f.properties -> ");
+                    self.value(FilterValue::Text(key));
+                    self.sql("), 'null') = 'null'");
+                }
+            },
+            // Literals are never null
+            Expression::IsNull(_) => self.sql("FALSE"),
+            Expression::Comparison { op, left, right } => self.comparison(*op, left, right)?,
+            Expression::Spatial { op, left, right } => self.spatial(*op, left, right)?,
+        }
+        Ok(())
+    }
+
+    /// Type of an operand, or `None` for a key of a project feature's properties
+    fn operand_type(&self, operand: &Operand) -> Result<Option<ValueType>, Cql2Error> {
+        Ok(match operand {
+            Operand::Property(name) => match self.target.column(name)? {
+                Column::Sql(_, value_type) => Some(value_type),
+                Column::PropertiesKey(_) => None,
+            },
+            Operand::String(_) => Some(ValueType::Text),
+            Operand::Number(_) => Some(ValueType::Number),
+            Operand::Boolean(_) => Some(ValueType::Boolean),
+            Operand::Timestamp(_) => Some(ValueType::Timestamp),
+            Operand::Date(_) => Some(ValueType::Date),
+            Operand::Geometry(_) | Operand::Bbox(_) => Some(ValueType::Geometry),
+        })
+    }
+
+    /// Type both sides of a comparison are compared as
+    fn comparison_type(&self, left: &Operand, right: &Operand) -> Result<ValueType, Cql2Error> {
+        let value_type = match (self.operand_type(left)?, self.operand_type(right)?) {
+            (None, None) => ValueType::Text,
+            // Temporal properties are stored as ISO 8601 strings, which sort chronologically
+            (Some(ValueType::Timestamp | ValueType::Date), None)
+            | (None, Some(ValueType::Timestamp | ValueType::Date)) => ValueType::Text,
+            (Some(value_type), None) | (None, Some(value_type)) => value_type,
+            (Some(ValueType::Timestamp), Some(ValueType::Date))
+            | (Some(ValueType::Date), Some(ValueType::Timestamp)) => ValueType::Timestamp,
+            (Some(left), Some(right)) if left == right => left,
+            (Some(left), Some(right)) => {
+                return Err(Cql2Error(format!(
+                    "cannot compare {} with {}",
+                    left.name(),
+                    right.name()
+                )));
+            }
+        };
+        match value_type {
+            ValueType::Geometry => Err(Cql2Error(
+                "geometries can only be compared with spatial functions".into(),
+            )),
+            value_type => Ok(value_type),
+        }
+    }
+
+    fn comparison(
+        &mut self,
+        op: ComparisonOp,
+        left: &Operand,
+        right: &Operand,
+    ) -> Result<(), Cql2Error> {
+        let value_type = self.comparison_type(left, right)?;
+        self.operand(left, value_type)?;
+        self.sql(&format!(" {} ", op.as_str()));
+        self.operand(right, value_type)
+    }
+
+    /// Write a non-spatial operand compared as `value_type`
+    fn operand(&mut self, operand: &Operand, value_type: ValueType) -> Result<(), Cql2Error> {
+        let value = match operand {
+            Operand::Property(name) => {
+                match self.target.column(name)? {
+                    Column::Sql(sql, _) => self.sql(&sql),
+                    Column::PropertiesKey(key) => self.properties_key(key, value_type),
+                }
+                return Ok(());
+            }
+            Operand::String(value) => FilterValue::Text(value.clone()),
+            Operand::Number(value) => FilterValue::Number(*value),
+            Operand::Boolean(value) => FilterValue::Boolean(*value),
+            Operand::Timestamp(value) => match value_type {
+                ValueType::Text => {
+                    FilterValue::Text(value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+                }
+                _ => FilterValue::Timestamp(*value),
+            },
+            Operand::Date(value) => match value_type {
+                ValueType::Text => FilterValue::Text(value.to_string()),
+                _ => FilterValue::Date(*value),
+            },
+            Operand::Geometry(_) | Operand::Bbox(_) => {
+                return Err(Cql2Error(
+                    "geometries can only be compared with spatial functions".into(),
+                ));
+            }
+        };
+        self.value(value);
+        Ok(())
+    }
+
+    /// Write a key of a project feature's properties as `value_type`, or null for
+    /// features where it holds another type
+    fn properties_key(&mut self, key: String, value_type: ValueType) {
+        let (json_type, cast) = match value_type {
+            ValueType::Number => ("number", "float8"),
+            ValueType::Boolean => ("boolean", "boolean"),
+            _ => {
+                self.sql("(f.properties ->> ");
+                self.value(FilterValue::Text(key));
+                self.sql(")");
+                return;
+            }
+        };
+        self.sql("CASE WHEN jsonb_typeof(f.properties -> ");
+        self.value(FilterValue::Text(key.clone()));
+        self.sql(&format!(") = '{json_type}' THEN (f.properties ->> "));
+        self.value(FilterValue::Text(key));
+        self.sql(&format!(")::{cast} END"));
+    }
+
+    fn spatial(&mut self, op: SpatialOp, left: &Operand, right: &Operand) -> Result<(), Cql2Error> {
+        // Geometry literals are transformed to the CRS of the column they are tested against
+        let column = [left, right].into_iter().find_map(|operand| match operand {
+            Operand::Property(name) => match self.target.column(name) {
+                Ok(Column::Sql(sql, ValueType::Geometry)) => Some(sql),
+                _ => None,
+            },
+            _ => None,
+        });
+        let function = match op {
+            SpatialOp::Intersects => "ST_Intersects",
+            SpatialOp::Equals => "ST_Equals",
+            SpatialOp::Disjoint => "ST_Disjoint",
+            SpatialOp::Touches => "ST_Touches",
+            SpatialOp::Within => "ST_Within",
+            SpatialOp::Overlaps => "ST_Overlaps",
+            SpatialOp::Crosses => "ST_Crosses",
+            SpatialOp::Contains => "ST_Contains",
+        };
+        self.sql(&format!("{function}("));
+        self.geometry(op, left, column.as_deref())?;
+        self.sql(", ");
+        self.geometry(op, right, column.as_deref())?;
+        self.sql(")");
+        Ok(())
+    }
+
+    fn geometry(
+        &mut self,
+        op: SpatialOp,
+        operand: &Operand,
+        column: Option<&str>,
+    ) -> Result<(), Cql2Error> {
+        if let Operand::Property(name) = operand {
+            return match self.target.column(name)? {
+                Column::Sql(sql, ValueType::Geometry) => {
+                    self.sql(&sql);
+                    Ok(())
+                }
+                _ => Err(Cql2Error(format!("'{name}' is not a geometry"))),
+            };
+        }
+        if column.is_some() {
+            self.sql("ST_Transform(");
+        }
+        match operand {
+            Operand::Geometry(geometry) => {
+                self.sql("ST_SetSRID(ST_GeomFromGeoJSON(");
+                self.value(FilterValue::Text(geometry.to_string()));
+                self.sql("), ");
+            }
+            Operand::Bbox(bbox) => {
+                self.sql("ST_MakeEnvelope(");
+                for coordinate in bbox {
+                    self.value(FilterValue::Number(*coordinate));
+                    self.sql(", ");
+                }
+            }
+            _ => {
+                return Err(Cql2Error(format!(
+                    "{} takes geometries or geometry properties",
+                    op.name()
+                )));
+            }
+        }
+        self.value(FilterValue::Srid(self.filter_srid));
+        self.sql(")");
+        if let Some(column) = column {
+            self.sql(&format!(", ST_SRID({column}))"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ogc::features::filtering::cql2::{FilterLang, parse};
+
+    use super::*;
+
+    fn compile(filter: &str, target: FilterTarget) -> Result<SqlFilter, Cql2Error> {
+        SqlFilter::new(&parse(filter, FilterLang::Cql2Text)?, target, 4326)
+    }
+
+    #[test]
+    fn project_feature_filters_compile_to_numbered_parameters() {
+        let filter = compile(
+            "name = 'T1' AND (capacity >= 4.2 OR status <> 'ARCHIVED') AND model IS NULL",
+            FilterTarget::ProjectFeatures,
+        )
+        .unwrap();
+        assert_eq!(
+            filter.condition(12),
+            "(f.name = $12 AND (CASE WHEN jsonb_typeof(f.properties -> $13) = 'number' \
+             THEN (f.properties ->> $14)::float8 END >= $15 OR f.status::text <> $16) AND \
+             COALESCE(jsonb_typeof(f.properties -> $17), 'null') = 'null')"
+        );
+        assert_eq!(filter.values.len(), 6);
+    }
+
+    #[test]
+    fn spatial_literals_are_transformed_to_the_column_crs() {
+        let filter = compile(
+            "S_INTERSECTS(geometry, BBOX(-4, 51, -2, 53))",
+            FilterTarget::ProjectFeatures,
+        )
+        .unwrap();
+        assert_eq!(
+            filter.condition(1),
+            "ST_Intersects(f.geom, ST_Transform(ST_MakeEnvelope($1, $2, $3, $4, $5), ST_SRID(f.geom)))"
+        );
+    }
+
+    #[test]
+    fn table_filters_use_quoted_columns_and_reject_unknown_ones() {
+        let columns = [
+            TableColumn {
+                name: "gid".into(),
+                data_type: "integer".into(),
+            },
+            TableColumn {
+                name: "Site Name".into(),
+                data_type: "character varying".into(),
+            },
+            TableColumn {
+                name: "geom".into(),
+                data_type: "geometry".into(),
+            },
+        ];
+        let target = FilterTarget::Table(&columns);
+        let filter = compile("\"Site Name\" = 'Hill' AND id > 3", target).unwrap();
+        assert_eq!(
+            filter.condition(9),
+            "(t.\"Site Name\"::text = $9 AND t.\"gid\" > $10)"
+        );
+        assert!(compile("missing = 1", target).is_err());
+        assert!(compile("\"Site Name\" = 1", target).is_err());
+        assert!(compile("geometry = 'a'", target).is_err());
+    }
+
+    #[test]
+    fn table_queryables_name_the_geometry_column_geometry() {
+        let columns = [
+            TableColumn {
+                name: "gid".into(),
+                data_type: "integer".into(),
+            },
+            TableColumn {
+                name: "surveyed".into(),
+                data_type: "date".into(),
+            },
+            TableColumn {
+                name: "geom".into(),
+                data_type: "geometry".into(),
+            },
+        ];
+        let queryables = FilterTarget::Table(&columns).queryables();
+        let names: Vec<_> = queryables.iter().map(|q| q.name.as_str()).collect();
+        assert_eq!(names, ["gid", "surveyed", "geometry"]);
+        assert_eq!(queryables[1].format, Some("date"));
+        assert_eq!(queryables[2].schema_type, None);
+    }
+}
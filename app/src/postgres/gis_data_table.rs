@@ -17,6 +17,14 @@ struct GisDataTableRow {
     description: Option<String>,
 }
 
+/// A column of a `gis_data` table
+#[derive(FromRow, Debug, Clone)]
+pub struct TableColumn {
+    pub name: String,
+    /// Postgres type, with the type name for extension types such as `geometry`
+    pub data_type: String,
+}
+
 const QUERY: &str = r#"
 SELECT t.tablename as "table_name",
 t.schemaname as "schema_name",
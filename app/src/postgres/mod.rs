@@ -1,11 +1,18 @@
 mod pg_repo;
 mod project_features;
 mod projects;
-pub use pg_repo::PostgresRepo;
+pub use exports::{ExportScope, StreamedExport};
+pub use filter::{FilterTarget, Queryable, SqlFilter};
+pub use gis_data_table::TableColumn;
+pub use pg_repo::{GeometryMeasurements, PostgresRepo, PropertiesKey};
+pub use sort::{SortError, SortTarget, SqlSort};
 mod api_key;
+mod exports;
 mod features;
+mod filter;
 mod gis_data_table;
 mod projcet_collections;
+mod sort;
 mod sql_fragments;
 mod technologies;
 mod users;
@@ -1,13 +1,19 @@
 /// Appplication repository
-use domain::{KeyId, ProjectCollectionId, ProjectId, UserId, enums::GeometryType};
+use domain::{
+    KeyId, ProjectCollectionId, ProjectId, TableName, UserId,
+    enums::{CoordinateDimension, GeometryType},
+};
 use futures::Stream;
-use sqlx::PgPool;
+use sqlx::{FromRow, PgPool};
 
-use crate::repo::{
-    PoolWrapper, RepositoryError, StreamItem,
-    traits::{
-        Insert, SelectAll, SelectAllWithParams, SelectAllWithParamsStreaming, SelectOne,
-        SelectOneWithParams, Update,
+use crate::{
+    postgres::TableColumn,
+    repo::{
+        PoolWrapper, RepositoryError, StreamItem,
+        traits::{
+            Insert, SelectAll, SelectAllWithParams, SelectAllWithParamsStreaming, SelectOne,
+            SelectOneWithParams, Update,
+        },
     },
 };
 
@@ -92,6 +98,20 @@ impl PostgresRepo {
     {
         item.insert(&self.db_pool).await
     }
+    /// Insert every item in a single transaction, rolling back all of them if any fails
+    #[tracing::instrument(skip(self, items))]
+    pub async fn insert_many<T>(&self, items: &[T]) -> Result<Vec<T::Id>, RepositoryError>
+    where
+        T: Insert,
+    {
+        let mut tx = self.db_pool.begin().await?;
+        let mut ids = Vec::with_capacity(items.len());
+        for item in items {
+            ids.push(item.insert(&mut *tx).await?);
+        }
+        tx.commit().await?;
+        Ok(ids)
+    }
     #[tracing::instrument(skip(self, item))]
     pub async fn update<T>(&self, item: &T) -> Result<T::Id, RepositoryError>
     where
@@ -147,6 +167,20 @@ impl PostgresRepo {
         .await?;
         Ok(geom)
     }
+    /// Whether uploads to a collection keep their Z values
+    #[tracing::instrument(skip(self, id))]
+    pub async fn get_collection_coordinate_dimension(
+        &self,
+        id: ProjectCollectionId,
+    ) -> Result<CoordinateDimension, RepositoryError> {
+        let dimension = sqlx::query_scalar!(
+            r#"SELECT coordinate_dimension as "coordinate_dimension: CoordinateDimension" FROM app.collections WHERE id = $1"#,
+            id.0
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+        Ok(dimension)
+    }
     #[tracing::instrument(skip(self, id))]
     pub async fn get_project_srid(&self, id: ProjectId) -> Result<Option<i32>, RepositoryError> {
         let srid = sqlx::query_scalar!("SELECT crs_srid FROM app.projects WHERE id = $1", id.0)
@@ -154,4 +188,115 @@ impl PostgresRepo {
             .await?;
         Ok(srid)
     }
+
+    /// Whether `srid` is a CRS known to PostGIS
+    #[tracing::instrument(skip(self))]
+    pub async fn srid_exists(&self, srid: i32) -> Result<bool, RepositoryError> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM public.spatial_ref_sys WHERE srid = $1) AS "exists!""#,
+            srid
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+        Ok(exists)
+    }
+
+    /// Columns of a table in `schema`, in table order
+    #[tracing::instrument(skip(self))]
+    pub async fn select_table_columns(
+        &self,
+        schema: &str,
+        table: &TableName,
+    ) -> Result<Vec<TableColumn>, RepositoryError> {
+        let columns = sqlx::query_as::<_, TableColumn>(
+            r#"
+            SELECT column_name::text AS name,
+                   CASE WHEN data_type = 'USER-DEFINED' THEN udt_name ELSE data_type END::text AS data_type
+              FROM information_schema.columns
+             WHERE table_schema = $1
+               AND table_name = $2
+             ORDER BY ordinal_position
+            "#,
+        )
+        .bind(schema)
+        .bind(table.as_ref())
+        .fetch_all(&self.db_pool)
+        .await?;
+        Ok(columns)
+    }
+
+    /// Every key of the user defined properties of a project collection's features, with
+    /// the JSON type of its value on the first feature that has it
+    #[tracing::instrument(skip(self))]
+    pub async fn select_properties_keys(
+        &self,
+        project_id: ProjectId,
+        collection_id: ProjectCollectionId,
+    ) -> Result<Vec<PropertiesKey>, RepositoryError> {
+        let keys = sqlx::query_as::<_, PropertiesKey>(
+            r#"
+            SELECT DISTINCT ON (p.key) p.key, jsonb_typeof(p.value) AS json_type
+              FROM app.project_features f, jsonb_each(f.properties) p
+             WHERE f.project_id = $1
+               AND f.collection_id = $2
+               AND f.status <> 'DELETED'
+             ORDER BY p.key, f.id
+            "#,
+        )
+        .bind(project_id.0)
+        .bind(collection_id.0)
+        .fetch_all(&self.db_pool)
+        .await?;
+        Ok(keys)
+    }
+
+    /// Bounding box of a geometry once transformed to `target_srid`, with its geodesic
+    /// area and length in metres, without storing it.
+    #[tracing::instrument(skip(self, geom_wkb))]
+    pub async fn measure_geometry(
+        &self,
+        geom_wkb: &[u8],
+        srid: i32,
+        target_srid: i32,
+    ) -> Result<GeometryMeasurements, RepositoryError> {
+        let measurements = sqlx::query_as!(
+            GeometryMeasurements,
+            r#"
+            WITH input AS (
+                SELECT ST_Transform(ST_GeomFromWKB($1, $2), $3::int) AS geom
+            )
+            SELECT
+                ARRAY[ST_XMin(geom), ST_YMin(geom), ST_XMax(geom), ST_YMax(geom)] AS "bbox!",
+                ST_Area(ST_Transform(geom, 4326)::geography) AS "area!",
+                ST_Length(ST_Transform(geom, 4326)::geography) AS "length!",
+                ST_IsValid(geom) AS "is_valid!"
+            FROM input
+            "#,
+            geom_wkb,
+            srid,
+            target_srid
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+        Ok(measurements)
+    }
+}
+
+pub struct GeometryMeasurements {
+    /// `[minx, miny, maxx, maxy]` in the target CRS
+    pub bbox: Vec<f64>,
+    /// Geodesic area in square metres
+    pub area: f64,
+    /// Geodesic length in metres
+    pub length: f64,
+    /// Whether the geometry is still valid after transforming it
+    pub is_valid: bool,
+}
+
+/// A key of project features' user defined properties
+#[derive(FromRow, Debug)]
+pub struct PropertiesKey {
+    pub key: String,
+    /// `jsonb_typeof` of a value of the key, e.g. `number`
+    pub json_type: String,
 }
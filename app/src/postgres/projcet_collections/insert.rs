@@ -1,4 +1,7 @@
-use domain::{ProjectCollectionId, ProjectCollectionInputDto, UserId, enums::GeometryType};
+use domain::{
+    ProjectCollectionId, ProjectCollectionInputDto, UserId,
+    enums::{CoordinateDimension, GeometryType},
+};
 use sqlx::{Acquire, Postgres};
 
 use crate::repo::traits::Insert;
@@ -14,14 +17,15 @@ impl Insert for (&ProjectCollectionInputDto, UserId) {
         let mut executor = conn.acquire().await?;
         sqlx::query_scalar!(
             r#"
-            INSERT INTO app.collections (title, slug, description, geometry_type, added_by, last_updated_by)
-            VALUES ($1, $2, $3, $4, $5, $5)
+            INSERT INTO app.collections (title, slug, description, geometry_type, coordinate_dimension, added_by, last_updated_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $6)
             RETURNING id AS "id: ProjectCollectionId"
             "#,
             collection.title.as_ref(),
             collection.slug,
             collection.description,
             &collection.geometry_type as &GeometryType,
+            collection.coordinate_dimension as CoordinateDimension,
             id.0
         ).fetch_one(&mut *executor).await.map_err(Into::into)
     }
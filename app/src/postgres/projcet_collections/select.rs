@@ -1,6 +1,6 @@
 use domain::{
     AddedBy, CollectionListItem, ProjectCollection, ProjectCollectionId, SupportedCrs,
-    enums::{CollectionId, GeometryType, Status},
+    enums::{CollectionId, CoordinateDimension, GeometryType, Status},
 };
 use ogcapi_types::common::{Bbox, Crs, SpatialExtent};
 
@@ -18,6 +18,7 @@ struct CollectionRow {
     pub storage_crs_srid: Option<i32>,
     pub extent: Option<Vec<f64>>,
     pub geometry_type: GeometryType,
+    pub coordinate_dimension: CoordinateDimension,
 }
 
 impl CollectionRow {
@@ -30,6 +31,7 @@ impl CollectionRow {
             storage_crs_srid,
             extent,
             geometry_type,
+            coordinate_dimension,
         } = self;
         let bbox: Option<Bbox> = extent.and_then(|bbox| Bbox::try_from(bbox.as_slice()).ok());
         let storage_crs = storage_crs_srid.map(Crs::from_srid);
@@ -43,6 +45,7 @@ impl CollectionRow {
             supported_crs,
             storage_crs,
             geometry_type,
+            coordinate_dimension,
             extent: bbox.map(|bbox| SpatialExtent {
                 bbox: vec![bbox],
                 crs: extent_crs,
@@ -70,6 +73,7 @@ impl SelectOneWithParams<ProjectCollectionId> for ProjectCollection {
                    slug,
                    description,
                    geometry_type as "geometry_type: GeometryType",
+                   coordinate_dimension as "coordinate_dimension: CoordinateDimension",
                    (SELECT CASE WHEN COUNT(DISTINCT ST_SRID(f.geom)) = 1
                            THEN MIN(ST_SRID(f.geom))::int
                            ELSE NULL
@@ -141,6 +145,7 @@ impl SelectAllWithParams for ProjectCollection {
                    slug,
                    description,
                    geometry_type AS "geometry_type: GeometryType",
+                   coordinate_dimension AS "coordinate_dimension: CoordinateDimension",
                    (SELECT CASE WHEN COUNT(DISTINCT ST_SRID(f.geom)) = 1
                            THEN MIN(ST_SRID(f.geom))::int
                            ELSE NULL
@@ -1,4 +1,5 @@
 use domain::{FeatureId, FeatureInputDTO, ProjectCollectionId, ProjectId, UserId};
+use serde_json::Value;
 
 use crate::repo::traits::Insert;
 
@@ -22,9 +23,10 @@ impl Insert for (&FeatureInputDTO, ProjectId, ProjectCollectionId, UserId) {
                             added_by,
                             last_updated_by,
                             is_primary,
-                            geom
+                            geom,
+                            properties
                             )
-                            VALUES ($1, $2, $3, $4, $4, COALESCE($5, false), ST_Transform(ST_GeomFromWKB($6, $7), $8::int))
+                            VALUES ($1, $2, $3, $4, $4, COALESCE($5, false), ST_Transform(ST_GeomFromWKB($6, $7), $8::int), $9)
                             RETURNING id AS "id: FeatureId"
 
         "#,
@@ -35,7 +37,8 @@ impl Insert for (&FeatureInputDTO, ProjectId, ProjectCollectionId, UserId) {
             dto.primary,
             dto.geom_wkb,
             dto.srid,
-            dto.target_srid
+            dto.target_srid,
+            Value::Object(dto.properties.clone())
         )
         .fetch_one(&mut *conn)
         .await?;
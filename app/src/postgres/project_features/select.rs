@@ -7,7 +7,7 @@ use futures::{Stream, StreamExt};
 use geojson::Geometry;
 use serde::Deserialize;
 use serde_json::Value;
-use sqlx::types::Json;
+use sqlx::{FromRow, types::Json};
 
 use crate::repo::{
     PoolWrapper, RepositoryError, StreamItem,
@@ -15,14 +15,14 @@ use crate::repo::{
     traits::{SelectAllWithParamsStreaming, SelectOneWithParams},
 };
 
-#[derive(Deserialize)]
+#[derive(Deserialize, FromRow)]
 struct ProjectFeatureRow {
     pub id: i32,
     pub project_id: i32,
     pub collection_id: i32,
     pub properties: serde_json::Value,
     pub name: String,
-    pub geometry: Json<geojson::Geometry>,
+    pub geometry: Option<Json<geojson::Geometry>>,
     pub is_primary: bool,
     pub storage_crs_srid: i32,
     pub number_matched: i64,
@@ -75,7 +75,7 @@ impl TryInto<ProjectFeature> for ProjectFeatureRow {
                 storage_crs_srid,
                 is_primary,
             },
-            geometry: geometry.0,
+            geometry: geometry.map(|geometry| geometry.0),
             properties_map: properties,
         })
     }
@@ -106,7 +106,7 @@ impl SelectOneWithParams<&ProjectFeatureId> for ProjectFeature {
                 c.title AS "collection_title!",
                 f.project_id,
                 f.is_primary,
-                ST_AsGeoJSON(ST_Transform(f.geom, $3))::jsonb as "geometry!: Json<Geometry>",
+                ST_AsGeoJSON(ST_Transform(f.geom, $3))::jsonb as "geometry: Json<Geometry>",
                 ST_SRID(geom) AS "storage_crs_srid!",
                 f.properties,
                 f.status as "status: Status",
@@ -153,32 +153,52 @@ impl SelectAllWithParamsStreaming for ProjectFeature {
             collection_id,
             offset,
             status,
-            ..
+            filter,
+            sort,
+            properties,
+            skip_geometry,
         } = params;
         let bbox = bbox.map(|bbox| match bbox {
             ogcapi_types::common::Bbox::Bbox2D(bbox) => bbox,
             ogcapi_types::common::Bbox::Bbox3D(bbox) => [bbox[0], bbox[1], bbox[3], bbox[4]],
         });
 
-        sqlx::query_as!(
-            ProjectFeatureRow,
+        // The sort's parameters come before the filter's, which vary in number
+        let order_by = sort.order_by("f.id", 13);
+        let filter_condition = filter
+            .as_ref()
+            .map(|filter| format!("AND ({})", filter.condition(13 + sort.parameter_count())))
+            .unwrap_or_default();
+        let geometry = match skip_geometry {
+            true => "NULL::jsonb",
+            false => "ST_AsGeoJSON(ST_Transform(f.geom, $1))::jsonb",
+        };
+        let properties_column = match properties {
+            Some(_) => {
+                "(SELECT COALESCE(jsonb_object_agg(p.key, p.value), '{}'::jsonb)
+                    FROM jsonb_each(f.properties) p
+                   WHERE p.key = ANY($12))"
+            }
+            None => "f.properties",
+        };
+        let query = format!(
             r#"
-            SELECT 
+            SELECT
                 f.id,
                 f.collection_id,
-                c.title AS "collection_title!",
+                c.title AS collection_title,
                 f.project_id,
-                ST_AsGeoJSON(ST_Transform(f.geom, $1))::jsonb as "geometry!: Json<Geometry>",
-                ST_SRID(geom) AS "storage_crs_srid!",
+                {geometry} as geometry,
+                ST_SRID(geom) AS storage_crs_srid,
                 f.is_primary,
                 f.name,
-                f.properties,
-                f.status as "status: Status",
+                {properties_column} AS properties,
+                f.status,
                 f.added,
-                ROW(ab.id, ab.first_name, ab.last_name, ab.clerk_id, (ROW(t_ab.id, t_ab.name)::app.team))::app.user AS "added_by!: AddedBy",
+                ROW(ab.id, ab.first_name, ab.last_name, ab.clerk_id, (ROW(t_ab.id, t_ab.name)::app.team))::app.user AS added_by,
                 f.last_updated,
-                ROW(ub.id, ub.first_name, ub.last_name, ub.clerk_id, (ROW(t_ub.id, t_ub.name)::app.team))::app.user AS "last_updated_by!: LastUpdatedBy",
-                COUNT(*) OVER() as "number_matched!"
+                ROW(ub.id, ub.first_name, ub.last_name, ub.clerk_id, (ROW(t_ub.id, t_ub.name)::app.team))::app.user AS last_updated_by,
+                COUNT(*) OVER() as number_matched
             FROM app.project_features f
             JOIN app.collections c ON c.id = f.collection_id
             JOIN app.users ab ON f.added_by = ab.id
@@ -191,32 +211,43 @@ impl SelectAllWithParamsStreaming for ProjectFeature {
             AND ($4::float IS NULL OR (
                 f.geom && ST_Transform(ST_MakeEnvelope($4, $5, $6, $7, $8), ST_SRID(f.geom))
                 ))
-            ORDER BY f.id
+            {filter_condition}
+            ORDER BY {order_by}
             LIMIT $9
             OFFSET $10
-            "#,
-            crs.as_srid() as i32,
-            collection_id.0,
-            project_id.0,
-            bbox.map(|bbox| bbox[0]),
-            bbox.map(|bbox| bbox[1]),
-            bbox.map(|bbox| bbox[2]),
-            bbox.map(|bbox| bbox[3]),
-            bbox_crs.unwrap_or_default().as_srid() as i32,
-            limit.map(|l| l as i64),
-            offset.unwrap_or(0) as i32,
-            status.unwrap_or(vec![Status::Active]) as Vec<Status>
-        )
-        .fetch(executor)
-        .map(|res| {
-            let row = res?;
-            let number_matched = row.number_matched;
-            let item: ProjectFeature = row.try_into()?;
-            Ok(StreamItem {
-                item,
-                number_matched,
+            "#
+        );
+
+        executor
+            .fetch_owned::<ProjectFeatureRow, _>(query, move |query| {
+                let query = query
+                    .bind(crs.as_srid())
+                    .bind(collection_id.0)
+                    .bind(project_id.0)
+                    .bind(bbox.map(|bbox| bbox[0]))
+                    .bind(bbox.map(|bbox| bbox[1]))
+                    .bind(bbox.map(|bbox| bbox[2]))
+                    .bind(bbox.map(|bbox| bbox[3]))
+                    .bind(bbox_crs.unwrap_or_default().as_srid())
+                    .bind(limit.map(|l| l as i64))
+                    .bind(offset.unwrap_or(0) as i32)
+                    .bind(status.unwrap_or(vec![Status::Active]))
+                    .bind(properties);
+                let query = sort.bind(query);
+                match filter {
+                    Some(filter) => filter.bind(query),
+                    None => query,
+                }
+            })
+            .map(|res| {
+                let row = res?;
+                let number_matched = row.number_matched;
+                let item: ProjectFeature = row.try_into()?;
+                Ok(StreamItem {
+                    item,
+                    number_matched,
+                })
             })
-        })
     }
 }
 
@@ -238,9 +269,9 @@ mod tests {
             collection_id: 0,
             properties: json!("{}"),
             name: uuid::Uuid::new_v4().to_string(),
-            geometry: sqlx::types::Json(geojson::Geometry::new(geojson::Value::Point(vec![
-                1., 1.,
-            ]))),
+            geometry: Some(sqlx::types::Json(geojson::Geometry::new(
+                geojson::Value::Point(vec![1., 1.]),
+            ))),
             is_primary: true,
             collection_title: Default::default(),
             status: Default::default(),
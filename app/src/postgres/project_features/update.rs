@@ -1,4 +1,6 @@
-use domain::{ProjectFeatureId, ProjectId, UserId, enums::Status};
+use chrono::{DateTime, Utc};
+use domain::{FeatureEditDTO, ProjectFeatureId, ProjectId, UserId, enums::Status};
+use serde_json::Value;
 use sqlx::{Acquire, Postgres};
 
 use crate::{handlers::api::features::patch::PatchProjectFeaturePayload, repo::traits::Update};
@@ -65,3 +67,108 @@ impl Update
         })
     }
 }
+
+/// Edit the version of a feature last updated at the given time, returning the time of
+/// the new version. Fails with `RowNotFound` when the feature has changed since.
+impl Update
+    for (
+        &FeatureEditDTO,
+        UserId,
+        ProjectId,
+        ProjectFeatureId,
+        DateTime<Utc>,
+    )
+{
+    type Id = DateTime<Utc>;
+
+    async fn update<'a, E>(&self, conn: E) -> Result<Self::Id, crate::repo::RepositoryError>
+    where
+        E: Acquire<'a, Database = Postgres>,
+    {
+        let (dto, user_id, project_id, feature_id, last_updated) = self;
+        let mut tx = conn.begin().await?;
+        if dto.primary == Some(true) {
+            sqlx::query!(
+                r#"
+            UPDATE app.project_features
+            SET is_primary = false,
+                last_updated = NOW(),
+                last_updated_by = $1
+            WHERE collection_id = $2
+            AND project_id = $3
+            AND is_primary = true
+            AND id <> $4"#,
+                user_id.0,
+                feature_id.collection_id.0,
+                project_id.0,
+                feature_id.feature_id.0
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        let last_updated = sqlx::query_scalar!(
+            r#"
+        UPDATE app.project_features
+        SET name = COALESCE($1, name),
+            is_primary = COALESCE($2, is_primary),
+            geom = COALESCE(ST_Transform(ST_GeomFromWKB($3, $4), ST_SRID(geom)), geom),
+            properties = $5,
+            last_updated = NOW(),
+            last_updated_by = $6
+        WHERE id = $7
+        AND collection_id = $8
+        AND project_id = $9
+        AND last_updated = $10
+        RETURNING last_updated"#,
+            dto.name.as_deref(),
+            dto.primary,
+            dto.geom_wkb.as_deref(),
+            dto.srid,
+            Value::Object(dto.properties.clone()),
+            user_id.0,
+            feature_id.feature_id.0,
+            feature_id.collection_id.0,
+            project_id.0,
+            last_updated
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(last_updated)
+    }
+}
+
+/// Set the status of the version of a feature last updated at the given time. Fails with
+/// `RowNotFound` when the feature has changed since.
+impl Update for (Status, UserId, ProjectId, ProjectFeatureId, DateTime<Utc>) {
+    type Id = DateTime<Utc>;
+
+    async fn update<'a, E>(&self, conn: E) -> Result<Self::Id, crate::repo::RepositoryError>
+    where
+        E: Acquire<'a, Database = Postgres>,
+    {
+        let (status, user_id, project_id, feature_id, last_updated) = self;
+        let mut conn = conn.acquire().await?;
+        let last_updated = sqlx::query_scalar!(
+            r#"
+        UPDATE app.project_features
+        SET status = $1,
+            last_updated = NOW(),
+            last_updated_by = $2
+        WHERE id = $3
+        AND collection_id = $4
+        AND project_id = $5
+        AND last_updated = $6
+        RETURNING last_updated"#,
+            status as &Status,
+            user_id.0,
+            feature_id.feature_id.0,
+            feature_id.collection_id.0,
+            project_id.0,
+            last_updated
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        Ok(last_updated)
+    }
+}
@@ -40,7 +40,16 @@ impl TryInto<Project> for ProjectRow {
     }
 }
 
-fn project_query() -> String {
+/// Select projects, with the centroid of their primary site boundary unless
+/// `skip_geometry`
+fn project_query(skip_geometry: bool) -> String {
+    let (geom, centroid_join) = match skip_geometry {
+        true => ("NULL::json", ""),
+        false => (
+            "ST_AsGeoJson(ST_Transform(pb.centroid, $1))::json",
+            "LEFT JOIN primary_boundary_centroid pb ON pb.project_id = p.id",
+        ),
+    };
     format!(
         r#"WITH primary_boundary_centroid AS (
             SELECT pf.project_id, ST_Centroid(pf.geom) AS centroid
@@ -64,12 +73,12 @@ fn project_query() -> String {
             {user_row_owner},
             {user_row_added_by},
             {user_row_last_updated_by},
-            ST_AsGeoJson(ST_Transform(pb.centroid, $1))::json AS geom
+            {geom} AS geom
         FROM app.projects p
         {user_join_owner}
         {user_join_added_by}
         {user_join_last_updated_by}
-        LEFT JOIN primary_boundary_centroid pb ON pb.project_id = p.id
+        {centroid_join}
         "#,
         user_row_owner = user_row_fragment("o", "owner"),
         user_row_added_by = user_row_fragment("a", "added_by"),
@@ -96,18 +105,21 @@ impl SelectAllWithParams for Project {
             _bbox: _,
             _bbox_crs: _,
             status,
+            sort,
+            skip_geometry,
         } = params;
-        let rows: Vec<ProjectRow> = sqlx::query_as(&format!(
+        let query = format!(
             "{}         WHERE p.status = ANY($2)
-            ORDER BY id
+            ORDER BY {}
                  LIMIT $3",
-            project_query()
-        ))
-        .bind(crs.as_srid())
-        .bind(status.unwrap_or(vec![Status::Active]))
-        .bind(limit.map(|l| l as i32))
-        .fetch_all(executor)
-        .await?;
+            project_query(skip_geometry),
+            sort.order_by("p.id", 4)
+        );
+        let query = sqlx::query_as(&query)
+            .bind(crs.as_srid())
+            .bind(status.unwrap_or(vec![Status::Active]))
+            .bind(limit.map(|l| l as i32));
+        let rows: Vec<ProjectRow> = sort.bind(query).fetch_all(executor).await?;
 
         let items = rows
             .into_iter()
@@ -147,7 +159,7 @@ impl SelectOneWithParams<ProjectId> for Project {
     {
         let SelectOneParams { crs } = params;
         let project_row: Option<ProjectRow> =
-            sqlx::query_as(&format!("{} AND p.id = $2", project_query()))
+            sqlx::query_as(&format!("{} AND p.id = $2", project_query(false)))
                 .bind(crs.as_srid())
                 .bind(id.0)
                 .fetch_optional(executor)
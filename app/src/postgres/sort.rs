@@ -0,0 +1,176 @@
+//! `sortby` parameters compiled to SQL `ORDER BY` lists
+use sqlx::{Postgres, postgres::PgArguments, query::QueryAs};
+
+use crate::postgres::{FilterTarget, TableColumn};
+
+/// Rows a sort is compiled against
+#[derive(Clone, Copy)]
+pub enum SortTarget<'a> {
+    /// `app.projects` aliased as `p`
+    Projects,
+    /// The features of a project collection, whose user defined properties have the
+    /// given keys, with properties named as in filters
+    ProjectFeatures(&'a [String]),
+    /// The rows of a `gis_data` table with the given columns
+    Table(&'a [TableColumn]),
+}
+
+/// Columns of `app.projects` the projects collection can be sorted by
+const PROJECT_COLUMNS: [(&str, &str); 5] = [
+    ("id", "p.id"),
+    ("name", "p.name"),
+    ("status", "p.status::text"),
+    ("added", "p.added"),
+    ("last_updated", "p.last_updated"),
+];
+
+/// What a sort key orders rows by
+#[derive(Clone, Debug)]
+pub(super) enum SortExpression {
+    /// SQL built only from known columns
+    Sql(String),
+    /// A key of a project feature's `properties`, bound as a parameter
+    PropertiesKey(String),
+}
+
+impl SortTarget<'_> {
+    fn expression(self, name: &str) -> Option<SortExpression> {
+        match self {
+            SortTarget::Projects => PROJECT_COLUMNS
+                .iter()
+                .find(|(property, _)| *property == name)
+                .map(|(_, sql)| SortExpression::Sql(sql.to_string())),
+            SortTarget::ProjectFeatures(properties_keys) => {
+                FilterTarget::ProjectFeatures.sort_expression(name, properties_keys)
+            }
+            SortTarget::Table(columns) => FilterTarget::Table(columns).sort_expression(name, &[]),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+#[error("cannot sort by '{0}'")]
+pub struct SortError(pub String);
+
+/// Sort keys built only from known columns and bound properties keys, so a `sortby`
+/// parameter never reaches the query itself
+#[derive(Clone, Debug, Default)]
+pub struct SqlSort {
+    keys: Vec<(SortExpression, &'static str)>,
+}
+
+impl SqlSort {
+    /// Compile `sortby` keys, each a property name prefixed with `-` to sort descending
+    /// or optionally `+` to sort ascending
+    pub fn new(sortby: &[String], target: SortTarget<'_>) -> Result<Self, SortError> {
+        let keys = sortby
+            .iter()
+            .map(|key| {
+                // A `+` in a query string is decoded as a space
+                let key = key.trim();
+                let (name, direction) = match key.strip_prefix('-') {
+                    Some(name) => (name, "DESC"),
+                    None => (key.strip_prefix('+').unwrap_or(key), "ASC"),
+                };
+                target
+                    .expression(name)
+                    .map(|expression| (expression, direction))
+                    .ok_or_else(|| SortError(name.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { keys })
+    }
+
+    /// Number of parameters the `ORDER BY` list binds
+    pub fn parameter_count(&self) -> usize {
+        self.keys
+            .iter()
+            .filter(|(expression, _)| matches!(expression, SortExpression::PropertiesKey(_)))
+            .count()
+    }
+
+    /// The `ORDER BY` list, numbering its parameters from `first_parameter` and ending with
+    /// the rows' unique `id` so that rows with equal sort keys keep the same order from one
+    /// page to the next
+    pub fn order_by(&self, id: &str, first_parameter: usize) -> String {
+        let mut parameter = first_parameter;
+        self.keys
+            .iter()
+            .map(|(expression, direction)| match expression {
+                SortExpression::Sql(sql) => format!("{sql} {direction}"),
+                // JSONB orders numbers numerically and strings lexically, each type together
+                SortExpression::PropertiesKey(_) => {
+                    parameter += 1;
+                    format!("f.properties -> ${} {direction}", parameter - 1)
+                }
+            })
+            .chain([id.to_string()])
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Bind the properties keys sorted by, after any parameters bound before the list
+    pub fn bind<'q, O>(
+        self,
+        mut query: QueryAs<'q, Postgres, O, PgArguments>,
+    ) -> QueryAs<'q, Postgres, O, PgArguments> {
+        for (expression, _) in self.keys {
+            if let SortExpression::PropertiesKey(key) = expression {
+                query = query.bind(key);
+            }
+        }
+        query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(sortby: &str) -> Vec<String> {
+        sortby.split(',').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn sort_keys_are_compiled_in_order_before_the_id() {
+        let sort = SqlSort::new(
+            &keys("-last_updated, name,hub_height"),
+            SortTarget::ProjectFeatures(&["hub_height".to_string()]),
+        )
+        .unwrap();
+        assert_eq!(
+            sort.order_by("f.id", 13),
+            "f.last_updated DESC, f.name ASC, f.properties -> $13 ASC, f.id"
+        );
+        assert_eq!(sort.parameter_count(), 1);
+        assert_eq!(SqlSort::default().order_by("gid", 9), "gid");
+    }
+
+    #[test]
+    fn properties_keys_are_bound_rather_than_written_into_the_sql() {
+        let properties_keys = ["owner's ref".to_string()];
+        let sort = SqlSort::new(
+            &keys("-owner's ref"),
+            SortTarget::ProjectFeatures(&properties_keys),
+        )
+        .unwrap();
+        assert_eq!(sort.order_by("f.id", 1), "f.properties -> $1 DESC, f.id");
+    }
+
+    #[test]
+    fn unknown_and_geometry_properties_are_rejected() {
+        let columns = [TableColumn {
+            name: "geom".into(),
+            data_type: "geometry".into(),
+        }];
+        let table = SortTarget::Table(&columns);
+        assert!(SqlSort::new(&keys("geometry"), table).is_err());
+        assert!(SqlSort::new(&keys("gid; DROP TABLE x"), table).is_err());
+        assert!(SqlSort::new(&keys("owner"), SortTarget::Projects).is_err());
+        let properties_keys = ["hub_height".to_string()];
+        let features = SortTarget::ProjectFeatures(&properties_keys);
+        assert!(SqlSort::new(&keys("x' DESC; --"), features).is_err());
+        assert!(SqlSort::new(&keys("rotor_diameter"), features).is_err());
+        assert!(SqlSort::new(&keys("geom"), features).is_err());
+    }
+}
@@ -3,6 +3,8 @@ pub mod project {
     use domain::enums::Status;
     use ogcapi_types::common::Crs;
 
+    use crate::postgres::SqlSort;
+
     pub struct SelectOneParams<'a> {
         pub crs: &'a Crs,
     }
@@ -13,6 +15,8 @@ pub mod project {
         pub _bbox: Option<&'a ogcapi_types::common::Bbox>,
         pub _bbox_crs: Option<&'a Crs>,
         pub status: Option<Vec<Status>>,
+        pub sort: SqlSort,
+        pub skip_geometry: bool,
     }
 }
 
@@ -20,6 +24,8 @@ pub mod project_features {
     use domain::{ProjectCollectionId, ProjectId, enums::Status};
     use ogcapi_types::common::Crs;
 
+    use crate::postgres::{SqlFilter, SqlSort};
+
     #[derive(Clone)]
     pub struct SelectAllParams {
         pub limit: Option<usize>,
@@ -30,6 +36,11 @@ pub mod project_features {
         pub bbox_crs: Option<Crs>,
         pub offset: Option<usize>,
         pub status: Option<Vec<Status>>,
+        pub filter: Option<SqlFilter>,
+        pub sort: SqlSort,
+        /// Keys of the user defined properties to select, every key when `None`
+        pub properties: Option<Vec<String>>,
+        pub skip_geometry: bool,
     }
 
     #[derive(Clone)]
@@ -57,6 +68,8 @@ pub mod features {
     use domain::TableName;
     use ogcapi_types::common::Crs;
 
+    use crate::postgres::{SqlFilter, SqlSort};
+
     pub struct SelectOneParams<'a> {
         pub schema: &'a str,
         pub table: TableName,
@@ -72,6 +85,11 @@ pub mod features {
         pub bbox: Option<ogcapi_types::common::Bbox>,
         pub bbox_crs: Option<Crs>,
         pub crs: Crs,
+        pub filter: Option<SqlFilter>,
+        pub sort: SqlSort,
+        /// Columns selected as the features' properties, in order
+        pub properties: Vec<String>,
+        pub skip_geometry: bool,
     }
 }
 pub mod api_keys {
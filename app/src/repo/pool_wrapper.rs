@@ -1,10 +1,44 @@
 #![allow(unused)]
-use sqlx::{Executor, Pool, Postgres};
+use futures::{SinkExt, StreamExt, channel::mpsc};
+use sqlx::{
+    Executor, FromRow, Pool, Postgres,
+    postgres::{PgArguments, PgRow},
+    query::QueryAs,
+};
+
+/// Rows fetched ahead of the consumer of [`PoolWrapper::fetch_owned`]
+const ROW_BUFFER: usize = 64;
 
 /// Wrapper of PgPool to allow streaming of database results
 #[derive(Debug)]
 pub struct PoolWrapper(pub Pool<Postgres>);
 
+impl PoolWrapper {
+    /// Stream the rows of a query whose SQL is built at runtime.
+    ///
+    /// A query's stream borrows its SQL, so the query runs on a spawned task that owns
+    /// it and forwards each row. The task stops once the stream is dropped.
+    pub fn fetch_owned<T, F>(self, sql: String, bind: F) -> mpsc::Receiver<Result<T, sqlx::Error>>
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin + 'static,
+        F: for<'q> FnOnce(
+                QueryAs<'q, Postgres, T, PgArguments>,
+            ) -> QueryAs<'q, Postgres, T, PgArguments>
+            + 'static,
+    {
+        let (mut sender, receiver) = mpsc::channel(ROW_BUFFER);
+        actix_web::rt::spawn(async move {
+            let mut rows = bind(sqlx::query_as(&sql)).fetch(&self.0);
+            while let Some(row) = rows.next().await {
+                if sender.send(row).await.is_err() {
+                    break;
+                }
+            }
+        });
+        receiver
+    }
+}
+
 impl<'c> Executor<'c> for PoolWrapper {
     type Database = Postgres;
 
@@ -1,21 +1,32 @@
 use crate::{
     URLS,
+    config::UploadSettings,
     enums::GeoManEnvironment,
+    errors::ApiError,
     handlers::api::{
         app_settings::get_app_settings,
         epsg::{post_epsg, post_epsg_from_shz},
         features::{
-            get::get_project_feature_shapefile, patch::patch_project_feature,
-            post::post_project_feature_shapefile,
+            export::{get_collection_shapefile, get_feature_kmz},
+            get::get_project_feature_shapefile,
+            patch::patch_project_feature,
+            post::{post_project_feature_geojson, post_project_feature_shapefile},
+            validate::post_project_feature_validate,
         },
         keys::{generate_api_key, get_api_keys, renew_api_key, revoke_api_key},
         project_collections::{get_collections, patch_collection, post_project_collection},
-        projects::{patch_project, post_project},
+        projects::{
+            get_project_bundle, get_project_dxf, get_project_gpkg, get_project_kmz, patch_project,
+            post_project,
+        },
         users::{get_user, get_users},
     },
     middleware::{auth_middleware, mock_auth_middlewear},
 };
+use actix_multipart::form::MultipartFormConfig;
 use actix_web::{
+    ResponseError,
+    http::StatusCode,
     middleware,
     web::{self, scope},
 };
@@ -37,14 +48,19 @@ use clerk_rs::clerk::Clerk;
 //     }
 // }
 
-pub fn api_routes(cfg: &mut web::ServiceConfig, _clerk: Clerk, run_environment: GeoManEnvironment) {
+pub fn api_routes(
+    cfg: &mut web::ServiceConfig,
+    _clerk: Clerk,
+    run_environment: GeoManEnvironment,
+    upload_settings: UploadSettings,
+) {
     let scp = scope(&URLS.api.base)
         .configure(api_key_routes)
         .configure(project_routes)
         .configure(user_routes)
         .configure(project_collection_routes)
-        .configure(project_features_routes)
-        .configure(epsg_routes)
+        .configure(|cfg| project_features_routes(cfg, &upload_settings))
+        .configure(|cfg| epsg_routes(cfg, &upload_settings))
         .route(&URLS.api.app_settings, web::get().to(get_app_settings));
 
     match run_environment {
@@ -71,7 +87,11 @@ pub fn project_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         scope(&URLS.api.projects)
             .service(post_project)
-            .service(patch_project),
+            .service(patch_project)
+            .service(get_project_gpkg)
+            .service(get_project_kmz)
+            .service(get_project_dxf)
+            .service(get_project_bundle),
     );
 }
 
@@ -88,18 +108,39 @@ pub fn project_collection_routes(cfg: &mut web::ServiceConfig) {
     );
 }
 
-pub fn project_features_routes(cfg: &mut web::ServiceConfig) {
+/// Multipart config that spools file parts to disk and rejects requests over `limit`
+/// bytes with a JSON `413 Payload Too Large` error
+fn multipart_config(limit: usize) -> MultipartFormConfig {
+    MultipartFormConfig::default()
+        .total_limit(limit)
+        .error_handler(move |err, _req| {
+            if err.status_code() == StatusCode::PAYLOAD_TOO_LARGE {
+                ApiError::PayloadTooLarge(limit).into()
+            } else {
+                err.into()
+            }
+        })
+}
+
+pub fn project_features_routes(cfg: &mut web::ServiceConfig, upload_settings: &UploadSettings) {
     cfg.service(
         scope(&URLS.api.project_features)
+            .app_data(web::Data::new(upload_settings.clone()))
+            .app_data(multipart_config(upload_settings.feature_upload_limit_bytes))
             .service(patch_project_feature)
+            .service(post_project_feature_validate)
+            .service(post_project_feature_geojson)
             .service(post_project_feature_shapefile)
+            .service(get_collection_shapefile)
+            .service(get_feature_kmz)
             .service(get_project_feature_shapefile),
     );
 }
 
-pub fn epsg_routes(cfg: &mut web::ServiceConfig) {
+pub fn epsg_routes(cfg: &mut web::ServiceConfig, upload_settings: &UploadSettings) {
     cfg.service(
         scope(&URLS.api.epsg)
+            .app_data(multipart_config(upload_settings.epsg_upload_limit_bytes))
             .service(post_epsg)
             .service(post_epsg_from_shz),
     );
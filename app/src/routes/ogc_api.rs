@@ -1,5 +1,5 @@
 use crate::{
-    URLS,
+    URLS, UploadSettings,
     enums::GeoManEnvironment,
     handlers::ogc_api,
     middleware::{dual_auth_middleware, mock_auth_middlewear},
@@ -9,10 +9,18 @@ use actix_web::{
     web::{self, scope},
 };
 
-pub fn ogc_routes(cfg: &mut web::ServiceConfig, run_environment: GeoManEnvironment) {
+pub fn ogc_routes(
+    cfg: &mut web::ServiceConfig,
+    run_environment: GeoManEnvironment,
+    upload_settings: UploadSettings,
+) {
     let scp = scope(&URLS.ogc_api.base)
         .wrap(middleware::NormalizePath::trim()) // required to pass OGC Features API test suit.
-        .service(scope(&URLS.ogc_api.project).configure(project_ogc_routes))
+        .service(
+            scope(&URLS.ogc_api.project)
+                .app_data(web::Data::new(upload_settings))
+                .configure(project_ogc_routes),
+        )
         .service(ogc_api::get_landing_page)
         .service(scope(&URLS.ogc_api.openapi).service(ogc_api::get_openapi))
         .service(
@@ -55,8 +63,13 @@ pub fn project_ogc_routes(cfg: &mut web::ServiceConfig) {
                 scope(&URLS.ogc_api.collections)
                     .service(ogc_api::get_project_collections)
                     .service(ogc_api::get_project_collection)
+                    .service(ogc_api::get_project_collection_queryables)
                     .service(ogc_api::get_project_features)
-                    .service(ogc_api::get_project_feature),
+                    .service(ogc_api::get_project_feature)
+                    .service(ogc_api::post_project_feature)
+                    .service(ogc_api::put_project_feature)
+                    .service(ogc_api::patch_project_feature)
+                    .service(ogc_api::delete_project_feature),
             ),
     );
 }
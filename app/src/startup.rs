@@ -87,9 +87,16 @@ pub async fn run(
                     cfg,
                     clerk.clone(),
                     config.app_settings.environment.run.clone(),
+                    config.upload_settings.clone(),
+                )
+            })
+            .configure(|cfg| {
+                ogc_routes(
+                    cfg,
+                    config.app_settings.environment.run.clone(),
+                    config.upload_settings.clone(),
                 )
             })
-            .configure(|cfg| ogc_routes(cfg, config.app_settings.environment.run.clone()))
             // .configure(|cfg| {
             //     docs_routes(cfg, clerk.clone(), config.app_settings.environment.clone())
             // })
@@ -13,6 +13,7 @@ use crate::{
 fn ogc_feature_byte_stream<T, F>(
     stream: T,
     collection_url: String,
+    query: Query,
 ) -> impl Stream<Item = Result<(Bytes, usize), anyhow::Error>>
 where
     T: Stream<Item = Result<F, RepositoryError>>,
@@ -20,7 +21,8 @@ where
 {
     stream.enumerate().map(move |(index, res)| {
         res.map_err(Into::into).and_then(|feature_row| {
-            let feature = feature_row.into_ogc_feature(collection_url.clone());
+            let mut feature = feature_row.into_ogc_feature(collection_url.clone());
+            query.select_members(&mut feature);
             let mut bytes = if index == 0 { Vec::new() } else { vec![b','] };
             serde_json::to_writer(&mut bytes, &feature)
                 .context("Failed to serialise feature to Json")?;
@@ -54,7 +56,7 @@ where
     let opening_stream = futures::stream::once(async move { Bytes::from(opening_json) });
 
     let feature_stream_with_index =
-        ogc_feature_byte_stream(feature_items, collection_id.to_string());
+        ogc_feature_byte_stream(feature_items, collection_id.to_string(), query.clone());
     let last_index = Arc::new(Mutex::new(None));
     let last_index_clone = last_index.clone();
 
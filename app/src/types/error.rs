@@ -4,4 +4,7 @@ use serde::{Deserialize, Serialize};
 pub struct ErrorResponse {
     pub message: String,
     pub long_message: String,
+    /// Layers the client can choose from when an upload holds more than one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layers: Option<Vec<String>>,
 }
@@ -1,7 +1,8 @@
 use app::{ErrorResponse, MockUserCredentials};
 use gdal::{
     Dataset,
-    vector::{Geometry, Layer, LayerOptions},
+    vector::{Geometry, Layer, LayerOptions, OGRwkbGeometryType},
+    vsi::get_vsi_mem_file_bytes_owned,
 };
 use geo::virtual_shapefile::ShapefileData;
 use rand::RngExt;
@@ -148,6 +149,35 @@ pub fn _create_gdal_multipolygon_wgs84() -> Geometry {
     assert!(!geom.is_empty(), "geometry empty");
     geom
 }
+/// A GeoPackage in BNG with one multipolygon in each of `layer_names`
+pub fn create_gpkg_with_layers(layer_names: &[&str]) -> Vec<u8> {
+    let filename = format!("/vsimem/{}.gpkg", uuid::Uuid::new_v4());
+    let mut dataset = gdal::DriverManager::get_driver_by_name("GPKG")
+        .expect("failed to get geopackage driver")
+        .create_vector_only(&filename)
+        .expect("failed to create geopackage");
+    for layer_name in layer_names {
+        let mut layer = dataset
+            .create_layer(LayerOptions {
+                name: layer_name,
+                options: None,
+                ty: OGRwkbGeometryType::wkbMultiPolygon,
+                srs: Some(
+                    &gdal::spatial_ref::SpatialRef::from_epsg(27700).expect("failed to create srs"),
+                ),
+            })
+            .expect("failed to create layer");
+        layer
+            .create_feature(create_gdal_multipolygon_bng())
+            .expect("failed to add geom");
+    }
+    dataset.flush_cache().expect("failed to flush cache");
+    dataset.close().expect("failed to close dataset");
+    let bytes = get_vsi_mem_file_bytes_owned(&filename).expect("failed to read gpkg bytes");
+    let _ = gdal::vsi::unlink_mem_file(&filename);
+    bytes
+}
+
 pub fn create_shapefile_dataset() -> (Dataset, String) {
     let filename = format!("/vsimem/{}.shp", uuid::Uuid::new_v4());
     let dataset = gdal::DriverManager::get_driver_by_name("ESRI Shapefile")
@@ -168,6 +198,18 @@ pub fn add_layer(dataset: &mut Dataset, ty: u32, crs: u32) -> Layer<'_> {
         .expect("failed to create layer")
 }
 
+/// Read a shapefile fixture from `test-data/shapefiles`, including its `.cpg` if present
+pub fn read_shapefile_fixture(name: &str) -> ShapefileData {
+    let path = format!("../test-data/shapefiles/{name}");
+    ShapefileData {
+        shp: std::fs::read(format!("{path}.shp")).expect("failed to read shp"),
+        dbf: std::fs::read(format!("{path}.dbf")).expect("failed to read dbf"),
+        shx: std::fs::read(format!("{path}.shx")).expect("failed to read shx"),
+        prj: std::fs::read(format!("{path}.prj")).expect("failed to read prj"),
+        cpg: std::fs::read(format!("{path}.cpg")).ok(),
+    }
+}
+
 pub fn dataset_to_shapefile_data(mut dataset: Dataset, filename: &str) -> ShapefileData {
     dataset.flush_cache().expect("failed to flush cache");
     dataset.close().expect("failed to close dataset");
@@ -198,8 +240,79 @@ pub fn add_shz_to_form(shz_bytes: Vec<u8>, form: Form) -> Form {
     )
 }
 
+pub fn add_gpkg_to_form(gpkg_bytes: Vec<u8>, form: Form) -> Form {
+    form.part(
+        "gpkg",
+        Part::bytes(gpkg_bytes)
+            .file_name("data.gpkg")
+            .mime_str("application/geopackage+sqlite3")
+            .expect("failed to add gpkg part"),
+    )
+}
+
+pub fn add_zip_to_form(zip_bytes: Vec<u8>, form: Form) -> Form {
+    form.part(
+        "zip",
+        Part::bytes(zip_bytes)
+            .file_name("data.zip")
+            .mime_str("application/zip")
+            .expect("failed to add zip part"),
+    )
+}
+
+pub fn add_dxf_to_form(dxf_bytes: Vec<u8>, form: Form) -> Form {
+    form.part(
+        "dxf",
+        Part::bytes(dxf_bytes)
+            .file_name("drawing.dxf")
+            .mime_str("image/vnd.dxf")
+            .expect("failed to add dxf part"),
+    )
+}
+
+pub fn add_gpx_to_form(gpx_bytes: Vec<u8>, form: Form) -> Form {
+    form.part(
+        "gpx",
+        Part::bytes(gpx_bytes)
+            .file_name("walkover.gpx")
+            .mime_str("application/gpx+xml")
+            .expect("failed to add gpx part"),
+    )
+}
+
+pub fn add_csv_to_form(csv: &str, form: Form) -> Form {
+    form.part(
+        "csv",
+        Part::text(csv.to_string())
+            .file_name("points.csv")
+            .mime_str("text/csv")
+            .expect("failed to add csv part"),
+    )
+}
+
 pub fn add_shapefile_to_form(filename: &str, data: ShapefileData, form: Form) -> Form {
     let mime = "application/octet-stream";
+    let form = match data.cpg {
+        Some(cpg) => form.part(
+            "cpg",
+            Part::bytes(cpg)
+                .file_name(format!("{filename}.cpg"))
+                .mime_str(mime)
+                .expect("failed to add cpg part"),
+        ),
+        None => form,
+    };
+    // An empty prj stands for a shapefile uploaded without one
+    let form = match data.prj.is_empty() {
+        false => form.part(
+            "prj",
+            Part::bytes(data.prj)
+                .file_name(format!("{filename}.prj"))
+                .mime_str(mime)
+                .expect("failed to add prj part"),
+        ),
+        true => form,
+    };
     form.part(
         "shp",
         Part::bytes(data.shp)
@@ -214,13 +327,6 @@ pub fn add_shapefile_to_form(filename: &str, data: ShapefileData, form: Form) ->
             .mime_str(mime)
             .expect("failed to add shx part"),
     )
-    .part(
-        "prj",
-        Part::bytes(data.prj)
-            .file_name(format!("{filename}.prj"))
-            .mime_str(mime)
-            .expect("failed to add prj part"),
-    )
     .part(
         "dbf",
         Part::bytes(data.dbf)
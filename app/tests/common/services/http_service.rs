@@ -71,4 +71,22 @@ impl HttpService {
         .await
         .expect(REQUEST_FAILED)
     }
+    pub async fn post_geojson<B: Serialize>(
+        &self,
+        client: &HttpClient,
+        body: &B,
+        id: impl Display,
+        auth: Option<&Auth>,
+    ) -> Response {
+        auth_request(
+            client
+                .post(&format!("{}/{}", self.endpoint, id))
+                .header("Content-Type", "application/geo+json")
+                .body(serde_json::to_vec(body).expect("failed to serialise body")),
+            auth,
+        )
+        .send()
+        .await
+        .expect(REQUEST_FAILED)
+    }
 }
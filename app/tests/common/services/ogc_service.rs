@@ -6,7 +6,7 @@ use crate::common::{
 };
 
 use app::URLS;
-use domain::{FeatureId, ProjectCollectionId, ProjectId, enums::CollectionId};
+use domain::{FeatureId, ProjectCollectionId, ProjectFeatureId, ProjectId, enums::CollectionId};
 use reqwest::{RequestBuilder, Response};
 use serde::Serialize;
 
@@ -272,4 +272,113 @@ impl OgcService {
         ));
         auth_request(req, auth).send().await.expect(REQUEST_FAILED)
     }
+
+    pub async fn get_project_collection_queryables(
+        &self,
+        client: &HttpClient,
+        project: ProjectId,
+        collection_id: ProjectCollectionId,
+    ) -> Response {
+        client
+            .get(format!(
+                "{}{}/{}{}/{}/queryables",
+                URLS.ogc_api.base,
+                URLS.ogc_api.project,
+                project.0,
+                URLS.ogc_api.collections,
+                collection_id
+            ))
+            .send()
+            .await
+            .expect(REQUEST_FAILED)
+    }
+
+    fn project_items_url(project: ProjectId, collection_id: ProjectCollectionId) -> String {
+        format!(
+            "{}{}/{}{}/{}/items",
+            URLS.ogc_api.base,
+            URLS.ogc_api.project,
+            project.0,
+            URLS.ogc_api.collections,
+            collection_id
+        )
+    }
+
+    fn with_if_match(req: RequestBuilder, if_match: Option<&str>) -> RequestBuilder {
+        match if_match {
+            Some(etag) => req.header("If-Match", etag),
+            None => req,
+        }
+    }
+
+    pub async fn post_project_feature<B: Serialize>(
+        &self,
+        client: &HttpClient,
+        project: ProjectId,
+        collection_id: ProjectCollectionId,
+        body: &B,
+        auth: Option<&Auth>,
+    ) -> Response {
+        let req = client
+            .post(Self::project_items_url(project, collection_id))
+            .json(body);
+        auth_request(req, auth).send().await.expect(REQUEST_FAILED)
+    }
+
+    pub async fn put_project_feature<B: Serialize>(
+        &self,
+        client: &HttpClient,
+        id: ProjectFeatureId,
+        project: ProjectId,
+        body: &B,
+        if_match: Option<&str>,
+        auth: Option<&Auth>,
+    ) -> Response {
+        let url = format!(
+            "{}/{}",
+            Self::project_items_url(project, id.collection_id),
+            id.feature_id.0
+        );
+        let req = Self::with_if_match(client.put(url).json(body), if_match);
+        auth_request(req, auth).send().await.expect(REQUEST_FAILED)
+    }
+
+    pub async fn patch_project_feature<B: Serialize>(
+        &self,
+        client: &HttpClient,
+        id: ProjectFeatureId,
+        project: ProjectId,
+        body: &B,
+        if_match: Option<&str>,
+        auth: Option<&Auth>,
+    ) -> Response {
+        let url = format!(
+            "{}/{}",
+            Self::project_items_url(project, id.collection_id),
+            id.feature_id.0
+        );
+        let req = client
+            .patch(url)
+            .header("Content-Type", "application/merge-patch+json")
+            .body(serde_json::to_vec(body).expect("failed to serialise patch"));
+        let req = Self::with_if_match(req, if_match);
+        auth_request(req, auth).send().await.expect(REQUEST_FAILED)
+    }
+
+    pub async fn delete_project_feature(
+        &self,
+        client: &HttpClient,
+        id: ProjectFeatureId,
+        project: ProjectId,
+        if_match: Option<&str>,
+        auth: Option<&Auth>,
+    ) -> Response {
+        let url = format!(
+            "{}/{}",
+            Self::project_items_url(project, id.collection_id),
+            id.feature_id.0
+        );
+        let req = Self::with_if_match(client.delete(url), if_match);
+        auth_request(req, auth).send().await.expect(REQUEST_FAILED)
+    }
 }
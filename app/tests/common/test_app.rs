@@ -10,7 +10,7 @@ use crate::common::{
     },
 };
 use app::{
-    AppConfig, Application, AuthenticatedUser, DatabaseSettings, Password, URLS,
+    AppConfig, Application, AuthenticatedUser, DatabaseSettings, Password, URLS, UploadSettings,
     constants::GIS_DATA_SCHEMA,
     enums::GeoManEnvironment,
     get_config,
@@ -25,7 +25,7 @@ use app::{
 };
 use domain::{
     FeatureId, ProjectCollectionId, ProjectFeatureId, ProjectId, TableName, TeamId, UserId,
-    enums::GeometryType,
+    enums::{CoordinateDimension, GeometryType},
 };
 use dotenvy::dotenv;
 use gdal::vector::{Geometry, LayerAccess};
@@ -68,6 +68,7 @@ pub struct AppBuilder {
     pub with_db: bool,
 
     pub environment: Option<GeoManEnvironment>,
+    pub upload_settings: Option<UploadSettings>,
 }
 
 impl AppBuilder {
@@ -75,10 +76,11 @@ impl AppBuilder {
         Self {
             with_db: true,
             environment: None,
+            upload_settings: None,
         }
     }
     pub async fn build(self) -> TestApp<ClerkAuthService> {
-        let app = TestApp::spawn(self.environment).await;
+        let app = TestApp::spawn_with_upload_settings(self.environment, self.upload_settings).await;
         if self.with_db {
             configure_database(&app.app_config.db_settings).await
         }
@@ -89,10 +91,21 @@ impl AppBuilder {
         self.environment = Some(env);
         self
     }
+    pub fn set_upload_settings(mut self, upload_settings: UploadSettings) -> Self {
+        self.upload_settings = Some(upload_settings);
+        self
+    }
 }
 
 impl TestApp<ClerkAuthService> {
     pub async fn spawn(run_env: Option<GeoManEnvironment>) -> Self {
+        Self::spawn_with_upload_settings(run_env, None).await
+    }
+
+    pub async fn spawn_with_upload_settings(
+        run_env: Option<GeoManEnvironment>,
+        upload_settings: Option<UploadSettings>,
+    ) -> Self {
         dotenv().ok();
         LazyLock::force(&TRACING);
         let db_name = Uuid::new_v4().to_string();
@@ -100,6 +113,9 @@ impl TestApp<ClerkAuthService> {
         config.db_settings.database_name = db_name.clone();
         // Set port to 0 so TCP Listner binds to random free port for tests
         config.app_settings.port = 0;
+        if let Some(upload_settings) = upload_settings {
+            config.upload_settings = upload_settings;
+        }
         let db_settings = config.db_settings.clone();
         let key1 = "TEST_USER_ID";
         let key2 = "TEST_USER_ID_2";
@@ -186,6 +202,46 @@ impl TestApp<ClerkAuthService> {
             .expect("failed to retrieve collection id")
     }
 
+    pub async fn generate_project_collection_id_with_type(
+        &self,
+        geometry_type: GeometryType,
+        auth: Option<&Auth>,
+    ) -> ProjectCollectionId {
+        let collection = CollectionReqPayload {
+            title: uuid::Uuid::new_v4().to_string(),
+            geometry_type,
+            description: None,
+            ..Default::default()
+        };
+        let response = self
+            .collections_service
+            .post_json(&self.api_client, auth, &collection)
+            .await;
+        handle_json_response(response)
+            .await
+            .expect("failed to retrieve collection id")
+    }
+
+    pub async fn generate_project_collection_id_with_dimension(
+        &self,
+        geometry_type: GeometryType,
+        coordinate_dimension: CoordinateDimension,
+        auth: Option<&Auth>,
+    ) -> ProjectCollectionId {
+        let collection = CollectionReqPayload {
+            geometry_type,
+            coordinate_dimension,
+            ..Default::default()
+        };
+        let response = self
+            .collections_service
+            .post_json(&self.api_client, auth, &collection)
+            .await;
+        handle_json_response(response)
+            .await
+            .expect("failed to retrieve collection id")
+    }
+
     pub async fn insert_project_feature(
         &self,
         collection_id: ProjectCollectionId,
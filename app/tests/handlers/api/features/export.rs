@@ -0,0 +1,93 @@
+use domain::{FeatureId, enums::GeometryType};
+use gdal::{Dataset, vector::LayerAccess};
+use geo::virtual_shapefile::VirtualFile;
+use serde_json::json;
+
+use crate::common::{
+    AppBuilder, Auth,
+    helpers::{assert_ok, check_error_response, handle_json_response},
+};
+
+#[actix_web::test]
+async fn get_collection_shapefile_exports_every_feature() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    for (name, coordinates) in [("T1", [-3.0, 52.0]), ("T2", [-3.1, 52.1])] {
+        let body = json!({
+            "type": "Feature",
+            "properties": {"hub_height_m": 90, "hub_height_ft": 295},
+            "geometry": {"type": "Point", "coordinates": coordinates},
+            "name": name
+        });
+        let response = app
+            .features_service
+            .post_geojson(
+                &app.api_client,
+                &body,
+                format!("{}/{}", project_id, collection_id),
+                Some(&auth),
+            )
+            .await;
+        let _feature_id: FeatureId = handle_json_response(response).await.unwrap();
+    }
+
+    for extension in ["shz", "zip"] {
+        let response = app
+            .features_service
+            .get_one(
+                &app.api_client,
+                Some(&auth),
+                format!("{project_id}/{collection_id}/export.{extension}"),
+            )
+            .await;
+        assert_ok(&response);
+        let body = response.bytes().await.expect("no response body");
+        let filename = format!("{}.{extension}", uuid::Uuid::new_v4());
+        let _virtual_file =
+            VirtualFile::new(&filename, body.to_vec()).expect("failed to create virtual file");
+        let path = match extension {
+            "zip" => format!("/vsizip//vsimem/{filename}"),
+            _ => format!("/vsimem/{filename}"),
+        };
+        let dataset = Dataset::open(path).expect("failed to open shapefile");
+        let mut layer = dataset.layers().next().expect("no layer");
+        assert_eq!(layer.feature_count(), 2);
+        let names: Vec<_> = layer.defn().fields().map(|field| field.name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "id",
+                "name",
+                "is_primary",
+                "status",
+                "hub_height",
+                "hub_heig_2"
+            ]
+        );
+        let feature = layer.features().next().expect("no features");
+        assert_eq!(
+            feature.field_as_string_by_name("name").unwrap().as_deref(),
+            Some("T1")
+        );
+    }
+}
+
+#[actix_web::test]
+async fn get_collection_shapefile_returns_404_for_unknown_collection() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let response = app
+        .features_service
+        .get_one(
+            &app.api_client,
+            Some(&auth),
+            format!("{project_id}/999999/export.shz"),
+        )
+        .await;
+    check_error_response(response, 404).await;
+}
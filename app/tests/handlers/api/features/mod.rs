@@ -1,3 +1,5 @@
+mod export;
 mod get;
 mod patch;
 mod post;
+mod validate;
@@ -1,16 +1,29 @@
-use app::handlers::api::project_collections::CollectionReqPayload;
-use domain::{FeatureId, ProjectCollectionId, enums::GeometryType};
+use app::{
+    UploadSettings,
+    handlers::api::{
+        features::post::FeatureUploadResponse, project_collections::CollectionReqPayload,
+        projects::PostProjectPayload,
+    },
+};
+use domain::{
+    FeatureId, ProjectCollectionId, ProjectId,
+    enums::{CoordinateDimension, GeometryType},
+};
 use gdal::{
-    vector::{LayerAccess, OGRwkbGeometryType},
+    vector::{FieldValue, LayerAccess, OGRFieldType, OGRwkbGeometryType},
     vsi::get_vsi_mem_file_bytes_owned,
 };
+use serde_json::json;
 
 use crate::common::{
-    AppBuilder, Auth,
+    AppBuilder, Auth, TestApp,
     helpers::{
-        add_layer, add_shapefile_to_form, add_shz_to_form, assert_ok, create_gdal_multipolygon_bng,
-        create_shapefile_dataset, dataset_to_shapefile_data, handle_json_response,
+        add_csv_to_form, add_dxf_to_form, add_gpkg_to_form, add_gpx_to_form, add_layer,
+        add_shapefile_to_form, add_shz_to_form, add_zip_to_form, assert_ok, check_error_response,
+        create_gdal_multipolygon_bng, create_gpkg_with_layers, create_shapefile_dataset,
+        dataset_to_shapefile_data, handle_json_response, read_shapefile_fixture,
     },
+    services::ClerkAuthService,
 };
 
 #[actix_web::test]
@@ -21,6 +34,7 @@ async fn post_shapefile_works() {
         title: uuid::Uuid::new_v4().to_string(),
         geometry_type: GeometryType::MultiPolygon,
         description: None,
+        ..Default::default()
     };
     let collection_id: ProjectCollectionId = handle_json_response(
         app.collections_service
@@ -67,6 +81,7 @@ async fn post_shapefile_works_with_shz() {
         title: uuid::Uuid::new_v4().to_string(),
         geometry_type: GeometryType::MultiPolygon,
         description: None,
+        ..Default::default()
     };
     let collection_id: ProjectCollectionId = handle_json_response(
         app.collections_service
@@ -110,3 +125,983 @@ async fn post_shapefile_works_with_shz() {
     assert_ok(&response);
     let _ogc_ft: ogc::features::Feature = handle_json_response(response).await.unwrap();
 }
+
+#[actix_web::test]
+async fn post_shapefile_stores_attributes_in_properties() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let (mut dataset, filename) = create_shapefile_dataset();
+    let mut layer = add_layer(&mut dataset, OGRwkbGeometryType::wkbMultiPolygon, 27700);
+    layer
+        .create_defn_fields(&[
+            ("owner", OGRFieldType::OFTString),
+            ("parcels", OGRFieldType::OFTInteger),
+        ])
+        .expect("failed to create fields");
+    for (owner, parcels, x) in [("Jones", 3, 0), ("Evans", 5, 1000)] {
+        let geom = gdal::vector::Geometry::from_wkt(&format!(
+            "POLYGON(({x} 0, {} 0, {} 100, {x} 100, {x} 0))",
+            x + 100,
+            x + 100
+        ))
+        .expect("failed to create polygon");
+        layer
+            .create_feature_fields(
+                geom,
+                &["owner", "parcels"],
+                &[
+                    FieldValue::StringValue(owner.to_string()),
+                    FieldValue::IntegerValue(parcels),
+                ],
+            )
+            .expect("failed to add feature");
+    }
+    let shapefile_data = dataset_to_shapefile_data(dataset, &filename);
+    let form = add_shapefile_to_form("test", shapefile_data, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("attributes", "collect");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let feature_id: FeatureId = handle_json_response(response).await.unwrap();
+    let response = app
+        .ogc_service
+        .get_project_feature(&app.api_client, project_id, collection_id, feature_id)
+        .await;
+    let ogc_ft: ogc::features::Feature = handle_json_response(response).await.unwrap();
+    assert_eq!(
+        ogc_ft.properties.get("owner"),
+        Some(&json!(["Jones", "Evans"]))
+    );
+    assert_eq!(ogc_ft.properties.get("parcels"), Some(&json!([3, 5])));
+}
+
+#[actix_web::test]
+async fn post_shapefile_decodes_dbf_with_cpg_code_page() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPoint, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let shapefile_data = read_shapefile_fixture("welsh_places_cp1252");
+    let form = add_shapefile_to_form("places", shapefile_data, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string());
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let feature_id: FeatureId = handle_json_response(response).await.unwrap();
+    let response = app
+        .ogc_service
+        .get_project_feature(&app.api_client, project_id, collection_id, feature_id)
+        .await;
+    let ogc_ft: ogc::features::Feature = handle_json_response(response).await.unwrap();
+    let names = ogc_ft
+        .properties
+        .get("name")
+        .expect("missing name property");
+    assert_eq!(names[0], json!("Ynys Môn"));
+    assert_eq!(names[1], json!("Aberdâr"));
+}
+
+#[actix_web::test]
+async fn post_shapefile_with_encoding_override_decodes_dbf() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPoint, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let shapefile_data = read_shapefile_fixture("gaelic_places_utf8_no_cpg");
+    let form = add_shapefile_to_form("places", shapefile_data, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("encoding", "UTF-8");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let feature_id: FeatureId = handle_json_response(response).await.unwrap();
+    let response = app
+        .ogc_service
+        .get_project_feature(&app.api_client, project_id, collection_id, feature_id)
+        .await;
+    let ogc_ft: ogc::features::Feature = handle_json_response(response).await.unwrap();
+    let names = ogc_ft
+        .properties
+        .get("name")
+        .expect("missing name property");
+    assert_eq!(names[0], json!("Dùn Èideann"));
+    assert_eq!(names[1], json!("Steòrnabhagh"));
+}
+
+fn create_self_intersecting_shapefile() -> geo::virtual_shapefile::ShapefileData {
+    let (mut dataset, filename) = create_shapefile_dataset();
+    let mut layer = add_layer(&mut dataset, OGRwkbGeometryType::wkbPolygon, 27700);
+    for wkt in [
+        "POLYGON((1000 0, 1100 0, 1100 100, 1000 100, 1000 0))",
+        "POLYGON((0 0, 100 100, 100 0, 0 100, 0 0))",
+    ] {
+        layer
+            .create_feature(
+                gdal::vector::Geometry::from_wkt(wkt).expect("failed to create polygon"),
+            )
+            .expect("failed to add feature");
+    }
+    dataset_to_shapefile_data(dataset, &filename)
+}
+
+#[actix_web::test]
+async fn post_shapefile_with_invalid_geometry_is_rejected_without_repair() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let form = add_shapefile_to_form(
+        "test",
+        create_self_intersecting_shapefile(),
+        reqwest::multipart::Form::new(),
+    )
+    .text("name", uuid::Uuid::new_v4().to_string());
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    check_error_response(response, 422).await;
+}
+
+#[actix_web::test]
+async fn post_shapefile_with_repair_fixes_invalid_geometry() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let form = add_shapefile_to_form(
+        "test",
+        create_self_intersecting_shapefile(),
+        reqwest::multipart::Form::new(),
+    )
+    .text("name", uuid::Uuid::new_v4().to_string())
+    .text("repair", "true");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let FeatureUploadResponse::Repaired { repair, .. } =
+        handle_json_response(response).await.unwrap()
+    else {
+        panic!("expected a repair report");
+    };
+    assert_eq!(repair.repaired_features, vec![1]);
+    assert!((repair.area_after - 15000.0).abs() < 1e-6);
+}
+
+#[actix_web::test]
+async fn post_shapefile_returns_400_without_name() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let form = add_shapefile_to_form(
+        "test",
+        read_shapefile_fixture("3_valid_polygon_osgb36"),
+        reqwest::multipart::Form::new(),
+    );
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    check_error_response(response, 400).await;
+}
+
+#[actix_web::test]
+async fn post_gpkg_works_with_layer_selector() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let gpkg = create_gpkg_with_layers(&["boundary", "red_line"]);
+    let form = add_gpkg_to_form(gpkg, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("layer", "red_line");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    assert_ok(&response);
+    let _feature_id: FeatureId = handle_json_response(response).await.unwrap();
+}
+
+#[actix_web::test]
+async fn post_gpkg_with_multiple_layers_requires_layer_selector() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let gpkg = create_gpkg_with_layers(&["boundary", "red_line"]);
+    let form = add_gpkg_to_form(gpkg, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string());
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    check_error_response(response, 422).await;
+}
+
+#[actix_web::test]
+async fn post_zipped_shapefile_lists_candidates_and_accepts_layer_selector() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let zip = std::fs::read("../test-data/zips/multiple_shapefiles.zip").unwrap();
+
+    let form = add_zip_to_form(zip.clone(), reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string());
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let error = check_error_response(response, 422).await;
+    let mut layers = error.layers.expect("expected candidate layers");
+    layers.sort();
+    assert_eq!(layers, vec!["access/tracks", "boundary"]);
+
+    let form = add_zip_to_form(zip, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("layer", "access/tracks");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let _feature_id: FeatureId = handle_json_response(response).await.unwrap();
+}
+
+#[actix_web::test]
+async fn post_geojson_feature_collection_works() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPoint, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let body = json!({
+        "type": "FeatureCollection",
+        "name": "turbines",
+        "crs": 27700,
+        "features": [
+            {"type": "Feature", "properties": {"hub_height": 90}, "geometry": {"type": "Point", "coordinates": [300000, 600000]}},
+            {"type": "Feature", "properties": {"hub_height": 95}, "geometry": {"type": "Point", "coordinates": [300500, 600000]}}
+        ]
+    });
+    let response = app
+        .features_service
+        .post_geojson(
+            &app.api_client,
+            &body,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let feature_id: FeatureId = handle_json_response(response).await.unwrap();
+    let response = app
+        .ogc_service
+        .get_project_feature(&app.api_client, project_id, collection_id, feature_id)
+        .await;
+    let ogc_ft: ogc::features::Feature = handle_json_response(response).await.unwrap();
+    assert_eq!(ogc_ft.properties.get("name"), Some(&json!("turbines")));
+    assert_eq!(ogc_ft.properties.get("hub_height"), Some(&json!([90, 95])));
+}
+
+#[actix_web::test]
+async fn post_geojson_geometry_rejects_incompatible_type() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let body = json!({
+        "type": "Point",
+        "coordinates": [-3.0, 52.0],
+        "name": uuid::Uuid::new_v4().to_string()
+    });
+    let response = app
+        .features_service
+        .post_geojson(
+            &app.api_client,
+            &body,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    check_error_response(response, 422).await;
+}
+
+#[actix_web::test]
+async fn post_kml_works() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let kml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2">
+  <Document>
+    <Folder>
+      <name>Landowner boundary</name>
+      <Placemark>
+        <name>Field 1</name>
+        <Polygon><outerBoundaryIs><LinearRing>
+          <coordinates>-3.0,52.0,0 -2.9,52.0,0 -2.9,52.1,0 -3.0,52.1,0 -3.0,52.0,0</coordinates>
+        </LinearRing></outerBoundaryIs></Polygon>
+      </Placemark>
+    </Folder>
+  </Document>
+</kml>"#;
+    let form = reqwest::multipart::Form::new()
+        .part(
+            "kml",
+            reqwest::multipart::Part::bytes(kml.as_bytes().to_vec())
+                .file_name("boundary.kml")
+                .mime_str("application/vnd.google-earth.kml+xml")
+                .expect("failed to add kml part"),
+        )
+        .text("name", uuid::Uuid::new_v4().to_string());
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let feature_id: FeatureId = handle_json_response(response).await.unwrap();
+    let response = app
+        .ogc_service
+        .get_project_feature(&app.api_client, project_id, collection_id, feature_id)
+        .await;
+    let ogc_ft: ogc::features::Feature = handle_json_response(response).await.unwrap();
+    assert_eq!(
+        ogc_ft.properties.get("folder"),
+        Some(&json!("Landowner boundary"))
+    );
+}
+
+#[actix_web::test]
+async fn post_shapefile_over_upload_limit_returns_413() {
+    let app = AppBuilder::new()
+        .set_upload_settings(UploadSettings {
+            feature_upload_limit_bytes: 1024,
+            ..Default::default()
+        })
+        .build()
+        .await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let form = add_shapefile_to_form(
+        "test",
+        read_shapefile_fixture("3_valid_polygon_osgb36"),
+        reqwest::multipart::Form::new(),
+    )
+    .text("name", uuid::Uuid::new_v4().to_string());
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let error = check_error_response(response, 413).await;
+    assert!(error.message.contains("1024 byte limit"));
+}
+
+#[actix_web::test]
+async fn post_geojson_over_upload_limit_returns_413() {
+    let app = AppBuilder::new()
+        .set_upload_settings(UploadSettings {
+            geojson_upload_limit_bytes: 64,
+            ..Default::default()
+        })
+        .build()
+        .await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let body = json!({
+        "type": "Point",
+        "name": "a point with a name long enough to go over the limit",
+        "coordinates": [-3.0, 52.0]
+    });
+    let response = app
+        .features_service
+        .post_geojson(
+            &app.api_client,
+            &body,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    check_error_response(response, 413).await;
+}
+
+#[actix_web::test]
+async fn post_shapefile_split_creates_one_feature_per_row() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPoint, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let shapefile_data = read_shapefile_fixture("welsh_places_cp1252");
+    let form = add_shapefile_to_form("places", shapefile_data, reqwest::multipart::Form::new())
+        .text("name", "places")
+        .text("mode", "split")
+        .text("name_field", "name");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let FeatureUploadResponse::Split { ids, repair } =
+        handle_json_response(response).await.unwrap()
+    else {
+        panic!("expected a split upload response");
+    };
+    assert_eq!(ids.len(), 5);
+    assert!(repair.is_none());
+    let response = app
+        .ogc_service
+        .get_project_feature(&app.api_client, project_id, collection_id, ids[0])
+        .await;
+    let ogc_ft: ogc::features::Feature = handle_json_response(response).await.unwrap();
+    assert_eq!(ogc_ft.properties.get("name"), Some(&json!("Ynys Môn")));
+}
+
+#[actix_web::test]
+async fn post_geojson_split_rolls_back_when_any_insert_fails() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let project = PostProjectPayload {
+        crs_srid: Some(27700),
+        ..Default::default()
+    };
+    let response = app
+        .projects_service
+        .post_json(&app.api_client, Some(&auth), &project)
+        .await;
+    let project_id: ProjectId = handle_json_response(response).await.unwrap();
+    // The second point can't be transformed into the project's CRS
+    let body = json!({
+        "type": "FeatureCollection",
+        "name": "turbines",
+        "mode": "split",
+        "features": [
+            {"type": "Feature", "properties": {}, "geometry": {"type": "Point", "coordinates": [-3.0, 52.0]}},
+            {"type": "Feature", "properties": {}, "geometry": {"type": "Point", "coordinates": [-3.0, 95.0]}}
+        ]
+    });
+    let response = app
+        .features_service
+        .post_geojson(
+            &app.api_client,
+            &body,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    assert!(!response.status().is_success());
+    let count: i64 = sqlx::query_scalar(
+        "SELECT count(*) FROM app.project_features WHERE project_id = $1 AND collection_id = $2",
+    )
+    .bind(project_id.0)
+    .bind(collection_id.0)
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[actix_web::test]
+async fn post_dxf_requires_srid_and_filters_cad_layers() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let dxf = std::fs::read("../test-data/dxf/site_layout.dxf").unwrap();
+
+    let form = add_dxf_to_form(dxf.clone(), reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("cad_layer", "SITE_BOUNDARY");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let error = check_error_response(response, 422).await;
+    assert!(error.long_message.contains("srid"));
+
+    let form = add_dxf_to_form(dxf.clone(), reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("cad_layer", "ROADS")
+        .text("srid", "27700");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let error = check_error_response(response, 422).await;
+    assert_eq!(
+        error.layers.expect("expected available cad layers"),
+        vec!["ACCESS_TRACK", "SITE_BOUNDARY", "TURBINES"]
+    );
+
+    let form = add_dxf_to_form(dxf, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("cad_layer", "SITE_BOUNDARY")
+        .text("cad_layer", "TURBINES")
+        .text("srid", "27700");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let feature_id: FeatureId = handle_json_response(response).await.unwrap();
+    let response = app
+        .ogc_service
+        .get_project_feature(&app.api_client, project_id, collection_id, feature_id)
+        .await;
+    let ogc_ft: ogc::features::Feature = handle_json_response(response).await.unwrap();
+    assert_eq!(
+        ogc_ft.properties.get("layer"),
+        Some(&json!(["SITE_BOUNDARY", "SITE_BOUNDARY", "TURBINES"]))
+    );
+}
+
+const SURVEY_POINTS_CSV: &str = "\
+Ref,Easting,Northing,Species,Count
+T1,400000,100000,Skylark,2
+T2,400050.5,100020,Meadow pipit,5
+T3,400100,100040,Skylark,1
+";
+
+#[actix_web::test]
+async fn post_csv_split_creates_named_points_with_properties() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let form = add_csv_to_form(SURVEY_POINTS_CSV, reqwest::multipart::Form::new())
+        .text("name", "survey")
+        .text("x_field", "Easting")
+        .text("y_field", "Northing")
+        .text("srid", "27700")
+        .text("mode", "split")
+        .text("name_field", "Ref");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let FeatureUploadResponse::Split { ids, .. } = handle_json_response(response).await.unwrap()
+    else {
+        panic!("expected a split upload response");
+    };
+    assert_eq!(ids.len(), 3);
+    let response = app
+        .ogc_service
+        .get_project_feature(&app.api_client, project_id, collection_id, ids[1])
+        .await;
+    let ogc_ft: ogc::features::Feature = handle_json_response(response).await.unwrap();
+    assert_eq!(ogc_ft.properties.get("name"), Some(&json!("T2")));
+    assert_eq!(
+        ogc_ft.properties.get("Species"),
+        Some(&json!("Meadow pipit"))
+    );
+    assert_eq!(ogc_ft.properties.get("Count"), Some(&json!(5)));
+    assert_eq!(ogc_ft.properties.get("Easting"), None);
+}
+
+#[actix_web::test]
+async fn post_csv_reports_rows_with_bad_coordinates() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPoint, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let csv = "Ref,Easting,Northing\nT1,400000,100000\nT2,n/a,100020\nT3,400100,\n";
+    let form = add_csv_to_form(csv, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("x_field", "Easting")
+        .text("y_field", "Northing")
+        .text("srid", "27700");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let error = check_error_response(response, 422).await;
+    assert!(error.long_message.contains("row 3 (x: 'n/a'"));
+    assert!(error.long_message.contains("row 4"));
+}
+
+#[actix_web::test]
+async fn post_gpx_picks_layer_for_collection_geometry_type() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let gpx = std::fs::read("../test-data/gpx/walkover.gpx").unwrap();
+
+    let point_collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPoint, Some(&auth))
+        .await;
+    let line_collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::LineString, Some(&auth))
+        .await;
+    for (collection_id, expected) in [
+        (point_collection_id, ("ele", json!([312.5, 331.0, 322.0]))),
+        (line_collection_id, ("ele_max", json!(335.0))),
+    ] {
+        let form = add_gpx_to_form(gpx.clone(), reqwest::multipart::Form::new())
+            .text("name", uuid::Uuid::new_v4().to_string());
+        let response = app
+            .features_service
+            .post_form(
+                &app.api_client,
+                form,
+                format!("{}/{}", project_id, collection_id),
+                Some(&auth),
+            )
+            .await;
+        let feature_id: FeatureId = handle_json_response(response).await.unwrap();
+        let response = app
+            .ogc_service
+            .get_project_feature(&app.api_client, project_id, collection_id, feature_id)
+            .await;
+        let ogc_ft: ogc::features::Feature = handle_json_response(response).await.unwrap();
+        let (key, value) = expected;
+        assert_eq!(ogc_ft.properties.get(key), Some(&value));
+    }
+}
+
+#[actix_web::test]
+async fn post_shapefile_without_prj_uses_srid() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let without_prj = || {
+        let mut shapefile_data = read_shapefile_fixture("3_valid_polygon_osgb36");
+        shapefile_data.prj.clear();
+        shapefile_data
+    };
+
+    let form = add_shapefile_to_form("boundary", without_prj(), reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string());
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    check_error_response(response, 422).await;
+
+    let form = add_shapefile_to_form("boundary", without_prj(), reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("srid", "27700");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let _feature_id: FeatureId = handle_json_response(response).await.unwrap();
+}
+
+#[actix_web::test]
+async fn post_shapefile_srid_must_match_prj_unless_forced() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let form = add_shapefile_to_form(
+        "boundary",
+        read_shapefile_fixture("3_valid_polygon_osgb36"),
+        reqwest::multipart::Form::new(),
+    )
+    .text("name", uuid::Uuid::new_v4().to_string())
+    .text("srid", "3857");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let error = check_error_response(response, 422).await;
+    assert!(error.long_message.contains("force_srid"));
+
+    let shapefile_data = read_shapefile_fixture("3_valid_polygon_osgb36");
+    let form = add_shapefile_to_form("boundary", shapefile_data, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("srid", "3857")
+        .text("force_srid", "true");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let _feature_id: FeatureId = handle_json_response(response).await.unwrap();
+}
+
+#[actix_web::test]
+async fn post_shapefile_with_unknown_srid_is_rejected() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let mut shapefile_data = read_shapefile_fixture("3_valid_polygon_osgb36");
+    shapefile_data.prj.clear();
+    let form = add_shapefile_to_form("boundary", shapefile_data, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("srid", "999999");
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let error = check_error_response(response, 422).await;
+    assert_eq!(error.message, "Invalid Coordinate Reference System ID");
+}
+
+/// Post a single-feature shapefile of `geom` and return the dimension of the first
+/// position of the stored feature's GeoJSON geometry.
+async fn post_shapefile_position_len(
+    app: &TestApp<ClerkAuthService>,
+    auth: &Auth,
+    collection_id: ProjectCollectionId,
+    project_id: ProjectId,
+    geom: gdal::vector::Geometry,
+) -> usize {
+    let (mut dataset, filename) = create_shapefile_dataset();
+    let mut layer = add_layer(&mut dataset, geom.geometry_type(), 27700);
+    layer.create_feature(geom).expect("failed to add geom");
+    let form = add_shapefile_to_form(
+        "test",
+        dataset_to_shapefile_data(dataset, &filename),
+        reqwest::multipart::Form::new(),
+    )
+    .text("name", uuid::Uuid::new_v4().to_string());
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(auth),
+        )
+        .await;
+    let feature_id: FeatureId = handle_json_response(response).await.unwrap();
+    let response = app
+        .ogc_service
+        .get_project_feature(&app.api_client, project_id, collection_id, feature_id)
+        .await;
+    let ogc_ft: ogc::features::Feature = handle_json_response(response).await.unwrap();
+    match ogc_ft.geometry.expect("feature without geometry").value {
+        geojson::Value::MultiPolygon(polygons) => polygons[0][0][0].len(),
+        geojson::Value::MultiPoint(points) => points[0].len(),
+        value => panic!("unexpected geometry {value:?}"),
+    }
+}
+
+#[actix_web::test]
+async fn post_polygon_z_shapefile_keeps_z_only_when_collection_stores_it() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let polygon_z = || {
+        gdal::vector::Geometry::from_wkt(
+            "POLYGON Z ((400000 100000 120, 400100 100000 125, 400100 100100 130, \
+             400000 100100 125, 400000 100000 120))",
+        )
+        .unwrap()
+    };
+
+    let flat_collection_id = app
+        .generate_project_collection_id_with_dimension(
+            GeometryType::MultiPolygon,
+            CoordinateDimension::Xy,
+            Some(&auth),
+        )
+        .await;
+    let len =
+        post_shapefile_position_len(&app, &auth, flat_collection_id, project_id, polygon_z()).await;
+    assert_eq!(len, 2);
+
+    let z_collection_id = app
+        .generate_project_collection_id_with_dimension(
+            GeometryType::MultiPolygon,
+            CoordinateDimension::Xyz,
+            Some(&auth),
+        )
+        .await;
+    let len =
+        post_shapefile_position_len(&app, &auth, z_collection_id, project_id, polygon_z()).await;
+    assert_eq!(len, 3);
+}
+
+#[actix_web::test]
+async fn post_point_zm_shapefile_drops_m() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let point_zm = || gdal::vector::Geometry::from_wkt("POINT ZM (400000 100000 95 12)").unwrap();
+
+    let flat_collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPoint, Some(&auth))
+        .await;
+    let len =
+        post_shapefile_position_len(&app, &auth, flat_collection_id, project_id, point_zm()).await;
+    assert_eq!(len, 2);
+
+    let z_collection_id = app
+        .generate_project_collection_id_with_dimension(
+            GeometryType::MultiPoint,
+            CoordinateDimension::Xyz,
+            Some(&auth),
+        )
+        .await;
+    let len =
+        post_shapefile_position_len(&app, &auth, z_collection_id, project_id, point_zm()).await;
+    assert_eq!(len, 3);
+}
@@ -0,0 +1,146 @@
+use app::handlers::api::features::validate::ValidationReport;
+use domain::enums::GeometryType;
+use gdal::vector::{Geometry, LayerAccess, OGRwkbGeometryType};
+
+use crate::common::{
+    AppBuilder, Auth,
+    helpers::{
+        add_gpkg_to_form, add_layer, add_shapefile_to_form, create_gpkg_with_layers,
+        create_shapefile_dataset, dataset_to_shapefile_data, handle_json_response,
+        read_shapefile_fixture,
+    },
+};
+
+#[actix_web::test]
+async fn validate_reports_every_invalid_feature() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let (mut dataset, filename) = create_shapefile_dataset();
+    let mut layer = add_layer(&mut dataset, OGRwkbGeometryType::wkbPolygon, 27700);
+    for wkt in [
+        "POLYGON((0 0, 100 100, 100 0, 0 100, 0 0))",
+        "POLYGON((1000 0, 1100 0, 1100 100, 1000 100, 1000 0))",
+        "POLYGON((2000 0, 2100 100, 2100 0, 2000 100, 2000 0))",
+    ] {
+        layer
+            .create_feature(Geometry::from_wkt(wkt).expect("failed to create polygon"))
+            .expect("failed to add feature");
+    }
+    let shapefile_data = dataset_to_shapefile_data(dataset, &filename);
+    let form = add_shapefile_to_form("test", shapefile_data, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string());
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}/validate", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let report: ValidationReport = handle_json_response(response).await.unwrap();
+    assert!(!report.valid);
+    assert_eq!(report.format, "shp");
+    assert_eq!(report.srid, Some(27700));
+    assert_eq!(report.feature_count, 3);
+    let indices: Vec<_> = report.problems.iter().map(|p| p.index).collect();
+    assert_eq!(indices, vec![Some(0), Some(2)]);
+    assert!(report.bbox.is_none());
+}
+
+#[actix_web::test]
+async fn validate_reports_extent_and_size_of_valid_upload() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let form = add_shapefile_to_form(
+        "test",
+        read_shapefile_fixture("3_valid_polygon_osgb36"),
+        reqwest::multipart::Form::new(),
+    )
+    .text("name", uuid::Uuid::new_v4().to_string());
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}/validate", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let report: ValidationReport = handle_json_response(response).await.unwrap();
+    assert!(report.valid, "unexpected problems: {:?}", report.problems);
+    assert_eq!(report.feature_count, 3);
+    assert_eq!(report.bbox.map(|bbox| bbox.len()), Some(4));
+    assert!(report.area.is_some_and(|area| area > 0.0));
+}
+
+#[actix_web::test]
+async fn validate_reports_layers_to_pick_from_without_a_name() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPolygon, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let gpkg = create_gpkg_with_layers(&["boundary", "red_line"]);
+    let form = add_gpkg_to_form(gpkg, reqwest::multipart::Form::new());
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}/validate", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let report: ValidationReport = handle_json_response(response).await.unwrap();
+    assert!(!report.valid);
+    assert_eq!(report.format, "gpkg");
+    assert!(report.layer.is_none());
+    assert_eq!(
+        report.layers,
+        Some(vec!["boundary".to_string(), "red_line".to_string()])
+    );
+    assert_eq!(report.problems.len(), 1);
+    assert_eq!(report.problems[0].index, None);
+}
+
+#[actix_web::test]
+async fn validate_reports_z_and_m_values() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPoint, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let geom = Geometry::from_wkt("POINT ZM (400000 100000 95 12)").unwrap();
+    let (mut dataset, filename) = create_shapefile_dataset();
+    let mut layer = add_layer(&mut dataset, geom.geometry_type(), 27700);
+    layer.create_feature(geom).expect("failed to add feature");
+    let form = add_shapefile_to_form(
+        "test",
+        dataset_to_shapefile_data(dataset, &filename),
+        reqwest::multipart::Form::new(),
+    );
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}/validate", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let report: ValidationReport = handle_json_response(response).await.unwrap();
+    assert!(report.valid, "unexpected problems: {:?}", report.problems);
+    assert!(report.has_z);
+    assert!(report.has_m);
+}
@@ -0,0 +1,270 @@
+use app::handlers::api::projects::PostProjectPayload;
+use domain::{FeatureId, ProjectId, enums::GeometryType};
+use gdal::{
+    Dataset,
+    vector::{LayerAccess, OGRFieldType},
+};
+use geo::virtual_shapefile::VirtualFile;
+use serde_json::json;
+
+use crate::common::{
+    AppBuilder, Auth,
+    helpers::{assert_ok, check_error_response, handle_json_response},
+};
+
+#[actix_web::test]
+async fn get_project_gpkg_has_layer_per_collection() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let project = PostProjectPayload {
+        crs_srid: Some(27700),
+        ..Default::default()
+    };
+    let response = app
+        .projects_service
+        .post_json(&app.api_client, Some(&auth), &project)
+        .await;
+    let project_id: ProjectId = handle_json_response(response).await.unwrap();
+    app.generate_primary_boundary_id(project_id, Some(&auth))
+        .await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let body = json!({
+        "type": "Feature",
+        "properties": {"hub_height": 90, "model": "V150"},
+        "geometry": {"type": "Point", "coordinates": [-3.0, 52.0]},
+        "name": "T1"
+    });
+    let response = app
+        .features_service
+        .post_geojson(
+            &app.api_client,
+            &body,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let _feature_id: FeatureId = handle_json_response(response).await.unwrap();
+
+    let response = app
+        .projects_service
+        .get_one(
+            &app.api_client,
+            Some(&auth),
+            format!("{project_id}/export.gpkg"),
+        )
+        .await;
+    assert_ok(&response);
+    let disposition = response
+        .headers()
+        .get("content-disposition")
+        .expect("no content disposition")
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(disposition.starts_with("attachment"));
+    assert!(disposition.contains(".gpkg"));
+    let body = response.bytes().await.expect("no response body");
+    let filename = format!("{}.gpkg", uuid::Uuid::new_v4());
+    let _virtual_file =
+        VirtualFile::new(&filename, body.to_vec()).expect("failed to create virtual file");
+    let dataset = Dataset::open(format!("/vsimem/{filename}")).expect("failed to open geopackage");
+    assert_eq!(dataset.layer_count(), 2);
+
+    let boundaries = dataset
+        .layer_by_name("site-boundaries")
+        .expect("no site boundaries layer");
+    assert_eq!(boundaries.feature_count(), 1);
+    let srs = boundaries.spatial_ref().expect("layer has no spatial ref");
+    assert_eq!(srs.auth_code().unwrap(), 27700);
+
+    let mut turbines = dataset
+        .layers()
+        .find(|layer| layer.name() != "site-boundaries")
+        .expect("no turbines layer");
+    let fields: Vec<_> = turbines
+        .defn()
+        .fields()
+        .map(|field| (field.name(), field.field_type()))
+        .collect();
+    for system_field in ["is_primary", "status", "added_by", "last_updated"] {
+        assert!(fields.iter().any(|(name, _)| name == system_field));
+    }
+    assert!(fields.contains(&("hub_height".to_string(), OGRFieldType::OFTInteger64)));
+    let feature = turbines.features().next().expect("no features");
+    assert_eq!(
+        feature.field_as_string_by_name("model").unwrap().as_deref(),
+        Some("V150")
+    );
+    assert_eq!(
+        feature
+            .field_as_string_by_name("status")
+            .unwrap()
+            .as_deref(),
+        Some("ACTIVE")
+    );
+}
+
+#[actix_web::test]
+async fn get_project_gpkg_returns_404_for_unknown_project() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let response = app
+        .projects_service
+        .get_one(&app.api_client, Some(&auth), "999999/export.gpkg")
+        .await;
+    check_error_response(response, 404).await;
+}
+
+#[actix_web::test]
+async fn get_project_kmz_has_placemark_per_feature() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let project = PostProjectPayload {
+        crs_srid: Some(27700),
+        ..Default::default()
+    };
+    let response = app
+        .projects_service
+        .post_json(&app.api_client, Some(&auth), &project)
+        .await;
+    let project_id: ProjectId = handle_json_response(response).await.unwrap();
+    app.generate_primary_boundary_id(project_id, Some(&auth))
+        .await;
+
+    let response = app
+        .projects_service
+        .get_one(
+            &app.api_client,
+            Some(&auth),
+            format!("{project_id}/export.kmz"),
+        )
+        .await;
+    assert_ok(&response);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/vnd.google-earth.kmz"
+    );
+    let body = response.bytes().await.expect("no response body");
+    let filename = format!("{}.kmz", uuid::Uuid::new_v4());
+    let _virtual_file =
+        VirtualFile::new(&filename, body.to_vec()).expect("failed to create virtual file");
+    let dataset =
+        Dataset::open(format!("/vsizip//vsimem/{filename}/doc.kml")).expect("failed to open kml");
+
+    let mut boundaries = dataset
+        .layer_by_name("site-boundaries")
+        .expect("no site boundaries layer");
+    let srs = boundaries.spatial_ref().expect("layer has no spatial ref");
+    assert_eq!(srs.auth_code().unwrap(), 4326);
+    let feature = boundaries.features().next().expect("no placemarks");
+    assert!(
+        feature
+            .field_as_string_by_name("Name")
+            .unwrap()
+            .is_some_and(|name| !name.is_empty())
+    );
+}
+
+#[actix_web::test]
+async fn get_project_kmz_returns_404_for_unknown_project() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let response = app
+        .projects_service
+        .get_one(&app.api_client, Some(&auth), "999999/export.kmz")
+        .await;
+    check_error_response(response, 404).await;
+}
+
+#[actix_web::test]
+async fn get_project_dxf_puts_collections_on_cad_layers() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let project = PostProjectPayload {
+        crs_srid: Some(27700),
+        ..Default::default()
+    };
+    let response = app
+        .projects_service
+        .post_json(&app.api_client, Some(&auth), &project)
+        .await;
+    let project_id: ProjectId = handle_json_response(response).await.unwrap();
+    app.generate_primary_boundary_id(project_id, Some(&auth))
+        .await;
+
+    let response = app
+        .projects_service
+        .get_one(
+            &app.api_client,
+            Some(&auth),
+            format!("{project_id}/export.dxf"),
+        )
+        .await;
+    assert_ok(&response);
+    let body = response.bytes().await.expect("no response body");
+    let filename = format!("{}.dxf", uuid::Uuid::new_v4());
+    let _virtual_file =
+        VirtualFile::new(&filename, body.to_vec()).expect("failed to create virtual file");
+    let dataset = Dataset::open(format!("/vsimem/{filename}")).expect("failed to open dxf");
+
+    let mut entities = dataset.layer(0).expect("no entities layer");
+    let features: Vec<_> = entities.features().collect();
+    assert!(!features.is_empty());
+    for feature in &features {
+        assert_eq!(
+            feature.field_as_string_by_name("Layer").unwrap().as_deref(),
+            Some("site-boundaries")
+        );
+    }
+    // Coordinates stay in British National Grid rather than WGS 84
+    let geometry = features[0].geometry().expect("entity has no geometry");
+    assert!(geometry.envelope().MinX > 180.0);
+}
+
+#[actix_web::test]
+async fn get_project_bundle_has_manifest_and_layers() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let project = PostProjectPayload {
+        crs_srid: Some(27700),
+        ..Default::default()
+    };
+    let response = app
+        .projects_service
+        .post_json(&app.api_client, Some(&auth), &project)
+        .await;
+    let project_id: ProjectId = handle_json_response(response).await.unwrap();
+    app.generate_primary_boundary_id(project_id, Some(&auth))
+        .await;
+
+    let response = app
+        .projects_service
+        .get_one(
+            &app.api_client,
+            Some(&auth),
+            format!("{project_id}/export.zip"),
+        )
+        .await;
+    assert_ok(&response);
+    let body = response.bytes().await.expect("no response body");
+    let mut archive =
+        zip::ZipArchive::new(std::io::Cursor::new(body.to_vec())).expect("bundle is not a zip");
+    let names: Vec<_> = archive.file_names().map(str::to_string).collect();
+    assert!(names.iter().any(|name| name.ends_with(".gpkg")));
+    assert!(names.contains(&"shapefiles/site-boundaries.shp".to_string()));
+    assert!(names.contains(&"shapefiles/site-boundaries.qml".to_string()));
+
+    let manifest: serde_json::Value =
+        serde_json::from_reader(archive.by_name("manifest.json").expect("no manifest"))
+            .expect("manifest is not json");
+    assert_eq!(manifest["crs"], "EPSG:27700");
+    assert_eq!(manifest["project"]["crs_srid"], 27700);
+    assert!(manifest["exported_by"]["name"].is_string());
+    let collections = manifest["collections"].as_array().unwrap();
+    assert_eq!(collections.len(), 1);
+    assert_eq!(collections[0]["slug"], "site-boundaries");
+    assert_eq!(collections[0]["feature_count"], 1);
+    assert!(collections[0]["title"].is_string());
+}
@@ -1,9 +1,10 @@
-use domain::enums::CollectionId;
+use domain::enums::{CollectionId, GeometryType};
 use ogc::features::filtering::Queryables;
+use serde_json::json;
 
 use crate::common::{
-    TestApp,
-    helpers::{assert_ok, handle_json_response},
+    Auth, TestApp,
+    helpers::{assert_ok, assert_status, handle_json_response},
 };
 
 #[actix_web::test]
@@ -18,3 +19,84 @@ async fn get_projects_queryables_works() {
         .await
         .expect("failed to deserailize json");
 }
+
+#[actix_web::test]
+async fn get_gis_data_table_queryables_describes_its_columns() {
+    let app = TestApp::spawn_with_db().await;
+    let table_name = app.generate_gis_data_table_name().await;
+
+    let response = app
+        .ogc_service
+        .get_collection_queryables(
+            &app.api_client,
+            CollectionId::DatabaseTable(table_name),
+            None,
+        )
+        .await;
+
+    assert_ok(&response);
+    let queryables: Queryables = handle_json_response(response)
+        .await
+        .expect("failed to deserailize json");
+    assert_eq!(
+        queryables.properties["gid"].r#type.as_deref(),
+        Some("number")
+    );
+    assert_eq!(
+        queryables.properties["some_text"].r#type.as_deref(),
+        Some("string")
+    );
+    assert_eq!(
+        queryables.properties["geometry"].format.as_deref(),
+        Some("geometry-any")
+    );
+}
+
+#[actix_web::test]
+async fn get_project_collection_queryables_includes_feature_properties() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let body = json!({
+        "type": "Feature",
+        "properties": {"name": "T1", "hub_height": 90, "model": "V150"},
+        "geometry": {"type": "Point", "coordinates": [-3.0, 52.0]},
+    });
+    let response = app
+        .ogc_service
+        .post_project_feature(
+            &app.api_client,
+            project_id,
+            collection_id,
+            &body,
+            Some(&auth),
+        )
+        .await;
+    assert_status(&response, 201);
+
+    let response = app
+        .ogc_service
+        .get_project_collection_queryables(&app.api_client, project_id, collection_id)
+        .await;
+
+    assert_ok(&response);
+    let queryables: Queryables = handle_json_response(response)
+        .await
+        .expect("failed to deserailize json");
+    assert_eq!(
+        queryables.properties["last_updated"].format.as_deref(),
+        Some("date-time")
+    );
+    assert!(queryables.properties["status"].r#enum.is_some());
+    assert_eq!(
+        queryables.properties["hub_height"].r#type.as_deref(),
+        Some("number")
+    );
+    assert_eq!(
+        queryables.properties["model"].r#type.as_deref(),
+        Some("string")
+    );
+}
@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::common::{
     TestApp,
-    helpers::{assert_ok, generate_random_wgs84_point_ewkt, handle_json_response},
+    helpers::{
+        assert_ok, check_error_response, generate_random_wgs84_point_ewkt, handle_json_response,
+    },
 };
 
 #[derive(Serialize, Deserialize)]
@@ -84,3 +86,83 @@ async fn get_features_works_with_limit() {
         .expect("Failed to retrieve feature collection");
     assert_eq!(feature_collection.features.len(), limit);
 }
+
+#[actix_web::test]
+async fn get_features_selects_properties_and_skips_geometry() {
+    let text = uuid::Uuid::new_v4().to_string();
+    let app = TestApp::spawn_with_db().await;
+    let table_name = app.generate_gis_data_table_name().await;
+    let (_, _, ewkt) = generate_random_wgs84_point_ewkt();
+    let _feature = app.insert_feature(&table_name, &ewkt, &text).await;
+
+    let response = app
+        .ogc_service
+        .get_features_with_params(
+            &app.api_client,
+            table_name.as_ref(),
+            &[("skipGeometry", "true"), ("properties", "some_text")],
+        )
+        .await;
+    let feature_collection: ogc::FeatureCollection = handle_json_response(response)
+        .await
+        .expect("failed to retrieve feature collection");
+    let ogc_feature = feature_collection.features.into_iter().next().unwrap();
+    assert!(ogc_feature.geometry.is_none());
+    assert_eq!(ogc_feature.properties["some_text"], text.as_str());
+}
+
+#[actix_web::test]
+async fn get_features_returns_400_for_unknown_properties() {
+    let app = TestApp::spawn_with_db().await;
+    let table_name = app.generate_gis_data_table_name().await;
+    let response = app
+        .ogc_service
+        .get_features_with_params(
+            &app.api_client,
+            table_name.as_ref(),
+            &[("properties", "some_text,missing")],
+        )
+        .await;
+    check_error_response(response, 400).await;
+}
+
+#[actix_web::test]
+async fn get_features_sorts_by_sortby() {
+    let app = TestApp::spawn_with_db().await;
+    let table_name = app.generate_gis_data_table_name().await;
+    for text in ["b", "c", "a"] {
+        let (_, _, ewkt) = generate_random_wgs84_point_ewkt();
+        let _feature = app.insert_feature(&table_name, &ewkt, text).await;
+    }
+
+    let response = app
+        .ogc_service
+        .get_features_with_params(
+            &app.api_client,
+            table_name.as_ref(),
+            &[("sortby", "-some_text")],
+        )
+        .await;
+    let feature_collection: ogc::FeatureCollection = handle_json_response(response)
+        .await
+        .expect("failed to retrieve feature collection");
+    let texts: Vec<_> = feature_collection
+        .features
+        .iter()
+        .map(|feature| feature.properties["some_text"].clone())
+        .collect();
+    assert_eq!(texts, ["c", "b", "a"]);
+}
+
+#[actix_web::test]
+async fn get_features_returns_400_for_unknown_sortby() {
+    let app = TestApp::spawn_with_db().await;
+    let table_name = app.generate_gis_data_table_name().await;
+    for sortby in ["missing", "geom", "some_text; DROP TABLE x"] {
+        let response = app
+            .ogc_service
+            .get_features_with_params(&app.api_client, table_name.as_ref(), &[("sortby", sortby)])
+            .await;
+        check_error_response(response, 400).await;
+    }
+}
@@ -0,0 +1,338 @@
+use domain::{
+    FeatureId, ProjectCollectionId, ProjectFeature, ProjectFeatureId, ProjectId,
+    enums::GeometryType,
+};
+use reqwest::Response;
+use serde_json::json;
+
+use crate::common::{
+    Auth, TestApp,
+    helpers::{assert_ok, assert_status, check_error_response, handle_json_response},
+};
+
+async fn post_turbine(
+    app: &TestApp,
+    auth: &Auth,
+    project_id: ProjectId,
+    collection_id: ProjectCollectionId,
+) -> ProjectFeatureId {
+    let body = json!({
+        "type": "Feature",
+        "properties": {"name": "T1", "hub_height": 90, "model": "V150"},
+        "geometry": {"type": "Point", "coordinates": [-3.0, 52.0]},
+    });
+    let response = app
+        .ogc_service
+        .post_project_feature(
+            &app.api_client,
+            project_id,
+            collection_id,
+            &body,
+            Some(auth),
+        )
+        .await;
+    assert_status(&response, 201);
+    let location = response.headers()["location"].to_str().unwrap();
+    let feature_id = location.rsplit('/').next().unwrap().parse().unwrap();
+    ProjectFeatureId {
+        collection_id,
+        feature_id: FeatureId(feature_id),
+    }
+}
+
+/// The feature and its entity tag
+async fn get_feature(
+    app: &TestApp,
+    project_id: ProjectId,
+    id: ProjectFeatureId,
+) -> (ProjectFeature, String) {
+    let response = app
+        .ogc_service
+        .get_project_feature(&app.api_client, project_id, id.collection_id, id.feature_id)
+        .await;
+    assert_ok(&response);
+    let etag = etag(&response);
+    let feature: ogc::Feature = handle_json_response(response).await.unwrap();
+    (feature.try_into().unwrap(), etag)
+}
+
+fn etag(response: &Response) -> String {
+    response.headers()["etag"].to_str().unwrap().to_string()
+}
+
+#[actix_web::test]
+async fn post_project_feature_creates_a_feature() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+
+    let id = post_turbine(&app, &auth, project_id, collection_id).await;
+    let (feature, _etag) = get_feature(&app, project_id, id).await;
+
+    assert_eq!(feature.properties.name, "T1");
+    assert_eq!(feature.properties_map["hub_height"], 90);
+}
+
+#[actix_web::test]
+async fn post_project_feature_returns_401_without_a_user() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let body = json!({
+        "type": "Feature",
+        "properties": {"name": "T1"},
+        "geometry": {"type": "Point", "coordinates": [-3.0, 52.0]},
+    });
+
+    let response = app
+        .ogc_service
+        .post_project_feature(&app.api_client, project_id, collection_id, &body, None)
+        .await;
+
+    check_error_response(response, 401).await;
+}
+
+#[actix_web::test]
+async fn put_project_feature_replaces_properties() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let id = post_turbine(&app, &auth, project_id, collection_id).await;
+    let (_feature, etag) = get_feature(&app, project_id, id).await;
+
+    let body = json!({
+        "type": "Feature",
+        "properties": {"name": "T2", "is_primary": false, "model": "SG 6.6"},
+        "geometry": {"type": "Point", "coordinates": [-3.5, 52.5]},
+    });
+    let response = app
+        .ogc_service
+        .put_project_feature(
+            &app.api_client,
+            id,
+            project_id,
+            &body,
+            Some(&etag),
+            Some(&auth),
+        )
+        .await;
+    assert_status(&response, 204);
+    let new_etag = self::etag(&response);
+
+    let (feature, current_etag) = get_feature(&app, project_id, id).await;
+    assert_eq!(feature.properties.name, "T2");
+    assert_eq!(feature.properties_map["model"], "SG 6.6");
+    assert!(!feature.properties_map.contains_key("hub_height"));
+    assert_eq!(new_etag, current_etag);
+    assert_ne!(etag, current_etag);
+}
+
+#[actix_web::test]
+async fn put_project_feature_returns_412_for_a_stale_etag() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let id = post_turbine(&app, &auth, project_id, collection_id).await;
+    let (_feature, etag) = get_feature(&app, project_id, id).await;
+    let patch = json!({"properties": {"model": "V162"}});
+    let response = app
+        .ogc_service
+        .patch_project_feature(
+            &app.api_client,
+            id,
+            project_id,
+            &patch,
+            Some(&etag),
+            Some(&auth),
+        )
+        .await;
+    assert_status(&response, 204);
+
+    let body = json!({
+        "type": "Feature",
+        "properties": {"name": "T2", "is_primary": false},
+        "geometry": {"type": "Point", "coordinates": [-3.5, 52.5]},
+    });
+    let response = app
+        .ogc_service
+        .put_project_feature(
+            &app.api_client,
+            id,
+            project_id,
+            &body,
+            Some(&etag),
+            Some(&auth),
+        )
+        .await;
+
+    check_error_response(response, 412).await;
+}
+
+#[actix_web::test]
+async fn put_project_feature_returns_400_without_name_or_is_primary() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let id = post_turbine(&app, &auth, project_id, collection_id).await;
+    let (_feature, etag) = get_feature(&app, project_id, id).await;
+
+    for properties in [json!({"name": "T2"}), json!({"is_primary": false})] {
+        let body = json!({
+            "type": "Feature",
+            "properties": properties,
+            "geometry": {"type": "Point", "coordinates": [-3.5, 52.5]},
+        });
+        let response = app
+            .ogc_service
+            .put_project_feature(
+                &app.api_client,
+                id,
+                project_id,
+                &body,
+                Some(&etag),
+                Some(&auth),
+            )
+            .await;
+        check_error_response(response, 400).await;
+    }
+    let (feature, _etag) = get_feature(&app, project_id, id).await;
+    assert_eq!(feature.properties.name, "T1");
+}
+
+#[actix_web::test]
+async fn patch_project_feature_merges_properties() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let id = post_turbine(&app, &auth, project_id, collection_id).await;
+    let (_feature, etag) = get_feature(&app, project_id, id).await;
+
+    let patch = json!({"properties": {"model": "V162", "hub_height": null, "rotor": 162}});
+    let response = app
+        .ogc_service
+        .patch_project_feature(
+            &app.api_client,
+            id,
+            project_id,
+            &patch,
+            Some(&etag),
+            Some(&auth),
+        )
+        .await;
+    assert_status(&response, 204);
+
+    let (feature, _etag) = get_feature(&app, project_id, id).await;
+    assert_eq!(feature.properties.name, "T1");
+    assert_eq!(feature.properties_map["model"], "V162");
+    assert_eq!(feature.properties_map["rotor"], 162);
+    assert!(!feature.properties_map.contains_key("hub_height"));
+}
+
+#[actix_web::test]
+async fn delete_project_feature_removes_the_feature() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let id = post_turbine(&app, &auth, project_id, collection_id).await;
+    let (_feature, etag) = get_feature(&app, project_id, id).await;
+
+    let response = app
+        .ogc_service
+        .delete_project_feature(&app.api_client, id, project_id, Some(&etag), Some(&auth))
+        .await;
+    assert_status(&response, 204);
+
+    let response = app
+        .ogc_service
+        .get_project_feature(&app.api_client, project_id, id.collection_id, id.feature_id)
+        .await;
+    check_error_response(response, 404).await;
+}
+
+#[actix_web::test]
+async fn delete_project_feature_returns_412_for_a_stale_etag() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let id = post_turbine(&app, &auth, project_id, collection_id).await;
+    let (_feature, etag) = get_feature(&app, project_id, id).await;
+    let patch = json!({"properties": {"model": "V162"}});
+    let response = app
+        .ogc_service
+        .patch_project_feature(
+            &app.api_client,
+            id,
+            project_id,
+            &patch,
+            Some(&etag),
+            Some(&auth),
+        )
+        .await;
+    assert_status(&response, 204);
+
+    let response = app
+        .ogc_service
+        .delete_project_feature(&app.api_client, id, project_id, Some(&etag), Some(&auth))
+        .await;
+
+    check_error_response(response, 412).await;
+}
+
+#[actix_web::test]
+async fn edits_return_428_without_if_match() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    let id = post_turbine(&app, &auth, project_id, collection_id).await;
+    let body = json!({
+        "type": "Feature",
+        "properties": {"name": "T2", "is_primary": false},
+        "geometry": {"type": "Point", "coordinates": [-3.5, 52.5]},
+    });
+
+    let response = app
+        .ogc_service
+        .put_project_feature(&app.api_client, id, project_id, &body, None, Some(&auth))
+        .await;
+    check_error_response(response, 428).await;
+    let response = app
+        .ogc_service
+        .patch_project_feature(&app.api_client, id, project_id, &body, None, Some(&auth))
+        .await;
+    check_error_response(response, 428).await;
+    let response = app
+        .ogc_service
+        .delete_project_feature(&app.api_client, id, project_id, None, Some(&auth))
+        .await;
+    check_error_response(response, 428).await;
+
+    let (feature, _etag) = get_feature(&app, project_id, id).await;
+    assert_eq!(feature.properties.name, "T1");
+}
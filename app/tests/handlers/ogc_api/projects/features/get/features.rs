@@ -1,8 +1,9 @@
-use domain::ProjectId;
+use domain::{FeatureId, ProjectCollectionId, ProjectId, enums::GeometryType};
+use serde_json::{Value, json};
 
 use crate::common::{
     Auth, TestApp,
-    helpers::{assert_ok, assert_status, handle_json_response},
+    helpers::{assert_ok, assert_status, check_error_response, handle_json_response},
 };
 
 // Als ensures only features relating to the relevant project are returned
@@ -73,3 +74,295 @@ async fn get_project_features_works_with_limit() {
         .expect("failed to retrieve features");
     assert_eq!(features.features.len(), limit)
 }
+
+async fn post_turbine(
+    app: &TestApp,
+    auth: &Auth,
+    project_id: ProjectId,
+    collection_id: ProjectCollectionId,
+    name: &str,
+    properties: Value,
+    coordinates: [f64; 2],
+) {
+    let body = json!({
+        "type": "Feature",
+        "name": name,
+        "properties": properties,
+        "geometry": {"type": "Point", "coordinates": coordinates},
+    });
+    let response = app
+        .features_service
+        .post_geojson(
+            &app.api_client,
+            &body,
+            format!("{}/{}", project_id, collection_id),
+            Some(auth),
+        )
+        .await;
+    let _feature_id: FeatureId = handle_json_response(response).await.unwrap();
+}
+
+#[actix_web::test]
+async fn get_project_features_applies_cql2_filters() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    for (name, properties, coordinates) in [
+        (
+            "T1",
+            json!({"hub_height": 90, "model": "V150"}),
+            [-3.0, 52.0],
+        ),
+        (
+            "T2",
+            json!({"hub_height": 120, "model": "V150"}),
+            [-2.0, 53.0],
+        ),
+        (
+            "T3",
+            json!({"hub_height": 120, "model": "SG"}),
+            [-2.0, 53.0],
+        ),
+    ] {
+        post_turbine(
+            &app,
+            &auth,
+            project_id,
+            collection_id,
+            name,
+            properties,
+            coordinates,
+        )
+        .await;
+    }
+
+    for (filter, filter_lang, expected) in [
+        ("model = 'V150' AND hub_height > 100", "cql2-text", "T2"),
+        (
+            "S_INTERSECTS(geometry, BBOX(-3.1, 51.9, -2.9, 52.1))",
+            "cql2-text",
+            "T1",
+        ),
+        (
+            r#"{"op": "=", "args": [{"property": "name"}, "T3"]}"#,
+            "cql2-json",
+            "T3",
+        ),
+    ] {
+        let response = app
+            .ogc_service
+            .get_project_features_with_params(
+                &app.api_client,
+                collection_id,
+                project_id,
+                &[("filter", filter), ("filter-lang", filter_lang)],
+            )
+            .await;
+        let features: ogc::FeatureCollection = handle_json_response(response)
+            .await
+            .expect("failed to retrieve features");
+        assert_eq!(features.features.len(), 1, "{filter}");
+        assert_eq!(features.features[0].properties["name"], expected);
+    }
+}
+
+#[actix_web::test]
+async fn get_project_features_returns_400_for_invalid_filter() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app.generate_project_collection_id(Some(&auth)).await;
+    for filter in ["hub_height >", "name = 3"] {
+        let response = app
+            .ogc_service
+            .get_project_features_with_params(
+                &app.api_client,
+                collection_id,
+                project_id,
+                &[("filter", filter)],
+            )
+            .await;
+        check_error_response(response, 400).await;
+    }
+}
+
+#[actix_web::test]
+async fn get_project_features_sorts_and_pages_by_sortby() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    for (name, hub_height) in [("T1", 90), ("T2", 120), ("T3", 100)] {
+        post_turbine(
+            &app,
+            &auth,
+            project_id,
+            collection_id,
+            name,
+            json!({"hub_height": hub_height}),
+            [-3.0, 52.0],
+        )
+        .await;
+    }
+
+    let response = app
+        .ogc_service
+        .get_project_features_with_params(
+            &app.api_client,
+            collection_id,
+            project_id,
+            &[("sortby", "-hub_height,name"), ("limit", "2")],
+        )
+        .await;
+    let page: ogc::FeatureCollection = handle_json_response(response)
+        .await
+        .expect("failed to retrieve features");
+    let names: Vec<_> = page
+        .features
+        .iter()
+        .map(|feature| feature.properties["name"].clone())
+        .collect();
+    assert_eq!(names, ["T2", "T3"]);
+
+    let next = page
+        .links
+        .iter()
+        .find(|link| link.rel == "next")
+        .expect("no next link");
+    let response = app
+        .api_client
+        .client
+        .get(&next.href)
+        .send()
+        .await
+        .expect("failed to execute request");
+    let page: ogc::FeatureCollection = handle_json_response(response)
+        .await
+        .expect("failed to retrieve features");
+    assert_eq!(page.features.len(), 1);
+    assert_eq!(page.features[0].properties["name"], "T1");
+}
+
+#[actix_web::test]
+async fn get_project_features_returns_400_for_invalid_sortby() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app.generate_project_collection_id(Some(&auth)).await;
+    let response = app
+        .ogc_service
+        .get_project_features_with_params(
+            &app.api_client,
+            collection_id,
+            project_id,
+            &[("sortby", "geometry")],
+        )
+        .await;
+    check_error_response(response, 400).await;
+}
+
+#[actix_web::test]
+async fn get_project_features_returns_400_for_unknown_sortby_properties() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    post_turbine(
+        &app,
+        &auth,
+        project_id,
+        collection_id,
+        "T1",
+        json!({"hub_height": 90}),
+        [-3.0, 52.0],
+    )
+    .await;
+    for sortby in ["rotor_diameter", "x' DESC; --"] {
+        let response = app
+            .ogc_service
+            .get_project_features_with_params(
+                &app.api_client,
+                collection_id,
+                project_id,
+                &[("sortby", sortby)],
+            )
+            .await;
+        check_error_response(response, 400).await;
+    }
+}
+
+#[actix_web::test]
+async fn get_project_features_selects_properties_and_skips_geometry() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    post_turbine(
+        &app,
+        &auth,
+        project_id,
+        collection_id,
+        "T1",
+        json!({"hub_height": 90, "model": "V150"}),
+        [-3.0, 52.0],
+    )
+    .await;
+
+    let response = app
+        .ogc_service
+        .get_project_features_with_params(
+            &app.api_client,
+            collection_id,
+            project_id,
+            &[("properties", "name,hub_height"), ("skipGeometry", "true")],
+        )
+        .await;
+    let features: ogc::FeatureCollection = handle_json_response(response)
+        .await
+        .expect("failed to retrieve features");
+    let feature = &features.features[0];
+    assert!(feature.geometry.is_none());
+    let mut names: Vec<_> = feature.properties.keys().collect();
+    names.sort();
+    assert_eq!(names, ["hub_height", "name"]);
+}
+
+#[actix_web::test]
+async fn get_project_features_returns_400_for_unknown_properties() {
+    let app = TestApp::spawn_with_db().await;
+    let auth = Auth::mock_session_token();
+    let project_id = app.generate_project_id(Some(&auth)).await;
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::Point, Some(&auth))
+        .await;
+    post_turbine(
+        &app,
+        &auth,
+        project_id,
+        collection_id,
+        "T1",
+        json!({"hub_height": 90}),
+        [-3.0, 52.0],
+    )
+    .await;
+
+    let response = app
+        .ogc_service
+        .get_project_features_with_params(
+            &app.api_client,
+            collection_id,
+            project_id,
+            &[("properties", "name,rotor_diameter")],
+        )
+        .await;
+    check_error_response(response, 400).await;
+}
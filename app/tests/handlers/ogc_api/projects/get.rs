@@ -3,7 +3,7 @@ use domain::project::Project;
 
 use crate::common::{
     Auth, TestApp,
-    helpers::{assert_ok, handle_json_response},
+    helpers::{assert_ok, check_error_response, handle_json_response},
 };
 
 #[actix_web::test]
@@ -75,6 +75,93 @@ async fn get_projects_works_with_limit() {
     assert_eq!(feature_collection.features.len(), limit)
 }
 
+#[actix_web::test]
+async fn get_projects_sorts_by_sortby() {
+    let app = TestApp::spawn_with_db().await;
+    for _ in 0..3 {
+        app.generate_project_id(Some(&Auth::mock_session_token()))
+            .await;
+    }
+
+    let response = app
+        .ogc_service
+        .get_features_with_params(
+            &app.api_client,
+            &CollectionId::Projects.to_string(),
+            &[("sortby", "-name")],
+        )
+        .await;
+    let feature_collection: ogc::FeatureCollection = handle_json_response(response)
+        .await
+        .expect("failed to retrieve projects");
+    let names: Vec<String> = feature_collection
+        .features
+        .into_iter()
+        .map(|f| {
+            Project::try_from(f)
+                .expect("failed to convert to project")
+                .properties
+                .name
+        })
+        .collect();
+    let mut sorted = names.clone();
+    sorted.sort_by(|a, b| b.cmp(a));
+    assert!(names.len() >= 3);
+    assert_eq!(names, sorted);
+}
+
+#[actix_web::test]
+async fn get_projects_returns_400_for_unknown_sortby() {
+    let app = TestApp::spawn_with_db().await;
+    let response = app
+        .ogc_service
+        .get_features_with_params(
+            &app.api_client,
+            &CollectionId::Projects.to_string(),
+            &[("sortby", "owner")],
+        )
+        .await;
+    check_error_response(response, 400).await;
+}
+
+#[actix_web::test]
+async fn get_projects_selects_properties_and_skips_geometry() {
+    let app = TestApp::spawn_with_db().await;
+    app.generate_project_id(Some(&Auth::mock_session_token()))
+        .await;
+
+    let response = app
+        .ogc_service
+        .get_features_with_params(
+            &app.api_client,
+            &CollectionId::Projects.to_string(),
+            &[("properties", "name,status"), ("skipGeometry", "true")],
+        )
+        .await;
+    let feature_collection: ogc::FeatureCollection = handle_json_response(response)
+        .await
+        .expect("failed to retrieve projects");
+    let feature = &feature_collection.features[0];
+    assert!(feature.geometry.is_none());
+    let mut names: Vec<_> = feature.properties.keys().collect();
+    names.sort();
+    assert_eq!(names, ["name", "status"]);
+}
+
+#[actix_web::test]
+async fn get_projects_returns_400_for_unknown_properties() {
+    let app = TestApp::spawn_with_db().await;
+    let response = app
+        .ogc_service
+        .get_features_with_params(
+            &app.api_client,
+            &CollectionId::Projects.to_string(),
+            &[("properties", "name,owner")],
+        )
+        .await;
+    check_error_response(response, 400).await;
+}
+
 #[actix_web::test]
 async fn get_project_has_centroid() {
     let app = TestApp::spawn_with_db().await;
@@ -0,0 +1,149 @@
+//! Checks that large uploads are streamed to disk rather than buffered in memory.
+//!
+//! This runs as its own test binary so that the allocation counter only sees the
+//! upload under test, not other tests running in parallel.
+#[allow(dead_code, unused_imports)]
+mod common;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use domain::{FeatureId, enums::GeometryType};
+use geo::virtual_shapefile::ShapefileData;
+
+use crate::common::{
+    AppBuilder, Auth,
+    helpers::{add_shapefile_to_form, handle_json_response},
+};
+
+/// Tracks bytes currently allocated on the Rust heap and the peak since the last reset
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const FEATURES: usize = 100_000;
+const DESCRIPTION_WIDTH: usize = 250;
+
+/// A point shapefile in EPSG:27700 whose size is dominated by a wide text column
+fn synthetic_shapefile() -> ShapefileData {
+    let shp_length = 100 + FEATURES * 28;
+    let shx_length = 100 + FEATURES * 8;
+    let header = |length: usize| {
+        let mut header = Vec::with_capacity(100);
+        header.extend_from_slice(&9994_i32.to_be_bytes());
+        header.extend_from_slice(&[0; 20]);
+        header.extend_from_slice(&((length / 2) as i32).to_be_bytes());
+        header.extend_from_slice(&1000_i32.to_le_bytes());
+        header.extend_from_slice(&1_i32.to_le_bytes());
+        for bound in [400_000.0, 100_000.0, 400_000.0 + FEATURES as f64, 101_000.0] {
+            header.extend_from_slice(&f64::to_le_bytes(bound));
+        }
+        header.extend_from_slice(&[0; 32]);
+        header
+    };
+
+    let mut shp = header(shp_length);
+    let mut shx = header(shx_length);
+    shp.reserve(shp_length - 100);
+    shx.reserve(shx_length - 100);
+    for i in 0..FEATURES {
+        let offset = 100 + i * 28;
+        shx.extend_from_slice(&((offset / 2) as i32).to_be_bytes());
+        shx.extend_from_slice(&10_i32.to_be_bytes());
+        shp.extend_from_slice(&(i as i32 + 1).to_be_bytes());
+        shp.extend_from_slice(&10_i32.to_be_bytes());
+        shp.extend_from_slice(&1_i32.to_le_bytes());
+        shp.extend_from_slice(&(400_000.0 + i as f64).to_le_bytes());
+        shp.extend_from_slice(&(100_000.0 + (i % 1000) as f64).to_le_bytes());
+    }
+
+    let record_length = 1 + DESCRIPTION_WIDTH;
+    let mut dbf = vec![3, 125, 1, 1];
+    dbf.extend_from_slice(&(FEATURES as u32).to_le_bytes());
+    dbf.extend_from_slice(&(32_u16 + 32 + 1).to_le_bytes());
+    dbf.extend_from_slice(&(record_length as u16).to_le_bytes());
+    dbf.extend_from_slice(&[0; 20]);
+    let mut field = b"descriptio".to_vec();
+    field.resize(11, 0);
+    field.push(b'C');
+    field.extend_from_slice(&[0; 4]);
+    field.extend_from_slice(&[DESCRIPTION_WIDTH as u8, 0]);
+    field.extend_from_slice(&[0; 14]);
+    dbf.extend_from_slice(&field);
+    dbf.push(0x0d);
+    dbf.reserve(FEATURES * record_length + 1);
+    for i in 0..FEATURES {
+        let mut record = format!(" Constraint area {i}").into_bytes();
+        record.resize(record_length, b' ');
+        dbf.extend_from_slice(&record);
+    }
+    dbf.push(0x1a);
+
+    ShapefileData {
+        shp,
+        dbf,
+        shx,
+        prj: std::fs::read("../test-data/shapefiles/3_valid_polygon_osgb36.prj")
+            .expect("failed to read prj"),
+        cpg: None,
+    }
+}
+
+#[actix_web::test]
+async fn large_shapefile_upload_is_not_buffered_in_memory() {
+    let app = AppBuilder::new().build().await;
+    let auth = Auth::mock_session_token();
+    let collection_id = app
+        .generate_project_collection_id_with_type(GeometryType::MultiPoint, Some(&auth))
+        .await;
+    let project_id = app.generate_project_id(Some(&auth)).await;
+
+    let shapefile = synthetic_shapefile();
+    let upload_size = shapefile.shp.len() + shapefile.shx.len() + shapefile.dbf.len();
+    let form = add_shapefile_to_form("constraints", shapefile, reqwest::multipart::Form::new())
+        .text("name", uuid::Uuid::new_v4().to_string())
+        .text("attributes", "first");
+
+    // The request body is already allocated, only count what the upload adds on top
+    let baseline = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let response = app
+        .features_service
+        .post_form(
+            &app.api_client,
+            form,
+            format!("{}/{}", project_id, collection_id),
+            Some(&auth),
+        )
+        .await;
+    let peak = PEAK.load(Ordering::Relaxed);
+    let _feature_id: FeatureId = handle_json_response(response).await.unwrap();
+
+    let growth = peak.saturating_sub(baseline);
+    assert!(
+        growth < upload_size / 2,
+        "upload of {upload_size} bytes grew the heap by {growth} bytes"
+    );
+}
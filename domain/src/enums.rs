@@ -37,6 +37,18 @@ pub enum GeometryType {
     GeometryCollection,
 }
 
+/// Which coordinates of uploaded geometries a collection stores. M values are always dropped.
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, Display, Default, PartialEq)]
+#[sqlx(type_name = "app.coordinate_dimension", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CoordinateDimension {
+    /// Force geometries to 2D, dropping any Z values
+    #[default]
+    Xy,
+    /// Keep Z values, e.g. heights of turbine locations or surveyed boundaries
+    Xyz,
+}
+
 impl From<GeometryType> for OGRwkbGeometryType::Type {
     fn from(value: GeometryType) -> Self {
         match value {
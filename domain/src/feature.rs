@@ -5,7 +5,7 @@ use crate::IntoOGCFeature;
 pub struct Feature {
     pub id: i32,
 
-    pub geom: geojson::Geometry,
+    pub geom: Option<geojson::Geometry>,
     pub properties: serde_json::Map<String, Value>,
 }
 
@@ -17,6 +17,6 @@ where {
             geom,
             properties,
         } = self;
-        ogc::Feature::new(id, properties, Some(geom), collection_url)
+        ogc::Feature::new(id, properties, geom, collection_url)
     }
 }
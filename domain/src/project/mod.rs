@@ -3,7 +3,7 @@ pub use entity::Project;
 mod input_dto;
 pub use input_dto::ProjectInputDto;
 mod properties;
-pub use properties::Properties;
+pub use properties::{PROPERTIES, Properties};
 mod name;
 pub use name::ProjectName;
 mod slug;
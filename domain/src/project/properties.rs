@@ -6,6 +6,32 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+/// Properties of a project's OGC feature, including the flattened `owner`, `added_by`
+/// and `last_updated_by`
+pub const PROPERTIES: [&str; 21] = [
+    "name",
+    "added",
+    "owner_id",
+    "owner_first_name",
+    "owner_last_name",
+    "owner_team",
+    "added_by_id",
+    "added_by_first_name",
+    "added_by_last_name",
+    "added_by_team",
+    "status",
+    "visibility",
+    "crs_srid",
+    "last_updated_by_id",
+    "last_updated_by_first_name",
+    "last_updated_by_last_name",
+    "last_updated_by_team",
+    "last_updated",
+    "slug",
+    "search_area_id",
+    "search_site_name",
+];
+
 #[derive(Serialize, Deserialize, FromRow, Debug)]
 pub struct Properties {
     pub name: String,
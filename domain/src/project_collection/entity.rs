@@ -4,7 +4,7 @@ use serde_json::Map;
 
 use crate::{
     CreateLinks, IntoOGCCollection, SupportedCrs,
-    enums::{CollectionId, GeometryType},
+    enums::{CollectionId, CoordinateDimension, GeometryType},
 };
 
 #[derive(Deserialize)]
@@ -17,6 +17,8 @@ pub struct ProjectCollection {
     pub extent: Option<SpatialExtent>,
     pub supported_crs: SupportedCrs,
     pub geometry_type: GeometryType,
+    #[serde(default)]
+    pub coordinate_dimension: CoordinateDimension,
 }
 
 impl IntoOGCCollection for ProjectCollection {
@@ -29,6 +31,7 @@ impl IntoOGCCollection for ProjectCollection {
             id,
             extent,
             geometry_type,
+            coordinate_dimension,
             slug,
         } = self;
         let links = ogcapi_types::common::Collection::create_links(collections_url, &id);
@@ -37,6 +40,10 @@ impl IntoOGCCollection for ProjectCollection {
                 "geometry_type".to_string(),
                 serde_json::json!(geometry_type),
             ),
+            (
+                "coordinate_dimension".to_string(),
+                serde_json::json!(coordinate_dimension),
+            ),
             ("slug".to_string(), serde_json::json!(slug)),
         ]);
 
@@ -1,8 +1,12 @@
-use crate::{enums::GeometryType, name::NameInputDTO};
+use crate::{
+    enums::{CoordinateDimension, GeometryType},
+    name::NameInputDTO,
+};
 
 pub struct ProjectCollectionInputDto {
     pub title: NameInputDTO,
     pub slug: String,
     pub description: Option<String>,
     pub geometry_type: GeometryType,
+    pub coordinate_dimension: CoordinateDimension,
 }
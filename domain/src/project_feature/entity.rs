@@ -1,5 +1,5 @@
 use crate::{AddedBy, IntoOGCFeature, LastUpdatedBy, enums::Status};
-use anyhow::{Context, anyhow};
+use anyhow::Context;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value, from_value, json};
@@ -9,9 +9,33 @@ pub struct ProjectFeature {
     pub id: i32,
     pub properties_map: Map<String, Value>,
     pub properties: Properties,
-    pub geometry: geojson::Geometry,
+    /// `None` when a query skipped the geometry
+    pub geometry: Option<geojson::Geometry>,
 }
 
+/// Properties of an OGC feature that come from [`Properties`] rather than the
+/// user defined properties, including the flattened `added_by` and `last_updated_by`
+pub const SYSTEM_PROPERTIES: [&str; 18] = [
+    "id",
+    "collection_id",
+    "project_id",
+    "name",
+    "storage_crs_srid",
+    "is_primary",
+    "status",
+    "added",
+    "last_updated",
+    "added_by_id",
+    "added_by_first_name",
+    "added_by_last_name",
+    "added_by_team",
+    "last_updated_by_id",
+    "last_updated_by_first_name",
+    "last_updated_by_last_name",
+    "last_updated_by_team",
+    "collection_title",
+];
+
 #[derive(Serialize, Deserialize, Default, FromRow)]
 pub struct Properties {
     pub collection_id: i32,
@@ -41,7 +65,7 @@ impl IntoOGCFeature for ProjectFeature {
         } = self;
         let mut additional: Map<String, Value> = from_value(json!(properties)).unwrap();
         properties_map.append(&mut additional);
-        ogc::Feature::new(id, properties_map, Some(geometry), collection_url)
+        ogc::Feature::new(id, properties_map, geometry, collection_url)
     }
 }
 
@@ -61,28 +85,7 @@ impl TryFrom<ogc::Feature> for ProjectFeature {
                 .context("Failed to deserialize system properties")?;
 
         // Remove all known system fields from properties map to leave only user-defined fields
-        // This includes base fields and flattened added_by/last_updated_by fields
-        let system_fields = [
-            "collection_id",
-            "project_id",
-            "name",
-            "storage_crs_srid",
-            "is_primary",
-            "status",
-            "added",
-            "last_updated",
-            "added_by_id",
-            "added_by_first_name",
-            "added_by_last_name",
-            "added_by_team",
-            "last_updated_by_id",
-            "last_updated_by_first_name",
-            "last_updated_by_last_name",
-            "last_updated_by_team",
-            "collection_title",
-        ];
-
-        for field in system_fields {
+        for field in SYSTEM_PROPERTIES {
             properties.remove(field);
         }
 
@@ -90,7 +93,7 @@ impl TryFrom<ogc::Feature> for ProjectFeature {
             id,
             properties: properties_struct,
             properties_map: properties,
-            geometry: geometry.ok_or(anyhow!("feature has no geometry"))?,
+            geometry,
         })
     }
 }
@@ -109,7 +112,7 @@ mod tests {
             id: 1,
             properties_map: map,
             properties: Default::default(),
-            geometry: geojson::Geometry::new(geojson::Value::Point(vec![1., 1.])),
+            geometry: Some(geojson::Geometry::new(geojson::Value::Point(vec![1., 1.]))),
         };
         let ogc = ft.into_ogc_feature(uuid::Uuid::new_v4().to_string());
         let ft = ProjectFeature::try_from(ogc).unwrap();
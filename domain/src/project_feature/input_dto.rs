@@ -1,7 +1,21 @@
+use serde_json::{Map, Value};
+
 pub struct FeatureInputDTO {
     pub name: String,
     pub primary: Option<bool>,
     pub geom_wkb: Vec<u8>,
     pub srid: i32,
     pub target_srid: i32,
+    pub properties: Map<String, Value>,
+}
+
+/// A new version of an existing feature, where `None` keeps the stored value
+pub struct FeatureEditDTO {
+    pub name: Option<String>,
+    pub primary: Option<bool>,
+    /// WKB geometry in `srid`, stored in the CRS of the current geometry
+    pub geom_wkb: Option<Vec<u8>>,
+    pub srid: i32,
+    /// Every user defined property, replacing the stored ones
+    pub properties: Map<String, Value>,
 }
@@ -1,4 +1,4 @@
 mod entity;
 pub use entity::*;
 mod input_dto;
-pub use input_dto::FeatureInputDTO;
+pub use input_dto::{FeatureEditDTO, FeatureInputDTO};
@@ -27,6 +27,12 @@ impl CreateLinks for ogcapi_types::common::Collection {
             Link::new(format!("{}/{}/items", collections_url, id), ITEMS)
                 .mediatype(GEO_JSON)
                 .title("Items"),
+            Link::new(
+                format!("{}/{}/queryables", collections_url, id),
+                "http://www.opengis.net/def/rel/ogc/1.0/queryables",
+            )
+            .mediatype("application/schema+json")
+            .title("Queryables"),
         ]
     }
 }
@@ -0,0 +1,188 @@
+use gdal::vector::{Feature, FieldValue, Layer, LayerAccess};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use crate::shapefile_processor::ProcessingError;
+
+/// How attributes are combined when several features are merged into one geometry.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributePolicy {
+    /// Keep the attributes of the first feature with a geometry.
+    First,
+    /// Collect each field into an array with one value per feature, in layer order.
+    #[default]
+    Collect,
+}
+
+/// Convert an OGR field value to JSON. Dates are written as ISO 8601 strings.
+pub fn field_value_to_json(value: Option<FieldValue>) -> Value {
+    match value {
+        None => Value::Null,
+        Some(FieldValue::IntegerValue(v)) => json!(v),
+        Some(FieldValue::IntegerListValue(v)) => json!(v),
+        Some(FieldValue::Integer64Value(v)) => json!(v),
+        Some(FieldValue::Integer64ListValue(v)) => json!(v),
+        Some(FieldValue::RealValue(v)) => json!(v),
+        Some(FieldValue::RealListValue(v)) => json!(v),
+        Some(FieldValue::StringValue(v)) => json!(v),
+        Some(FieldValue::StringListValue(v)) => json!(v),
+        Some(FieldValue::DateValue(v)) => json!(v.to_string()),
+        Some(FieldValue::DateTimeValue(v)) => json!(v.to_rfc3339()),
+    }
+}
+
+/// The attribute table row of a single feature as a JSON object.
+pub fn feature_properties(feature: &Feature) -> Map<String, Value> {
+    feature
+        .fields()
+        .map(|(name, value)| (name, field_value_to_json(value)))
+        .collect()
+}
+
+/// Read the attributes of every feature with a geometry in the first layer and
+/// combine them according to `policy`.
+///
+/// A layer holding a single feature always yields that feature's attributes unchanged.
+pub fn merge_attributes(
+    dataset: &gdal::Dataset,
+    policy: AttributePolicy,
+) -> Result<Map<String, Value>, ProcessingError> {
+    let mut layer = dataset.layers().next().ok_or(ProcessingError::NoLayers)?;
+    merge_layer_attributes(&mut layer, policy)
+}
+
+pub fn merge_layer_attributes(
+    layer: &mut Layer,
+    policy: AttributePolicy,
+) -> Result<Map<String, Value>, ProcessingError> {
+    let features = layer
+        .features()
+        .filter(|feature| feature.geometry().is_some_and(|geom| !geom.is_empty()));
+    // Only the first row is kept, so don't read the rest of a possibly large table
+    let rows: Vec<Map<String, Value>> = match policy {
+        AttributePolicy::First => features
+            .take(1)
+            .map(|feature| feature_properties(&feature))
+            .collect(),
+        AttributePolicy::Collect => features
+            .map(|feature| feature_properties(&feature))
+            .collect(),
+    };
+
+    if rows.len() <= 1 {
+        return Ok(rows.into_iter().next().unwrap_or_default());
+    }
+
+    match policy {
+        AttributePolicy::First => Ok(rows.into_iter().next().unwrap_or_default()),
+        AttributePolicy::Collect => {
+            let mut collected: Map<String, Value> = Map::new();
+            for (index, row) in rows.iter().enumerate() {
+                for (key, value) in row {
+                    let values = collected
+                        .entry(key.clone())
+                        .or_insert_with(|| Value::Array(vec![Value::Null; index]));
+                    if let Value::Array(values) = values {
+                        values.push(value.clone());
+                    }
+                }
+                // Pad fields missing from this row so every array lines up with the features
+                for values in collected.values_mut() {
+                    if let Value::Array(values) = values
+                        && values.len() < index + 1
+                    {
+                        values.push(Value::Null);
+                    }
+                }
+            }
+            Ok(collected)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gdal::vector::{Geometry, LayerOptions, OGRFieldType, OGRwkbGeometryType};
+
+    fn create_dataset_with_attributes(rows: &[(&str, i32, f64)]) -> gdal::Dataset {
+        let filename = format!("/vsimem/{}.shp", uuid::Uuid::new_v4());
+        let mut dataset = gdal::DriverManager::get_driver_by_name("ESRI Shapefile")
+            .expect("failed to get shapefile driver")
+            .create_vector_only(&filename)
+            .expect("failed to create dataset");
+        let mut layer = dataset
+            .create_layer(LayerOptions {
+                name: "test",
+                options: None,
+                ty: OGRwkbGeometryType::wkbPoint,
+                srs: Some(
+                    &gdal::spatial_ref::SpatialRef::from_epsg(27700).expect("failed to create srs"),
+                ),
+            })
+            .expect("failed to create layer");
+        layer
+            .create_defn_fields(&[
+                ("name", OGRFieldType::OFTString),
+                ("turbines", OGRFieldType::OFTInteger),
+                ("capacity", OGRFieldType::OFTReal),
+            ])
+            .expect("failed to create fields");
+        for (index, (name, turbines, capacity)) in rows.iter().enumerate() {
+            layer
+                .create_feature_fields(
+                    Geometry::from_wkt(&format!("POINT({index} {index})")).unwrap(),
+                    &["name", "turbines", "capacity"],
+                    &[
+                        FieldValue::StringValue(name.to_string()),
+                        FieldValue::IntegerValue(*turbines),
+                        FieldValue::RealValue(*capacity),
+                    ],
+                )
+                .expect("failed to create feature");
+        }
+        dataset
+    }
+
+    #[test]
+    fn field_values_convert_to_json() {
+        assert_eq!(field_value_to_json(None), Value::Null);
+        assert_eq!(
+            field_value_to_json(Some(FieldValue::IntegerValue(3))),
+            json!(3)
+        );
+        assert_eq!(
+            field_value_to_json(Some(FieldValue::RealValue(1.5))),
+            json!(1.5)
+        );
+        assert_eq!(
+            field_value_to_json(Some(FieldValue::StringValue("a".to_string()))),
+            json!("a")
+        );
+    }
+
+    #[test]
+    fn single_feature_attributes_are_stored_flat() {
+        let dataset = create_dataset_with_attributes(&[("north", 4, 16.8)]);
+        let properties = merge_attributes(&dataset, AttributePolicy::Collect).unwrap();
+        assert_eq!(properties.get("name"), Some(&json!("north")));
+        assert_eq!(properties.get("turbines"), Some(&json!(4)));
+        assert_eq!(properties.get("capacity"), Some(&json!(16.8)));
+    }
+
+    #[test]
+    fn collect_policy_keeps_one_value_per_feature() {
+        let dataset = create_dataset_with_attributes(&[("north", 4, 16.8), ("south", 2, 8.4)]);
+        let properties = merge_attributes(&dataset, AttributePolicy::Collect).unwrap();
+        assert_eq!(properties.get("name"), Some(&json!(["north", "south"])));
+        assert_eq!(properties.get("turbines"), Some(&json!([4, 2])));
+    }
+
+    #[test]
+    fn first_policy_keeps_first_feature() {
+        let dataset = create_dataset_with_attributes(&[("north", 4, 16.8), ("south", 2, 8.4)]);
+        let properties = merge_attributes(&dataset, AttributePolicy::First).unwrap();
+        assert_eq!(properties.get("name"), Some(&json!("north")));
+    }
+}
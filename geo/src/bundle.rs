@@ -0,0 +1,300 @@
+use anyhow::Context;
+use gdal::{spatial_ref::SpatialRef, vector::OGRwkbGeometryType};
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    export::{
+        ExportItem, GEOPACKAGE_LAYOUT, LayerSchema, LayerWriter, SHAPEFILE_LAYOUT,
+        SHAPEFILE_OPTIONS, close_dataset, collection_colour, create_geopackage, create_shapefile,
+        layer_features, next_layer,
+    },
+    shapefile_processor::flatten_type,
+};
+
+/// Folder of a bundle holding one shapefile and QGIS style per layer
+const SHAPEFILE_DIR: &str = "shapefiles";
+
+/// A layer written to a bundle, as listed in its manifest
+pub struct BundleLayer {
+    pub name: String,
+    pub feature_count: usize,
+}
+
+/// Write a project data bundle to a new zip archive at `path`, with every geometry in
+/// `srid`.
+///
+/// The archive holds `manifest.json`, a GeoPackage named `{name}.gpkg` with every layer of
+/// `items`, and a shapefile per layer under `shapefiles/`. Each shapefile has a QGIS
+/// `.qml` style alongside it, which QGIS applies when the shapefile is opened and which
+/// can be loaded onto the GeoPackage layer of the same name. The manifest is made by
+/// `manifest` from the layers once every feature has been written.
+///
+/// Each feature is written to its GeoPackage layer and shapefile as it's read from
+/// `items`, and the files are copied into the archive one at a time, so large projects are
+/// never held in memory.
+pub fn write_bundle(
+    path: &Path,
+    name: &str,
+    srid: u32,
+    items: impl IntoIterator<Item = ExportItem>,
+    manifest: impl FnOnce(&[BundleLayer]) -> anyhow::Result<Vec<u8>>,
+) -> anyhow::Result<()> {
+    let staging = tempfile::Builder::new()
+        .prefix("geoman-bundle-")
+        .tempdir()
+        .context("failed to create bundle directory")?;
+    let shapefile_dir = staging.path().join(SHAPEFILE_DIR);
+    fs::create_dir(&shapefile_dir).context("failed to create shapefile directory")?;
+    let mut geopackage = create_geopackage(&staging.path().join(format!("{name}.gpkg")))?;
+    let srs = SpatialRef::from_epsg(srid).context("failed to create spatial ref")?;
+
+    let mut layers = Vec::new();
+    let mut items = items.into_iter().peekable();
+    while let Some(schema) = next_layer(&mut items)? {
+        let mut shapefile = create_shapefile(&shapefile_dir.join(format!("{}.shp", schema.name)))?;
+        let mut transaction = geopackage
+            .start_transaction()
+            .context("failed to start transaction")?;
+        let mut geopackage_layer =
+            LayerWriter::create(&mut transaction, &srs, &schema, &GEOPACKAGE_LAYOUT, None)?;
+        let mut shapefile_layer = LayerWriter::create(
+            &mut shapefile,
+            &srs,
+            &schema,
+            &SHAPEFILE_LAYOUT,
+            SHAPEFILE_OPTIONS,
+        )?;
+        let mut feature_count = 0;
+        for feature in layer_features(&mut items) {
+            geopackage_layer.write(&feature)?;
+            shapefile_layer.write(&feature)?;
+            feature_count += 1;
+        }
+        drop((geopackage_layer, shapefile_layer));
+        transaction
+            .commit()
+            .context("failed to commit transaction")?;
+        close_dataset(shapefile, "shapefile")?;
+        fs::write(
+            shapefile_dir.join(format!("{}.qml", schema.name)),
+            qgis_style(&schema),
+        )
+        .context(format!("failed to write style of layer '{}'", schema.name))?;
+        layers.push(BundleLayer {
+            name: schema.name,
+            feature_count,
+        });
+    }
+    close_dataset(geopackage, "geopackage")?;
+    fs::write(staging.path().join("manifest.json"), manifest(&layers)?)
+        .context("failed to write manifest")?;
+
+    let mut archive = ZipWriter::new(File::create(path).context("failed to create bundle")?);
+    for file in staged_files(staging.path())? {
+        let entry_name = file
+            .strip_prefix(staging.path())
+            .context("failed to name bundle entry")?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let size = file
+            .metadata()
+            .context(format!("failed to read size of '{entry_name}'"))?
+            .len();
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(size >= u32::MAX.into());
+        archive
+            .start_file(entry_name.as_str(), options)
+            .context(format!("failed to add '{entry_name}' to bundle"))?;
+        let mut source = File::open(&file).context(format!("failed to open '{entry_name}'"))?;
+        io::copy(&mut source, &mut archive)
+            .context(format!("failed to copy '{entry_name}' into bundle"))?;
+    }
+    archive.finish().context("failed to finish bundle")?;
+    Ok(())
+}
+
+/// Every file under `dir`, sorted so bundles list their entries in the same order
+fn staged_files(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).context("failed to read bundle directory")? {
+        let path = entry.context("failed to read bundle directory")?.path();
+        if path.is_dir() {
+            files.extend(staged_files(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// QGIS layer style drawing a layer in its collection's colour, labelled by feature name
+fn qgis_style(schema: &LayerSchema) -> String {
+    let (red, green, blue) = rgb(collection_colour(&schema.name));
+    let colour = format!("{red},{green},{blue},255");
+    let symbol = match flatten_type(schema.geometry_type) {
+        OGRwkbGeometryType::wkbPoint | OGRwkbGeometryType::wkbMultiPoint => format!(
+            r#"<symbol type="marker" name="0" alpha="1">
+        <layer class="SimpleMarker" enabled="1" pass="0" locked="0">
+          <prop k="name" v="circle"/>
+          <prop k="color" v="{colour}"/>
+          <prop k="outline_color" v="35,35,35,255"/>
+          <prop k="size" v="3"/>
+        </layer>
+      </symbol>"#
+        ),
+        OGRwkbGeometryType::wkbLineString | OGRwkbGeometryType::wkbMultiLineString => format!(
+            r#"<symbol type="line" name="0" alpha="1">
+        <layer class="SimpleLine" enabled="1" pass="0" locked="0">
+          <prop k="line_color" v="{colour}"/>
+          <prop k="line_width" v="0.8"/>
+        </layer>
+      </symbol>"#
+        ),
+        _ => format!(
+            r#"<symbol type="fill" name="0" alpha="1">
+        <layer class="SimpleFill" enabled="1" pass="0" locked="0">
+          <prop k="color" v="{red},{green},{blue},102"/>
+          <prop k="outline_color" v="{colour}"/>
+          <prop k="outline_width" v="0.6"/>
+          <prop k="style" v="solid"/>
+        </layer>
+      </symbol>"#
+        ),
+    };
+    format!(
+        r#"<!DOCTYPE qgis PUBLIC 'http://mrcc.com/qgis.dtd' 'SYSTEM'>
+<qgis version="3.28" styleCategories="Symbology|Labeling">
+  <renderer-v2 type="singleSymbol" symbollevels="0" enableorderby="0" forceraster="0">
+    <symbols>
+      {symbol}
+    </symbols>
+  </renderer-v2>
+  <labeling type="simple">
+    <settings>
+      <text-style fieldName="name" fontSize="9" textColor="35,35,35,255"/>
+    </settings>
+  </labeling>
+</qgis>
+"#
+    )
+}
+
+/// Red, green and blue of a `#rrggbb` colour
+fn rgb(hex: &str) -> (u8, u8, u8) {
+    let channel =
+        |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).unwrap_or_default();
+    (channel(1..3), channel(3..5), channel(5..7))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ExportFeature;
+    use chrono::Utc;
+    use gdal::{Dataset, vector::Geometry};
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    /// A layer with one feature, as read for a streamed export
+    fn layer(name: &str, geometry_type: OGRwkbGeometryType::Type, wkt: &str) -> [ExportItem; 2] {
+        [
+            ExportItem::Layer(LayerSchema::new(name.to_string(), geometry_type)),
+            ExportItem::Feature(ExportFeature {
+                id: 1,
+                name: format!("{name} 1"),
+                is_primary: false,
+                status: "ACTIVE".to_string(),
+                added_by: "Ada Lovelace".to_string(),
+                last_updated: Utc::now(),
+                properties: Default::default(),
+                geom_wkb: Geometry::from_wkt(wkt).unwrap().wkb().unwrap(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn bundle_has_manifest_geopackage_and_styled_shapefiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.zip");
+        let items = [
+            layer(
+                "site-boundaries",
+                OGRwkbGeometryType::wkbMultiPolygon,
+                "MULTIPOLYGON(((0 0,1 0,1 1,0 0)))",
+            ),
+            layer("turbines", OGRwkbGeometryType::wkbPoint, "POINT(0 0)"),
+        ];
+        write_bundle(
+            &path,
+            "windy-hill",
+            27700,
+            items.into_iter().flatten(),
+            |layers| {
+                let counts: Vec<_> = layers
+                    .iter()
+                    .map(|layer| format!("{}={}", layer.name, layer.feature_count))
+                    .collect();
+                Ok(counts.join(",").into_bytes())
+            },
+        )
+        .expect("failed to write bundle");
+
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let names: Vec<_> = archive.file_names().map(str::to_string).collect();
+        for expected in [
+            "manifest.json",
+            "windy-hill.gpkg",
+            "shapefiles/site-boundaries.shp",
+            "shapefiles/site-boundaries.dbf",
+            "shapefiles/site-boundaries.qml",
+            "shapefiles/turbines.shp",
+            "shapefiles/turbines.qml",
+        ] {
+            assert!(
+                names.iter().any(|name| name == expected),
+                "{expected} missing"
+            );
+        }
+        let mut manifest = String::new();
+        archive
+            .by_name("manifest.json")
+            .unwrap()
+            .read_to_string(&mut manifest)
+            .unwrap();
+        assert_eq!(manifest, "site-boundaries=1,turbines=1");
+
+        let extracted = dir.path().join("extracted");
+        archive.extract(&extracted).unwrap();
+        let dataset = Dataset::open(extracted.join("windy-hill.gpkg")).unwrap();
+        assert_eq!(dataset.layer_count(), 2);
+        let dataset = Dataset::open(extracted.join("shapefiles/turbines.shp")).unwrap();
+        assert_eq!(dataset.layer(0).unwrap().feature_count(), 1);
+    }
+
+    #[test]
+    fn qgis_style_matches_geometry_family() {
+        let style = qgis_style(&LayerSchema::new(
+            "turbines".to_string(),
+            OGRwkbGeometryType::wkbPoint,
+        ));
+        assert!(style.contains(r#"class="SimpleMarker""#));
+        let style = qgis_style(&LayerSchema::new(
+            "site-boundaries".to_string(),
+            OGRwkbGeometryType::wkbMultiPolygon,
+        ));
+        assert!(style.contains(r#"class="SimpleFill""#));
+        assert!(style.contains(r#"fieldName="name""#));
+    }
+
+    #[test]
+    fn rgb_reads_hex_colours() {
+        assert_eq!(rgb("#e41a1c"), (228, 26, 28));
+    }
+}
@@ -0,0 +1,218 @@
+use anyhow::{Context, anyhow};
+use gdal::{
+    Dataset,
+    vector::{FieldValue, Geometry, LayerAccess, LayerOptions, OGRFieldType, OGRwkbGeometryType},
+};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+
+use crate::{kml::geometry_parts, virtual_shapefile::ShapefileError};
+
+const LAYER_NAME: &str = "entities";
+/// Largest gap, in drawing units, between the ends of a polyline that still counts as closed
+const CLOSE_TOLERANCE: f64 = 1e-6;
+
+/// How closed polylines (and circles) in a DXF file are imported.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClosedPolylines {
+    /// Closed polylines become polygons, e.g. site boundaries drawn as outlines
+    #[default]
+    Polygon,
+    /// Closed polylines are kept as line strings
+    Line,
+}
+
+/// Options for reading a `.dxf` file
+#[derive(Debug, Clone, Default)]
+pub struct DxfOptions {
+    /// CAD layers (the `Layer` attribute of each entity) to import, all of them when empty
+    pub cad_layers: Vec<String>,
+    pub closed_polylines: ClosedPolylines,
+}
+
+/// Open a `.dxf` file as a single 2D layer.
+///
+/// DXF has no CRS, so the layer has no spatial reference and the upload must give an SRID.
+/// Entities on CAD layers not listed in `options` are dropped, Z values are dropped and
+/// geometry collections (e.g. exploded blocks) are split into their parts so the result can
+/// be passed straight to `merge_geometries`.
+pub fn open_dxf(path: &str, options: &DxfOptions) -> Result<Dataset, ShapefileError> {
+    let source = Dataset::open(path)
+        .context("failed to open dxf dataset")
+        .map_err(ShapefileError::InvalidData)?;
+    let mut source_layer = source
+        .layers()
+        .next()
+        .ok_or_else(|| ShapefileError::InvalidData(anyhow!("no entities found in dxf")))?;
+
+    let mut target = gdal::DriverManager::get_driver_by_name("Memory")
+        .context("failed to get memory driver")
+        .map_err(ShapefileError::UnexpectedError)?
+        .create_vector_only("")
+        .context("failed to create memory dataset")
+        .map_err(ShapefileError::UnexpectedError)?;
+    let mut target_layer = target
+        .create_layer(LayerOptions {
+            name: LAYER_NAME,
+            srs: None,
+            ty: OGRwkbGeometryType::wkbUnknown,
+            options: None,
+        })
+        .context("failed to create entities layer")
+        .map_err(ShapefileError::UnexpectedError)?;
+    target_layer
+        .create_defn_fields(&[
+            ("layer", OGRFieldType::OFTString),
+            ("handle", OGRFieldType::OFTString),
+        ])
+        .context("failed to create entity fields")
+        .map_err(ShapefileError::UnexpectedError)?;
+
+    let mut available = BTreeSet::new();
+    for feature in source_layer.features() {
+        let cad_layer = feature
+            .field_as_string_by_name("Layer")
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        available.insert(cad_layer.clone());
+        if !options.cad_layers.is_empty() && !options.cad_layers.contains(&cad_layer) {
+            continue;
+        }
+        let Some(geom) = feature.geometry() else {
+            continue;
+        };
+        let handle = feature
+            .field_as_string_by_name("EntityHandle")
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let values = [
+            FieldValue::StringValue(cad_layer),
+            FieldValue::StringValue(handle),
+        ];
+        for part in geometry_parts(geom) {
+            let part = match options.closed_polylines {
+                ClosedPolylines::Polygon => closed_polyline_to_polygon(&part).unwrap_or(part),
+                ClosedPolylines::Line => part,
+            };
+            target_layer
+                .create_feature_fields(part, &["layer", "handle"], &values)
+                .context("failed to copy dxf entity")
+                .map_err(ShapefileError::UnexpectedError)?;
+        }
+    }
+    drop(target_layer);
+
+    let missing: Vec<String> = options
+        .cad_layers
+        .iter()
+        .filter(|cad_layer| !available.contains(*cad_layer))
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        return Err(ShapefileError::CadLayersNotFound {
+            missing,
+            available: available.into_iter().collect(),
+        });
+    }
+    if target
+        .layer(0)
+        .is_ok_and(|layer| layer.feature_count() == 0)
+    {
+        return Err(ShapefileError::InvalidData(anyhow!(
+            "no entities with geometry found in dxf"
+        )));
+    }
+    Ok(target)
+}
+
+/// A polygon with the same outline as a closed line string, or `None` for any other geometry.
+fn closed_polyline_to_polygon(geom: &Geometry) -> Option<Geometry> {
+    if geom.geometry_type() != OGRwkbGeometryType::wkbLineString {
+        return None;
+    }
+    let points = geom.get_point_vec();
+    let (&(first_x, first_y, _), &(last_x, last_y, _)) = (points.first()?, points.last()?);
+    // Arcs such as circles are approximated, so their ends can differ by rounding error
+    let closed =
+        (first_x - last_x).abs() < CLOSE_TOLERANCE && (first_y - last_y).abs() < CLOSE_TOLERANCE;
+    if points.len() < 4 || !closed {
+        return None;
+    }
+    let mut ring = Geometry::empty(OGRwkbGeometryType::wkbLinearRing).ok()?;
+    for &(x, y, _) in &points[..points.len() - 1] {
+        ring.add_point_2d((x, y));
+    }
+    ring.add_point_2d((first_x, first_y));
+    let mut polygon = Geometry::empty(OGRwkbGeometryType::wkbPolygon).ok()?;
+    polygon.add_geometry(ring).ok()?;
+    Some(polygon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapefile_processor::merge_geometries;
+    use gdal::vsi;
+
+    /// A minimal DXF with two closed boundaries and an open track on separate CAD layers
+    const DXF: &str = "0\nSECTION\n2\nENTITIES\n\
+0\nLWPOLYLINE\n5\n1A\n8\nBOUNDARY\n90\n4\n70\n1\n\
+10\n400000\n20\n100000\n10\n400100\n20\n100000\n10\n400100\n20\n100100\n10\n400000\n20\n100100\n\
+0\nLWPOLYLINE\n5\n1B\n8\nBOUNDARY\n90\n4\n70\n1\n\
+10\n400200\n20\n100000\n10\n400300\n20\n100000\n10\n400300\n20\n100100\n10\n400200\n20\n100100\n\
+0\nLWPOLYLINE\n5\n1C\n8\nTRACKS\n90\n2\n70\n0\n\
+10\n400000\n20\n100000\n10\n400200\n20\n100000\n\
+0\nENDSEC\n0\nEOF\n";
+
+    fn open_test_dxf(options: &DxfOptions) -> Result<Dataset, ShapefileError> {
+        let path = format!("/vsimem/{}.dxf", uuid::Uuid::new_v4());
+        vsi::create_mem_file(&path, DXF.as_bytes().to_vec()).unwrap();
+        let result = open_dxf(&path, options);
+        let _ = vsi::unlink_mem_file(&path);
+        result
+    }
+
+    #[test]
+    fn closed_polylines_on_selected_layer_become_polygons() {
+        let ds = open_test_dxf(&DxfOptions {
+            cad_layers: vec!["BOUNDARY".to_string()],
+            closed_polylines: ClosedPolylines::Polygon,
+        })
+        .expect("failed to read dxf");
+        assert_eq!(ds.layer(0).unwrap().feature_count(), 2);
+        assert!(ds.layer(0).unwrap().spatial_ref().is_none());
+        let merged = merge_geometries(&ds, OGRwkbGeometryType::wkbMultiPolygon)
+            .expect("failed to merge dxf geometries");
+        assert_eq!(merged.geometry_count(), 2);
+    }
+
+    #[test]
+    fn closed_polylines_can_be_kept_as_lines() {
+        let ds = open_test_dxf(&DxfOptions {
+            cad_layers: vec![],
+            closed_polylines: ClosedPolylines::Line,
+        })
+        .expect("failed to read dxf");
+        let merged = merge_geometries(&ds, OGRwkbGeometryType::wkbMultiLineString)
+            .expect("failed to merge dxf geometries");
+        assert_eq!(merged.geometry_count(), 3);
+    }
+
+    #[test]
+    fn unknown_cad_layer_lists_available_layers() {
+        let result = open_test_dxf(&DxfOptions {
+            cad_layers: vec!["ROADS".to_string()],
+            ..Default::default()
+        });
+        match result {
+            Err(ShapefileError::CadLayersNotFound { missing, available }) => {
+                assert_eq!(missing, vec!["ROADS"]);
+                assert_eq!(available, vec!["BOUNDARY", "TRACKS"]);
+            }
+            _ => panic!("expected CadLayersNotFound error"),
+        }
+    }
+}
@@ -0,0 +1,892 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use gdal::{
+    Dataset,
+    spatial_ref::SpatialRef,
+    vector::{
+        FieldValue, Geometry, Layer, LayerAccess, LayerOptions, OGRFieldType, OGRwkbGeometryType,
+    },
+};
+use serde_json::{Map, Value};
+use std::{fs::File, iter::Peekable, path::Path};
+
+use crate::shapefile_processor::{WKB_25D_BIT, flatten_type};
+
+/// Per-feature fields written before the flattened `properties`
+#[derive(Debug, Clone, Copy)]
+enum SystemField {
+    Id,
+    Name,
+    IsPrimary,
+    Status,
+    AddedBy,
+    LastUpdated,
+    /// KML style of the feature's collection, see [`layer_style`]
+    Style,
+}
+
+impl SystemField {
+    fn name(self) -> &'static str {
+        match self {
+            SystemField::Id => "id",
+            SystemField::Name => "name",
+            SystemField::IsPrimary => "is_primary",
+            SystemField::Status => "status",
+            SystemField::AddedBy => "added_by",
+            SystemField::LastUpdated => "last_updated",
+            // The field GDAL reads a feature's style from when none is set on the feature
+            SystemField::Style => "OGR_STYLE",
+        }
+    }
+
+    fn field_type(self) -> OGRFieldType::Type {
+        match self {
+            SystemField::Id | SystemField::IsPrimary => OGRFieldType::OFTInteger,
+            SystemField::Name | SystemField::Status | SystemField::AddedBy | SystemField::Style => {
+                OGRFieldType::OFTString
+            }
+            SystemField::LastUpdated => OGRFieldType::OFTDateTime,
+        }
+    }
+
+    fn value(self, feature: &ExportFeature, style: &str) -> FieldValue {
+        match self {
+            SystemField::Id => FieldValue::IntegerValue(feature.id),
+            SystemField::Name => FieldValue::StringValue(feature.name.clone()),
+            SystemField::IsPrimary => FieldValue::IntegerValue(feature.is_primary.into()),
+            SystemField::Status => FieldValue::StringValue(feature.status.clone()),
+            SystemField::AddedBy => FieldValue::StringValue(feature.added_by.clone()),
+            SystemField::LastUpdated => {
+                FieldValue::DateTimeValue(feature.last_updated.fixed_offset())
+            }
+            SystemField::Style => FieldValue::StringValue(style.to_string()),
+        }
+    }
+}
+
+/// The fields an export format can hold
+pub(crate) struct FieldLayout {
+    system_fields: &'static [SystemField],
+    /// Longest field name in bytes, if the format limits it
+    max_name_len: Option<usize>,
+}
+
+pub(crate) const GEOPACKAGE_LAYOUT: FieldLayout = FieldLayout {
+    system_fields: &[
+        SystemField::Id,
+        SystemField::Name,
+        SystemField::IsPrimary,
+        SystemField::Status,
+        SystemField::AddedBy,
+        SystemField::LastUpdated,
+    ],
+    max_name_len: None,
+};
+
+/// DBF field names are limited to 10 bytes and dates can't hold a time
+pub(crate) const SHAPEFILE_LAYOUT: FieldLayout = FieldLayout {
+    system_fields: &[
+        SystemField::Id,
+        SystemField::Name,
+        SystemField::IsPrimary,
+        SystemField::Status,
+    ],
+    max_name_len: Some(10),
+};
+
+/// Layer creation options of shapefiles, so the DBF is read back as UTF-8
+pub(crate) const SHAPEFILE_OPTIONS: Option<&[&str]> = Some(&["ENCODING=UTF-8"]);
+
+/// KML turns the `name` field into the placemark name and every other field into
+/// `ExtendedData`, so only the properties are added
+const KML_LAYOUT: FieldLayout = FieldLayout {
+    system_fields: &[SystemField::Name, SystemField::Style],
+    max_name_len: None,
+};
+
+/// KML is always in WGS 84 longitude/latitude
+pub const KML_SRID: u32 = 4326;
+
+/// Height of DXF feature name text, in the units of the export CRS
+const DXF_TEXT_HEIGHT: f64 = 5.0;
+
+/// Colours given to collections in styled exports, picked by collection slug
+const COLLECTION_COLOURS: [&str; 8] = [
+    "#e41a1c", "#377eb8", "#4daf4a", "#984ea3", "#ff7f00", "#a65628", "#f781bf", "#999999",
+];
+
+/// A stored project feature to be written to an export file.
+pub struct ExportFeature {
+    pub id: i32,
+    pub name: String,
+    pub is_primary: bool,
+    pub status: String,
+    /// Full name of the user who added the feature
+    pub added_by: String,
+    pub last_updated: DateTime<Utc>,
+    pub properties: Map<String, Value>,
+    /// Geometry in the CRS of the export
+    pub geom_wkb: Vec<u8>,
+}
+
+/// The layer a collection is written to, gathered from its features before any of them
+/// are written, since a layer's fields can't change once it has features.
+#[derive(Debug, Clone)]
+pub struct LayerSchema {
+    pub name: String,
+    pub geometry_type: OGRwkbGeometryType::Type,
+    /// `properties` keys in order of first appearance, with the narrowest field type that
+    /// holds every value seen so far, or `None` if every value was null
+    properties: Vec<(String, Option<OGRFieldType::Type>)>,
+    has_z: bool,
+}
+
+impl LayerSchema {
+    pub fn new(name: String, geometry_type: OGRwkbGeometryType::Type) -> Self {
+        Self {
+            name,
+            geometry_type,
+            properties: Vec::new(),
+            has_z: false,
+        }
+    }
+
+    /// Widen the schema to hold a feature with `properties`, whose geometry has Z values
+    /// if `has_z` is set.
+    pub fn add(&mut self, properties: &Map<String, Value>, has_z: bool) {
+        self.has_z |= has_z;
+        for (key, value) in properties {
+            match self.properties.iter_mut().find(|(seen, _)| seen == key) {
+                Some((_, field_type)) => *field_type = widen_field_type(*field_type, value),
+                None => self
+                    .properties
+                    .push((key.clone(), widen_field_type(None, value))),
+            }
+        }
+    }
+}
+
+/// Part of an export read one collection at a time: each layer's schema is followed by
+/// the features written to it.
+pub enum ExportItem {
+    Layer(LayerSchema),
+    Feature(ExportFeature),
+}
+
+/// The next layer of `items`, failing if a feature comes before any layer
+pub(crate) fn next_layer(
+    items: &mut Peekable<impl Iterator<Item = ExportItem>>,
+) -> anyhow::Result<Option<LayerSchema>> {
+    match items.next() {
+        None => Ok(None),
+        Some(ExportItem::Layer(schema)) => Ok(Some(schema)),
+        Some(ExportItem::Feature(feature)) => {
+            anyhow::bail!("feature {} is not in a layer", feature.id)
+        }
+    }
+}
+
+/// The features of `items` up to the next layer
+pub(crate) fn layer_features(
+    items: &mut Peekable<impl Iterator<Item = ExportItem>>,
+) -> impl Iterator<Item = ExportFeature> {
+    std::iter::from_fn(move || {
+        match items.next_if(|item| matches!(item, ExportItem::Feature(_)))? {
+            ExportItem::Feature(feature) => Some(feature),
+            ExportItem::Layer(_) => None,
+        }
+    })
+}
+
+/// Write the layers of `items` to a new GeoPackage at `path`, with every geometry in
+/// `srid`.
+///
+/// Each feature gets the system fields followed by its `properties`, expanded into one
+/// field per key of its layer's schema.
+pub fn write_geopackage(
+    path: &Path,
+    srid: u32,
+    items: impl IntoIterator<Item = ExportItem>,
+) -> anyhow::Result<()> {
+    let mut dataset = create_geopackage(path)?;
+    let srs = SpatialRef::from_epsg(srid).context("failed to create spatial ref")?;
+    let mut items = items.into_iter().peekable();
+    // Writing each layer in its own transaction keeps GeoPackage exports fast
+    while let Some(schema) = next_layer(&mut items)? {
+        let mut transaction = dataset
+            .start_transaction()
+            .context("failed to start transaction")?;
+        let mut layer =
+            LayerWriter::create(&mut transaction, &srs, &schema, &GEOPACKAGE_LAYOUT, None)?;
+        for feature in layer_features(&mut items) {
+            layer.write(&feature)?;
+        }
+        drop(layer);
+        transaction
+            .commit()
+            .context("failed to commit transaction")?;
+    }
+    close_dataset(dataset, "geopackage")
+}
+
+/// Write the one layer of `items` to a new shapefile at `path`, with every geometry in
+/// `srid`.
+///
+/// The path's extension picks the container: `.shz` for a zipped shapefile QGIS opens
+/// directly, or `.shp.zip` for a plain zip archive older desktop GIS can extract. Each
+/// feature gets its `id`, `name`, `is_primary` and `status` followed by its `properties`,
+/// with field names truncated to the DBF limit of 10 bytes.
+pub fn write_shapefile(
+    path: &Path,
+    srid: u32,
+    items: impl IntoIterator<Item = ExportItem>,
+) -> anyhow::Result<()> {
+    let mut items = items.into_iter().peekable();
+    let schema = next_layer(&mut items)?.context("no layer to write to shapefile")?;
+    let mut dataset = create_shapefile(path)?;
+    let srs = SpatialRef::from_epsg(srid).context("failed to create spatial ref")?;
+    let mut layer = LayerWriter::create(
+        &mut dataset,
+        &srs,
+        &schema,
+        &SHAPEFILE_LAYOUT,
+        SHAPEFILE_OPTIONS,
+    )?;
+    for feature in layer_features(&mut items) {
+        layer.write(&feature)?;
+    }
+    drop(layer);
+    if let Some(schema) = next_layer(&mut items)? {
+        anyhow::bail!("shapefile can't hold a second layer '{}'", schema.name);
+    }
+    close_dataset(dataset, "shapefile")
+}
+
+/// Write the layers of `items` to a new KMZ at `path`, with one folder per layer.
+///
+/// Geometries must be in [`KML_SRID`]. Each feature becomes a placemark named after the
+/// feature with its `properties` as `ExtendedData`, styled in its collection's colour.
+pub fn write_kmz(path: &Path, items: impl IntoIterator<Item = ExportItem>) -> anyhow::Result<()> {
+    // The KML driver only writes plain KML, so write it straight into a new zip archive
+    let kml_path = format!("/vsizip/{}/doc.kml", path.display());
+    let mut dataset = gdal::DriverManager::get_driver_by_name("KML")
+        .context("failed to get kml driver")?
+        .create_vector_only(&kml_path)
+        .context("failed to create kmz")?;
+    let srs = SpatialRef::from_epsg(KML_SRID).context("failed to create spatial ref")?;
+    let mut items = items.into_iter().peekable();
+    while let Some(schema) = next_layer(&mut items)? {
+        let mut target = LayerWriter::create(&mut dataset, &srs, &schema, &KML_LAYOUT, None)?;
+        for feature in layer_features(&mut items) {
+            target.write(&feature)?;
+        }
+    }
+    close_dataset(dataset, "kmz")
+}
+
+/// Write the layers of `items` to a new DXF at `path`, with every geometry in the CRS it
+/// was selected in.
+///
+/// DXF has a single layer of entities, so each feature is put on a CAD layer named after
+/// its collection, along with a text entity holding the feature's name. DXF can't hold
+/// any other attributes.
+pub fn write_dxf(path: &Path, items: impl IntoIterator<Item = ExportItem>) -> anyhow::Result<()> {
+    let mut dataset = gdal::DriverManager::get_driver_by_name("DXF")
+        .context("failed to get dxf driver")?
+        .create_vector_only(path)
+        .context("failed to create dxf")?;
+    let mut target = dataset
+        .create_layer(LayerOptions {
+            name: "entities",
+            ..Default::default()
+        })
+        .context("failed to create dxf entities")?;
+    // The one field DXF accepts besides its own, used for the text entities' labels
+    target
+        .create_defn_fields(&[(SystemField::Style.name(), OGRFieldType::OFTString)])
+        .context("failed to create dxf style field")?;
+
+    let mut items = items.into_iter().peekable();
+    while let Some(schema) = next_layer(&mut items)? {
+        let cad_layer = FieldValue::StringValue(schema.name);
+        for feature in layer_features(&mut items) {
+            let geom =
+                Geometry::from_wkb(&feature.geom_wkb).context("failed to read feature geometry")?;
+            let label_point = geom.point_on_surface();
+            target
+                .create_feature_fields(geom, &["Layer"], &[cad_layer.clone()])
+                .context(format!("failed to write feature {}", feature.id))?;
+            if feature.name.is_empty() {
+                continue;
+            }
+            target
+                .create_feature_fields(
+                    label_point,
+                    &["Layer", SystemField::Style.name()],
+                    &[
+                        cad_layer.clone(),
+                        FieldValue::StringValue(text_label_style(&feature.name)),
+                    ],
+                )
+                .context(format!("failed to write name of feature {}", feature.id))?;
+        }
+    }
+    close_dataset(dataset, "dxf")
+}
+
+/// OGR style string that makes a point a DXF text entity reading `text`
+fn text_label_style(text: &str) -> String {
+    let text = text.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("LABEL(f:\"Arial\",t:\"{text}\",s:{DXF_TEXT_HEIGHT}g)")
+}
+
+/// OGR style string of a layer's features: its collection's colour as a point symbol,
+/// a line or a polygon outline with a translucent fill.
+fn layer_style(schema: &LayerSchema) -> String {
+    let colour = collection_colour(&schema.name);
+    match flatten_type(schema.geometry_type) {
+        OGRwkbGeometryType::wkbPoint | OGRwkbGeometryType::wkbMultiPoint => {
+            format!("SYMBOL(c:{colour})")
+        }
+        OGRwkbGeometryType::wkbLineString | OGRwkbGeometryType::wkbMultiLineString => {
+            format!("PEN(c:{colour},w:3px)")
+        }
+        _ => format!("PEN(c:{colour},w:2px);BRUSH(fc:{colour}66)"),
+    }
+}
+
+/// Hex colour of a layer, the same for a collection slug in every export
+pub(crate) fn collection_colour(name: &str) -> &'static str {
+    let hash = name.bytes().fold(0u32, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte.into())
+    });
+    COLLECTION_COLOURS[hash as usize % COLLECTION_COLOURS.len()]
+}
+
+/// Write an export to a file in a private temporary directory with `write` and return it
+/// opened for reading.
+///
+/// The directory is removed straight away, so the file disappears once the returned
+/// handle is closed and large exports can be streamed without holding them in memory.
+pub fn write_temp_file(
+    filename: &str,
+    write: impl FnOnce(&Path) -> anyhow::Result<()>,
+) -> anyhow::Result<File> {
+    let dir = tempfile::Builder::new()
+        .prefix("geoman-export-")
+        .tempdir()
+        .context("failed to create export directory")?;
+    let path = dir.path().join(filename);
+    write(&path)?;
+    File::open(&path).context("failed to open export file")
+}
+
+pub(crate) fn create_geopackage(path: &Path) -> anyhow::Result<Dataset> {
+    gdal::DriverManager::get_driver_by_name("GPKG")
+        .context("failed to get geopackage driver")?
+        .create_vector_only(path)
+        .context("failed to create geopackage")
+}
+
+pub(crate) fn create_shapefile(path: &Path) -> anyhow::Result<Dataset> {
+    gdal::DriverManager::get_driver_by_name("ESRI Shapefile")
+        .context("failed to get shapefile driver")?
+        .create_vector_only(path)
+        .context("failed to create shapefile")
+}
+
+/// Flush and close a dataset written as `format`, reporting any error writing it out
+pub(crate) fn close_dataset(mut dataset: Dataset, format: &str) -> anyhow::Result<()> {
+    dataset.flush_cache().context("failed to flush cache")?;
+    dataset
+        .close()
+        .context(format!("failed to close {format}"))?;
+    Ok(())
+}
+
+/// A layer created from its schema, which features are then written to one at a time
+pub(crate) struct LayerWriter<'a> {
+    target: Layer<'a>,
+    layout: &'static FieldLayout,
+    properties: Vec<PropertyField>,
+    style: String,
+}
+
+impl<'a> LayerWriter<'a> {
+    pub(crate) fn create(
+        dataset: &'a mut Dataset,
+        srs: &SpatialRef,
+        schema: &LayerSchema,
+        layout: &'static FieldLayout,
+        options: Option<&[&str]>,
+    ) -> anyhow::Result<Self> {
+        let mut geometry_type = schema.geometry_type;
+        if schema.has_z {
+            geometry_type |= WKB_25D_BIT;
+        }
+
+        let properties = property_fields(schema, layout);
+        let mut fields: Vec<(&str, OGRFieldType::Type)> = layout
+            .system_fields
+            .iter()
+            .map(|field| (field.name(), field.field_type()))
+            .collect();
+        fields.extend(
+            properties
+                .iter()
+                .map(|field| (field.name.as_str(), field.field_type)),
+        );
+
+        let target = dataset
+            .create_layer(LayerOptions {
+                name: &schema.name,
+                srs: Some(srs),
+                ty: geometry_type,
+                options,
+            })
+            .context(format!("failed to create layer '{}'", schema.name))?;
+        target.create_defn_fields(&fields).context(format!(
+            "failed to create fields of layer '{}'",
+            schema.name
+        ))?;
+        Ok(Self {
+            target,
+            layout,
+            properties,
+            style: layer_style(schema),
+        })
+    }
+
+    pub(crate) fn write(&mut self, feature: &ExportFeature) -> anyhow::Result<()> {
+        let geom =
+            Geometry::from_wkb(&feature.geom_wkb).context("failed to read feature geometry")?;
+        let (mut names, mut values): (Vec<&str>, Vec<FieldValue>) = self
+            .layout
+            .system_fields
+            .iter()
+            .map(|field| (field.name(), field.value(feature, &self.style)))
+            .unzip();
+        for field in &self.properties {
+            if let Some(value) = feature
+                .properties
+                .get(&field.key)
+                .and_then(|value| field_value(value, field.field_type))
+            {
+                names.push(&field.name);
+                values.push(value);
+            }
+        }
+        self.target
+            .create_feature_fields(geom, &names, &values)
+            .context(format!("failed to write feature {}", feature.id))?;
+        Ok(())
+    }
+}
+
+/// A `properties` key written as a field of its own
+struct PropertyField {
+    key: String,
+    name: String,
+    field_type: OGRFieldType::Type,
+}
+
+/// One field per `properties` key of a layer, in order of first appearance.
+///
+/// Names are truncated to the layout's limit, and keys clashing with a system field or an
+/// earlier key (field names are case-insensitive in most formats) get a numbered suffix,
+/// e.g. a `status` property becomes `status_2`. Keys are visited in the same order for the
+/// same features, so the names are deterministic.
+fn property_fields(schema: &LayerSchema, layout: &FieldLayout) -> Vec<PropertyField> {
+    let mut taken: Vec<String> = layout
+        .system_fields
+        .iter()
+        .map(|field| field.name().to_lowercase())
+        .collect();
+    schema
+        .properties
+        .iter()
+        .map(|(key, field_type)| {
+            let name = unique_name(key, &taken, layout.max_name_len);
+            taken.push(name.to_lowercase());
+            PropertyField {
+                key: key.clone(),
+                name,
+                field_type: field_type.unwrap_or(OGRFieldType::OFTString),
+            }
+        })
+        .collect()
+}
+
+fn unique_name(key: &str, taken: &[String], max_len: Option<usize>) -> String {
+    let max_len = max_len.unwrap_or(usize::MAX);
+    let is_taken = |name: &str| taken.contains(&name.to_lowercase());
+    let name = truncate(key, max_len);
+    if !is_taken(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| {
+            let suffix = format!("_{n}");
+            format!(
+                "{}{suffix}",
+                truncate(key, max_len.saturating_sub(suffix.len()))
+            )
+        })
+        .find(|name| !is_taken(name))
+        .expect("ran out of field name suffixes")
+}
+
+/// The longest prefix of `name` of at most `max_len` bytes that ends on a character boundary
+fn truncate(name: &str, max_len: usize) -> &str {
+    let mut end = max_len.min(name.len());
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    &name[..end]
+}
+
+/// The narrowest field type that holds `value` as well as every value `field_type` held.
+/// Booleans are written as integers, and arrays, objects and mixed values as their JSON
+/// text.
+fn widen_field_type(
+    field_type: Option<OGRFieldType::Type>,
+    value: &Value,
+) -> Option<OGRFieldType::Type> {
+    let value_type = match value {
+        Value::Null => return field_type,
+        Value::Bool(_) => OGRFieldType::OFTInteger,
+        Value::Number(number) if number.is_i64() => OGRFieldType::OFTInteger64,
+        Value::Number(_) => OGRFieldType::OFTReal,
+        _ => OGRFieldType::OFTString,
+    };
+    Some(match (field_type, value_type) {
+        (None, value_type) => value_type,
+        (Some(current), value_type) if current == value_type => current,
+        (
+            Some(OGRFieldType::OFTInteger64 | OGRFieldType::OFTReal),
+            OGRFieldType::OFTInteger64 | OGRFieldType::OFTReal,
+        ) => OGRFieldType::OFTReal,
+        _ => OGRFieldType::OFTString,
+    })
+}
+
+fn field_value(value: &Value, field_type: OGRFieldType::Type) -> Option<FieldValue> {
+    Some(match (value, field_type) {
+        (Value::Null, _) => return None,
+        (Value::Bool(value), OGRFieldType::OFTInteger) => FieldValue::IntegerValue((*value).into()),
+        (Value::Number(number), OGRFieldType::OFTInteger64) => {
+            FieldValue::Integer64Value(number.as_i64()?)
+        }
+        (Value::Number(number), OGRFieldType::OFTReal) => FieldValue::RealValue(number.as_f64()?),
+        (Value::String(value), _) => FieldValue::StringValue(value.clone()),
+        (value, _) => FieldValue::StringValue(value.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapefile_processor::has_z;
+    use serde_json::json;
+
+    fn export_feature(id: i32, wkt: &str, properties: Value) -> ExportFeature {
+        ExportFeature {
+            id,
+            name: format!("feature {id}"),
+            is_primary: id == 1,
+            status: "ACTIVE".to_string(),
+            added_by: "Ada Lovelace".to_string(),
+            last_updated: Utc::now(),
+            properties: properties.as_object().cloned().unwrap_or_default(),
+            geom_wkb: Geometry::from_wkt(wkt).unwrap().wkb().unwrap(),
+        }
+    }
+
+    /// The features of one collection, as a test builds them
+    struct ExportLayer {
+        name: String,
+        geometry_type: OGRwkbGeometryType::Type,
+        features: Vec<ExportFeature>,
+    }
+
+    /// `layers` as they're read for a streamed export
+    fn export_items(layers: Vec<ExportLayer>) -> Vec<ExportItem> {
+        let mut items = Vec::new();
+        for layer in layers {
+            let mut schema = LayerSchema::new(layer.name, layer.geometry_type);
+            for feature in &layer.features {
+                let geom = Geometry::from_wkb(&feature.geom_wkb).unwrap();
+                schema.add(&feature.properties, has_z(geom.geometry_type()));
+            }
+            items.push(ExportItem::Layer(schema));
+            items.extend(layer.features.into_iter().map(ExportItem::Feature));
+        }
+        items
+    }
+
+    fn schema(features: &[ExportFeature]) -> LayerSchema {
+        let mut schema = LayerSchema::new("turbines".to_string(), OGRwkbGeometryType::wkbPoint);
+        for feature in features {
+            schema.add(&feature.properties, false);
+        }
+        schema
+    }
+
+    #[test]
+    fn property_types_are_widened_across_features() {
+        let field_type = |values: &[Value]| values.iter().fold(None, widen_field_type);
+        assert_eq!(
+            field_type(&[json!(1), json!(null), json!(2.5)]),
+            Some(OGRFieldType::OFTReal)
+        );
+        assert_eq!(
+            field_type(&[json!(1), json!("one")]),
+            Some(OGRFieldType::OFTString)
+        );
+        assert_eq!(
+            field_type(&[json!(true), json!(false)]),
+            Some(OGRFieldType::OFTInteger)
+        );
+        assert_eq!(field_type(&[json!(null)]), None);
+    }
+
+    #[test]
+    fn schema_keeps_keys_in_order_of_first_appearance() {
+        let features = [
+            export_feature(1, "POINT(0 0)", json!({"b": 1, "a": null})),
+            export_feature(2, "POINT(0 0)", json!({"c": "x", "a": 2.5, "b": 2})),
+        ];
+        assert_eq!(
+            schema(&features).properties,
+            vec![
+                ("b".to_string(), Some(OGRFieldType::OFTInteger64)),
+                ("a".to_string(), Some(OGRFieldType::OFTReal)),
+                ("c".to_string(), Some(OGRFieldType::OFTString)),
+            ]
+        );
+    }
+
+    #[test]
+    fn property_keys_clashing_with_system_fields_are_renamed() {
+        let features = vec![export_feature(
+            1,
+            "POINT(0 0)",
+            json!({"Status": "consented", "status_2": 1}),
+        )];
+        let names: Vec<_> = property_fields(&schema(&features), &GEOPACKAGE_LAYOUT)
+            .into_iter()
+            .map(|field| field.name)
+            .collect();
+        assert_eq!(names, vec!["Status_2", "status_2_2"]);
+    }
+
+    #[test]
+    fn geopackage_has_one_layer_per_collection_with_typed_fields() {
+        let path = format!("/vsimem/{}.gpkg", uuid::Uuid::new_v4());
+        let layers = vec![
+            ExportLayer {
+                name: "site-boundaries".to_string(),
+                geometry_type: OGRwkbGeometryType::wkbMultiPolygon,
+                features: vec![export_feature(
+                    1,
+                    "MULTIPOLYGON(((0 0,1 0,1 1,0 0)))",
+                    json!({"capacity_mw": 49.9, "turbines": 7, "consented": true}),
+                )],
+            },
+            ExportLayer {
+                name: "turbines".to_string(),
+                geometry_type: OGRwkbGeometryType::wkbPoint,
+                features: vec![
+                    export_feature(2, "POINT(0 0)", json!({"hub_height": 90})),
+                    export_feature(3, "POINT(1 1)", json!({"hub_height": 92.5})),
+                ],
+            },
+        ];
+        write_geopackage(Path::new(&path), 27700, export_items(layers))
+            .expect("failed to write geopackage");
+
+        let dataset = Dataset::open(&path).unwrap();
+        assert_eq!(dataset.layer_count(), 2);
+        let mut boundaries = dataset.layer_by_name("site-boundaries").unwrap();
+        let field_types: Vec<_> = boundaries
+            .defn()
+            .fields()
+            .map(|field| (field.name(), field.field_type()))
+            .collect();
+        assert!(field_types.contains(&("capacity_mw".to_string(), OGRFieldType::OFTReal)));
+        assert!(field_types.contains(&("turbines".to_string(), OGRFieldType::OFTInteger64)));
+        assert!(field_types.contains(&("last_updated".to_string(), OGRFieldType::OFTDateTime)));
+        {
+            let feature = boundaries.features().next().unwrap();
+            assert_eq!(
+                feature.field_as_integer_by_name("is_primary").unwrap(),
+                Some(1)
+            );
+        }
+        assert_eq!(
+            boundaries.spatial_ref().unwrap().auth_code().unwrap(),
+            27700
+        );
+        let turbines = dataset.layer_by_name("turbines").unwrap();
+        assert_eq!(turbines.feature_count(), 2);
+        drop(dataset);
+        let _ = gdal::vsi::unlink_mem_file(&path);
+    }
+
+    #[test]
+    fn shapefile_field_names_are_truncated_deterministically() {
+        let features = vec![export_feature(
+            1,
+            "POINT(0 0)",
+            json!({"hub_height_m": 90, "hub_height_ft": 295, "Statusbericht": "ok", "ŵyneb_ŵyneb": 1}),
+        )];
+        let names: Vec<_> = property_fields(&schema(&features), &SHAPEFILE_LAYOUT)
+            .into_iter()
+            .map(|field| field.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec!["Statusberi", "hub_height", "hub_heig_2", "ŵyneb_ŵy"]
+        );
+    }
+
+    #[test]
+    fn shapefile_export_writes_one_layer_with_system_fields() {
+        for extension in ["shz", "shp.zip"] {
+            let path = format!("/vsimem/{}.{extension}", uuid::Uuid::new_v4());
+            let layer = ExportLayer {
+                name: "turbines".to_string(),
+                geometry_type: OGRwkbGeometryType::wkbPoint,
+                features: vec![
+                    export_feature(1, "POINT(0 0)", json!({"hub_height_m": 90})),
+                    export_feature(2, "POINT(1 1)", json!({"hub_height_ft": 301.5})),
+                ],
+            };
+            write_shapefile(Path::new(&path), 27700, export_items(vec![layer]))
+                .expect("failed to write shapefile");
+
+            let dataset = Dataset::open(&path).unwrap();
+            assert_eq!(dataset.layer_count(), 1);
+            let layer = dataset.layer(0).unwrap();
+            let names: Vec<_> = layer.defn().fields().map(|field| field.name()).collect();
+            assert_eq!(
+                names,
+                vec![
+                    "id",
+                    "name",
+                    "is_primary",
+                    "status",
+                    "hub_height",
+                    "hub_heig_2"
+                ]
+            );
+            assert_eq!(layer.feature_count(), 2);
+            drop(dataset);
+            let _ = gdal::vsi::unlink_mem_file(&path);
+        }
+    }
+
+    #[test]
+    fn kmz_has_named_placemarks_styled_by_collection() {
+        let path = format!("/vsimem/{}.kmz", uuid::Uuid::new_v4());
+        let layers = vec![ExportLayer {
+            name: "site-boundaries".to_string(),
+            geometry_type: OGRwkbGeometryType::wkbMultiPolygon,
+            features: vec![export_feature(
+                1,
+                "MULTIPOLYGON(((-3 52,-2.9 52,-2.9 52.1,-3 52)))",
+                json!({"landowner": "Tŷ Mawr"}),
+            )],
+        }];
+        write_kmz(Path::new(&path), export_items(layers)).expect("failed to write kmz");
+
+        let dataset = Dataset::open(format!("/vsizip/{path}/doc.kml")).unwrap();
+        let mut layer = dataset.layer_by_name("site-boundaries").unwrap();
+        {
+            let feature = layer.features().next().unwrap();
+            assert_eq!(
+                feature.field_as_string_by_name("Name").unwrap().as_deref(),
+                Some("feature 1")
+            );
+            assert_eq!(
+                feature
+                    .field_as_string_by_name("landowner")
+                    .unwrap()
+                    .as_deref(),
+                Some("Tŷ Mawr")
+            );
+        }
+        drop(dataset);
+        let _ = gdal::vsi::unlink_mem_file(&path);
+    }
+
+    #[test]
+    fn dxf_has_cad_layer_per_collection_with_name_text() {
+        let path = format!("/vsimem/{}.dxf", uuid::Uuid::new_v4());
+        let layers = vec![
+            ExportLayer {
+                name: "site-boundaries".to_string(),
+                geometry_type: OGRwkbGeometryType::wkbMultiPolygon,
+                features: vec![export_feature(
+                    1,
+                    "MULTIPOLYGON(((300000 200000,300100 200000,300100 200100,300000 200000)))",
+                    json!({}),
+                )],
+            },
+            ExportLayer {
+                name: "turbines".to_string(),
+                geometry_type: OGRwkbGeometryType::wkbPoint,
+                features: vec![export_feature(2, "POINT(300050 200020)", json!({}))],
+            },
+        ];
+        write_dxf(Path::new(&path), export_items(layers)).expect("failed to write dxf");
+
+        let dataset = Dataset::open(&path).unwrap();
+        let mut layer = dataset.layer(0).unwrap();
+        let entities: Vec<_> = layer
+            .features()
+            .map(|feature| {
+                (
+                    feature.field_as_string_by_name("Layer").unwrap(),
+                    feature.field_as_string_by_name("Text").unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(entities.len(), 4);
+        assert!(entities.contains(&(
+            Some("site-boundaries".to_string()),
+            Some("feature 1".to_string())
+        )));
+        assert!(entities.contains(&(Some("turbines".to_string()), Some("feature 2".to_string()))));
+        drop(dataset);
+        let _ = gdal::vsi::unlink_mem_file(&path);
+    }
+
+    #[test]
+    fn text_label_style_escapes_quotes() {
+        assert_eq!(
+            text_label_style(r#"T1 "north""#),
+            r#"LABEL(f:"Arial",t:"T1 \"north\"",s:5g)"#
+        );
+    }
+
+    #[test]
+    fn layer_style_matches_geometry_family() {
+        let schema = |geometry_type| LayerSchema::new("turbines".to_string(), geometry_type);
+        assert!(layer_style(&schema(OGRwkbGeometryType::wkbPoint)).starts_with("SYMBOL"));
+        assert!(layer_style(&schema(OGRwkbGeometryType::wkbMultiPolygon)).contains("BRUSH"));
+    }
+
+    #[test]
+    fn shapefile_export_rejects_a_second_layer() {
+        let path = format!("/vsimem/{}.shz", uuid::Uuid::new_v4());
+        let layer = |name: &str| ExportLayer {
+            name: name.to_string(),
+            geometry_type: OGRwkbGeometryType::wkbPoint,
+            features: vec![export_feature(1, "POINT(0 0)", json!({}))],
+        };
+        let items = export_items(vec![layer("turbines"), layer("met-masts")]);
+        assert!(write_shapefile(Path::new(&path), 27700, items).is_err());
+        let _ = gdal::vsi::unlink_mem_file(&path);
+    }
+}
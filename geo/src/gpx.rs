@@ -0,0 +1,289 @@
+use anyhow::{Context, anyhow};
+use chrono::{DateTime, FixedOffset};
+use gdal::{
+    Dataset,
+    spatial_ref::SpatialRef,
+    vector::{
+        Feature, FieldValue, Layer, LayerAccess, LayerOptions, OGRFieldType, OGRwkbGeometryType,
+    },
+};
+use std::collections::HashMap;
+
+use crate::virtual_shapefile::ShapefileError;
+
+/// GPX coordinates are always WGS 84 longitude/latitude.
+pub const GPX_SRID: u32 = 4326;
+/// Layer of the opened dataset holding waypoints as points
+pub const WAYPOINTS_LAYER: &str = "waypoints";
+/// Layer of the opened dataset holding tracks and routes as lines
+pub const TRACKS_LAYER: &str = "tracks";
+
+const WAYPOINT_FIELDS: [&str; 6] = ["name", "desc", "cmt", "type", "ele", "time"];
+const TRACK_FIELDS: [&str; 4] = ["name", "desc", "cmt", "type"];
+
+/// Open a `.gpx` file as a dataset in EPSG:4326 with up to two layers: [`WAYPOINTS_LAYER`]
+/// and [`TRACKS_LAYER`], which holds both tracks and routes. Empty layers are left out.
+///
+/// Elevation is dropped from the geometries and kept in the attributes instead: waypoints
+/// keep their `ele` and `time`, tracks and routes get the elevation range and the start and
+/// end time of their points.
+pub fn open_gpx(path: &str) -> Result<Dataset, ShapefileError> {
+    let source = Dataset::open(path)
+        .context("failed to open gpx dataset")
+        .map_err(ShapefileError::InvalidData)?;
+    let srs = SpatialRef::from_epsg(GPX_SRID)
+        .context("failed to create spatial ref")
+        .map_err(ShapefileError::UnexpectedError)?;
+    let mut target = gdal::DriverManager::get_driver_by_name("Memory")
+        .context("failed to get memory driver")
+        .map_err(ShapefileError::UnexpectedError)?
+        .create_vector_only("")
+        .context("failed to create memory dataset")
+        .map_err(ShapefileError::UnexpectedError)?;
+
+    if let Ok(mut waypoints) = source.layer_by_name("waypoints")
+        && waypoints.feature_count() > 0
+    {
+        let mut layer = create_layer(
+            &mut target,
+            WAYPOINTS_LAYER,
+            OGRwkbGeometryType::wkbPoint,
+            &srs,
+            &[
+                ("name", OGRFieldType::OFTString),
+                ("desc", OGRFieldType::OFTString),
+                ("cmt", OGRFieldType::OFTString),
+                ("type", OGRFieldType::OFTString),
+                ("ele", OGRFieldType::OFTReal),
+                ("time", OGRFieldType::OFTDateTime),
+            ],
+        )?;
+        for feature in waypoints.features() {
+            copy_feature(&mut layer, &feature, &WAYPOINT_FIELDS, Vec::new())?;
+        }
+    }
+
+    let mut tracks = source
+        .layer_by_name("tracks")
+        .ok()
+        .map(|layer| ("track", layer, "track_points", "track_fid"))
+        .into_iter()
+        .chain(
+            source
+                .layer_by_name("routes")
+                .ok()
+                .map(|layer| ("route", layer, "route_points", "route_fid")),
+        )
+        .filter(|(_, layer, _, _)| layer.feature_count() > 0)
+        .peekable();
+    if tracks.peek().is_some() {
+        let mut layer = create_layer(
+            &mut target,
+            TRACKS_LAYER,
+            OGRwkbGeometryType::wkbMultiLineString,
+            &srs,
+            &[
+                ("name", OGRFieldType::OFTString),
+                ("desc", OGRFieldType::OFTString),
+                ("cmt", OGRFieldType::OFTString),
+                ("type", OGRFieldType::OFTString),
+                ("source", OGRFieldType::OFTString),
+                ("ele_min", OGRFieldType::OFTReal),
+                ("ele_max", OGRFieldType::OFTReal),
+                ("start_time", OGRFieldType::OFTDateTime),
+                ("end_time", OGRFieldType::OFTDateTime),
+            ],
+        )?;
+        for (kind, mut lines, points_layer, fid_field) in tracks {
+            let summaries = source
+                .layer_by_name(points_layer)
+                .map(|mut points| summarise_points(&mut points, fid_field))
+                .unwrap_or_default();
+            for (index, feature) in lines.features().enumerate() {
+                let fid = feature.fid().map_or(index as i64, |fid| fid as i64);
+                let mut extra = vec![("source", FieldValue::StringValue(kind.to_string()))];
+                if let Some(summary) = summaries.get(&fid) {
+                    extra.extend(summary.field_values());
+                }
+                copy_feature(&mut layer, &feature, &TRACK_FIELDS, extra)?;
+            }
+        }
+    }
+
+    if target.layer_count() == 0 {
+        return Err(ShapefileError::InvalidData(anyhow!(
+            "no waypoints, tracks or routes found in gpx"
+        )));
+    }
+    Ok(target)
+}
+
+fn create_layer<'a>(
+    dataset: &'a mut Dataset,
+    name: &str,
+    ty: OGRwkbGeometryType::Type,
+    srs: &SpatialRef,
+    fields: &[(&str, OGRFieldType::Type)],
+) -> Result<Layer<'a>, ShapefileError> {
+    let layer = dataset
+        .create_layer(LayerOptions {
+            name,
+            srs: Some(srs),
+            ty,
+            options: None,
+        })
+        .context(format!("failed to create {name} layer"))
+        .map_err(ShapefileError::UnexpectedError)?;
+    layer
+        .create_defn_fields(fields)
+        .context(format!("failed to create {name} fields"))
+        .map_err(ShapefileError::UnexpectedError)?;
+    Ok(layer)
+}
+
+/// Copy a feature as a 2D geometry with the set values of `fields` and `extra` values.
+fn copy_feature(
+    layer: &mut Layer,
+    feature: &Feature,
+    fields: &[&str],
+    extra: Vec<(&str, FieldValue)>,
+) -> Result<(), ShapefileError> {
+    let Some(geom) = feature.geometry() else {
+        return Ok(());
+    };
+    let mut geom = geom.clone();
+    geom.flatten_to_2d();
+    let (names, values): (Vec<&str>, Vec<FieldValue>) = fields
+        .iter()
+        .filter_map(|&name| {
+            let value = feature.field(feature.field_index(name).ok()?).ok()??;
+            Some((name, value))
+        })
+        .chain(extra)
+        .unzip();
+    layer
+        .create_feature_fields(geom, &names, &values)
+        .context("failed to copy gpx feature")
+        .map_err(ShapefileError::UnexpectedError)?;
+    Ok(())
+}
+
+/// Elevation range and time span of the points of a track or route
+#[derive(Default)]
+struct PointSummary {
+    ele_min: Option<f64>,
+    ele_max: Option<f64>,
+    start_time: Option<DateTime<FixedOffset>>,
+    end_time: Option<DateTime<FixedOffset>>,
+}
+
+impl PointSummary {
+    fn add(&mut self, ele: Option<f64>, time: Option<DateTime<FixedOffset>>) {
+        if let Some(ele) = ele {
+            self.ele_min = Some(self.ele_min.map_or(ele, |min| min.min(ele)));
+            self.ele_max = Some(self.ele_max.map_or(ele, |max| max.max(ele)));
+        }
+        if let Some(time) = time {
+            self.start_time = Some(self.start_time.map_or(time, |start| start.min(time)));
+            self.end_time = Some(self.end_time.map_or(time, |end| end.max(time)));
+        }
+    }
+
+    fn field_values(&self) -> Vec<(&'static str, FieldValue)> {
+        [
+            ("ele_min", self.ele_min.map(FieldValue::RealValue)),
+            ("ele_max", self.ele_max.map(FieldValue::RealValue)),
+            ("start_time", self.start_time.map(FieldValue::DateTimeValue)),
+            ("end_time", self.end_time.map(FieldValue::DateTimeValue)),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| (name, value)))
+        .collect()
+    }
+}
+
+/// Summarise the `track_points` or `route_points` layer by the track or route they belong to
+fn summarise_points(points: &mut Layer, fid_field: &str) -> HashMap<i64, PointSummary> {
+    let mut summaries: HashMap<i64, PointSummary> = HashMap::new();
+    for point in points.features() {
+        let Ok(Some(fid)) = point.field_as_integer_by_name(fid_field) else {
+            continue;
+        };
+        let ele = point.field_as_double_by_name("ele").ok().flatten();
+        let time = point.field_as_datetime_by_name("time").ok().flatten();
+        summaries.entry(fid as i64).or_default().add(ele, time);
+    }
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{attributes::merge_layer_attributes, shapefile_processor::merge_layer_geometries};
+    use gdal::vsi;
+    use serde_json::json;
+
+    const GPX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+  <wpt lat="52.40" lon="-3.10"><ele>312.5</ele><time>2025-05-01T09:00:00Z</time><name>Gate</name></wpt>
+  <wpt lat="52.41" lon="-3.11"><ele>330.0</ele><name>Stile</name></wpt>
+  <trk>
+    <name>Access route</name>
+    <trkseg>
+      <trkpt lat="52.40" lon="-3.10"><ele>310</ele><time>2025-05-01T09:00:00Z</time></trkpt>
+      <trkpt lat="52.41" lon="-3.11"><ele>335</ele><time>2025-05-01T09:20:00Z</time></trkpt>
+      <trkpt lat="52.42" lon="-3.12"><ele>320</ele><time>2025-05-01T09:45:00Z</time></trkpt>
+    </trkseg>
+  </trk>
+</gpx>"#;
+
+    fn open_test_gpx(gpx: &str) -> Result<Dataset, ShapefileError> {
+        let path = format!("/vsimem/{}.gpx", uuid::Uuid::new_v4());
+        vsi::create_mem_file(&path, gpx.as_bytes().to_vec()).unwrap();
+        let result = open_gpx(&path);
+        let _ = vsi::unlink_mem_file(&path);
+        result
+    }
+
+    #[test]
+    fn waypoints_keep_elevation_and_time_as_attributes() {
+        let ds = open_test_gpx(GPX).expect("failed to read gpx");
+        let mut layer = ds.layer_by_name(WAYPOINTS_LAYER).unwrap();
+        let merged = merge_layer_geometries(&mut layer, OGRwkbGeometryType::wkbMultiPoint).unwrap();
+        assert_eq!(merged.geometry_count(), 2);
+        let properties = merge_layer_attributes(&mut layer, Default::default()).unwrap();
+        assert_eq!(properties.get("name"), Some(&json!(["Gate", "Stile"])));
+        assert_eq!(properties.get("ele"), Some(&json!([312.5, 330.0])));
+        assert_eq!(
+            properties.get("time"),
+            Some(&json!(["2025-05-01T09:00:00+00:00", null]))
+        );
+    }
+
+    #[test]
+    fn tracks_are_2d_lines_with_elevation_range_and_time_span() {
+        let ds = open_test_gpx(GPX).expect("failed to read gpx");
+        let mut layer = ds.layer_by_name(TRACKS_LAYER).unwrap();
+        let merged = merge_layer_geometries(&mut layer, OGRwkbGeometryType::wkbLineString).unwrap();
+        assert_eq!(merged.geometry_type(), OGRwkbGeometryType::wkbLineString);
+        assert_eq!(merged.point_count(), 3);
+        let properties = merge_layer_attributes(&mut layer, Default::default()).unwrap();
+        assert_eq!(properties.get("source"), Some(&json!("track")));
+        assert_eq!(properties.get("ele_min"), Some(&json!(310.0)));
+        assert_eq!(properties.get("ele_max"), Some(&json!(335.0)));
+        assert_eq!(
+            properties.get("end_time"),
+            Some(&json!("2025-05-01T09:45:00+00:00"))
+        );
+    }
+
+    #[test]
+    fn empty_layers_are_left_out() {
+        let gpx = r#"<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+  <wpt lat="52.40" lon="-3.10"><name>Gate</name></wpt>
+</gpx>"#;
+        let ds = open_test_gpx(gpx).expect("failed to read gpx");
+        assert_eq!(ds.layer_count(), 1);
+        assert_eq!(ds.layer(0).unwrap().name(), WAYPOINTS_LAYER);
+    }
+}
@@ -0,0 +1,219 @@
+use anyhow::{Context, anyhow};
+use gdal::{
+    Dataset,
+    spatial_ref::SpatialRef,
+    vector::{FieldValue, Geometry, LayerAccess, LayerOptions, OGRFieldType, OGRwkbGeometryType},
+    vsi,
+};
+use uuid::Uuid;
+
+use crate::virtual_shapefile::ShapefileError;
+
+/// KML coordinates are always WGS 84 longitude/latitude.
+pub const KML_SRID: u32 = 4326;
+const LAYER_NAME: &str = "placemarks";
+
+/// Open a `.kml` file as a single-layer dataset in EPSG:4326.
+///
+/// Placemarks from every folder are flattened into one layer, Z values are dropped and
+/// `MultiGeometry` placemarks are split into their parts so the result can be passed
+/// straight to `merge_geometries`.
+pub fn dataset_from_kml(bytes: Vec<u8>) -> Result<Dataset, ShapefileError> {
+    let path = format!("/vsimem/{}.kml", Uuid::new_v4());
+    vsi::create_mem_file(&path, bytes)
+        .context("failed to create virtual kml file")
+        .map_err(ShapefileError::UnexpectedError)?;
+    let result = open_kml(&path);
+    let _ = vsi::unlink_mem_file(&path);
+    result
+}
+
+/// Open a `.kml` file by path, see [`dataset_from_kml`].
+pub fn open_kml(path: &str) -> Result<Dataset, ShapefileError> {
+    let source = Dataset::open(path)
+        .context("failed to open kml dataset")
+        .map_err(ShapefileError::InvalidData)?;
+    flatten_placemarks(&source)
+}
+
+/// Open a `.kmz` file, reading the zipped KML document through `/vsizip/`.
+pub fn dataset_from_kmz(bytes: Vec<u8>) -> Result<Dataset, ShapefileError> {
+    let path = format!("/vsimem/{}.kmz", Uuid::new_v4());
+    vsi::create_mem_file(&path, bytes)
+        .context("failed to create virtual kmz file")
+        .map_err(ShapefileError::UnexpectedError)?;
+    let result = open_kmz(&path);
+    let _ = vsi::unlink_mem_file(&path);
+    result
+}
+
+/// Open a `.kmz` file by path, see [`dataset_from_kmz`].
+pub fn open_kmz(path: &str) -> Result<Dataset, ShapefileError> {
+    open_zipped_kml(path).and_then(|source| flatten_placemarks(&source))
+}
+
+fn open_zipped_kml(kmz_path: &str) -> Result<Dataset, ShapefileError> {
+    let zip_path = format!("/vsizip/{kmz_path}");
+    let entries = vsi::read_dir(&zip_path, true)
+        .context("failed to read kmz archive")
+        .map_err(ShapefileError::InvalidData)?;
+    let mut kml_entries: Vec<_> = entries
+        .into_iter()
+        .filter(|entry| {
+            entry
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("kml"))
+        })
+        .collect();
+    // By convention the main document is doc.kml at the root of the archive
+    kml_entries.sort_by_key(|entry| {
+        (
+            entry.components().count(),
+            entry.file_name() != Some("doc.kml".as_ref()),
+        )
+    });
+    let kml = kml_entries
+        .first()
+        .ok_or_else(|| ShapefileError::IncorrectFiles("no .kml document in kmz".to_string()))?;
+    Dataset::open(format!("{zip_path}/{}", kml.display()))
+        .context("failed to open kml document in kmz")
+        .map_err(ShapefileError::InvalidData)
+}
+
+/// Copy the placemarks of every layer (KML folder) into a single in-memory layer.
+fn flatten_placemarks(source: &Dataset) -> Result<Dataset, ShapefileError> {
+    let srs = SpatialRef::from_epsg(KML_SRID)
+        .context("failed to create spatial ref")
+        .map_err(ShapefileError::UnexpectedError)?;
+    let mut target = gdal::DriverManager::get_driver_by_name("Memory")
+        .context("failed to get memory driver")
+        .map_err(ShapefileError::UnexpectedError)?
+        .create_vector_only("")
+        .context("failed to create memory dataset")
+        .map_err(ShapefileError::UnexpectedError)?;
+    let mut target_layer = target
+        .create_layer(LayerOptions {
+            name: LAYER_NAME,
+            srs: Some(&srs),
+            ty: OGRwkbGeometryType::wkbUnknown,
+            options: None,
+        })
+        .context("failed to create placemark layer")
+        .map_err(ShapefileError::UnexpectedError)?;
+    target_layer
+        .create_defn_fields(&[
+            ("name", OGRFieldType::OFTString),
+            ("description", OGRFieldType::OFTString),
+            ("folder", OGRFieldType::OFTString),
+        ])
+        .context("failed to create placemark fields")
+        .map_err(ShapefileError::UnexpectedError)?;
+
+    for mut layer in source.layers() {
+        let folder = layer.name();
+        for feature in layer.features() {
+            let Some(geom) = feature.geometry() else {
+                continue;
+            };
+            let mut name = None;
+            let mut description = None;
+            for (field, value) in feature.fields() {
+                match (field.to_lowercase().as_str(), value) {
+                    ("name", Some(FieldValue::StringValue(v))) => name = Some(v),
+                    ("description", Some(FieldValue::StringValue(v))) => description = Some(v),
+                    _ => {}
+                }
+            }
+            let values = [
+                FieldValue::StringValue(name.unwrap_or_default()),
+                FieldValue::StringValue(description.unwrap_or_default()),
+                FieldValue::StringValue(folder.clone()),
+            ];
+            for part in geometry_parts(geom) {
+                target_layer
+                    .create_feature_fields(part, &["name", "description", "folder"], &values)
+                    .context("failed to copy placemark")
+                    .map_err(ShapefileError::UnexpectedError)?;
+            }
+        }
+    }
+    drop(target_layer);
+    if target
+        .layer(0)
+        .is_ok_and(|layer| layer.feature_count() == 0)
+    {
+        return Err(ShapefileError::InvalidData(anyhow!(
+            "no placemarks with geometry found in kml"
+        )));
+    }
+    Ok(target)
+}
+
+/// 2D copies of a geometry, with geometry collections split into their members.
+pub(crate) fn geometry_parts(geom: &Geometry) -> Vec<Geometry> {
+    let ty = geom.geometry_type();
+    if ty == OGRwkbGeometryType::wkbGeometryCollection
+        || ty == OGRwkbGeometryType::wkbGeometryCollection25D
+    {
+        return (0..geom.geometry_count())
+            .flat_map(|i| geometry_parts(&geom.get_geometry(i)))
+            .collect();
+    }
+    let mut part = geom.clone();
+    part.flatten_to_2d();
+    vec![part]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapefile_processor::merge_geometries;
+
+    const KML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2">
+  <Document>
+    <Folder>
+      <name>North field</name>
+      <Placemark>
+        <name>Field 1</name>
+        <Polygon><outerBoundaryIs><LinearRing>
+          <coordinates>-3.0,52.0,100 -2.9,52.0,100 -2.9,52.1,100 -3.0,52.1,100 -3.0,52.0,100</coordinates>
+        </LinearRing></outerBoundaryIs></Polygon>
+      </Placemark>
+    </Folder>
+    <Folder>
+      <name>South field</name>
+      <Placemark>
+        <name>Field 2</name>
+        <MultiGeometry>
+          <Polygon><outerBoundaryIs><LinearRing>
+            <coordinates>-3.0,51.0,0 -2.9,51.0,0 -2.9,51.1,0 -3.0,51.1,0 -3.0,51.0,0</coordinates>
+          </LinearRing></outerBoundaryIs></Polygon>
+          <Polygon><outerBoundaryIs><LinearRing>
+            <coordinates>-2.0,51.0,0 -1.9,51.0,0 -1.9,51.1,0 -2.0,51.1,0 -2.0,51.0,0</coordinates>
+          </LinearRing></outerBoundaryIs></Polygon>
+        </MultiGeometry>
+      </Placemark>
+    </Folder>
+  </Document>
+</kml>"#;
+
+    #[test]
+    fn kml_folders_are_flattened_into_one_2d_layer() {
+        let ds = dataset_from_kml(KML.as_bytes().to_vec()).expect("failed to read kml");
+        assert_eq!(ds.layer_count(), 1);
+        let layer = ds.layer(0).unwrap();
+        let srid = layer.spatial_ref().unwrap().auth_code().unwrap();
+        assert_eq!(srid, 4326);
+        let merged = merge_geometries(&ds, OGRwkbGeometryType::wkbMultiPolygon)
+            .expect("failed to merge kml geometries");
+        assert_eq!(merged.geometry_type(), OGRwkbGeometryType::wkbMultiPolygon);
+        assert_eq!(merged.geometry_count(), 3);
+    }
+
+    #[test]
+    fn empty_kml_returns_error() {
+        let kml = r#"<kml xmlns="http://www.opengis.net/kml/2.2"><Document></Document></kml>"#;
+        assert!(dataset_from_kml(kml.as_bytes().to_vec()).is_err());
+    }
+}
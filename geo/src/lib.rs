@@ -1,2 +1,10 @@
+pub mod attributes;
+pub mod bundle;
+pub mod dxf;
+pub mod export;
+pub mod gpx;
+pub mod kml;
 pub mod shapefile_processor;
+pub mod tabular;
+pub mod upload;
 pub mod virtual_shapefile;
@@ -1,10 +1,21 @@
 use anyhow::Context;
-use gdal::vector::{Geometry, LayerAccess, OGRwkbGeometryType, geometry_type_to_name};
+use gdal::{
+    cpl::CslStringList,
+    vector::{Geometry, Layer, LayerAccess, OGRwkbGeometryType, geometry_type_to_name},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::attributes::feature_properties;
 
 #[derive(thiserror::Error, Debug)]
 pub enum ProcessingError {
     #[error("no layers found in dataset")]
     NoLayers,
+    #[error("layer '{0}' not found in dataset")]
+    LayerNotFound(String),
+    #[error("dataset contains multiple layers, select one of: {}", .0.join(", "))]
+    MultipleLayers(Vec<String>),
     #[error("no features with geometry found in layer")]
     NoFeaturesWithGeometry,
     #[error("incompatible geometry type on feature {index}: expected {expected} but found {found}")]
@@ -21,15 +32,106 @@ pub enum ProcessingError {
     InvalidGeometry { index: usize },
     #[error("merged geometry is invalid")]
     InvalidMergedGeometry,
+    #[error("geometry on feature {index} could not be repaired")]
+    UnrepairableGeometry { index: usize },
+    #[error("field '{0}' not found in layer")]
+    FieldNotFound(String),
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
 
+/// Select a layer by name, or the only layer when no name is given.
+pub fn select_layer<'a>(
+    dataset: &'a gdal::Dataset,
+    name: Option<&str>,
+) -> Result<Layer<'a>, ProcessingError> {
+    if let Some(name) = name {
+        return dataset
+            .layer_by_name(name)
+            .map_err(|_| ProcessingError::LayerNotFound(name.to_string()));
+    }
+    if dataset.layer_count() > 1 {
+        return Err(ProcessingError::MultipleLayers(
+            dataset.layers().map(|layer| layer.name()).collect(),
+        ));
+    }
+    dataset.layers().next().ok_or(ProcessingError::NoLayers)
+}
+
+/// Like [`select_layer`], but when no name is given and the dataset holds several layers,
+/// pick the only one whose declared geometry type fits `expected_type`.
+///
+/// This lets e.g. a GPX file's waypoints go to a point collection and its tracks to a line
+/// collection without naming a layer.
+pub fn select_layer_for_type<'a>(
+    dataset: &'a gdal::Dataset,
+    name: Option<&str>,
+    expected_type: OGRwkbGeometryType::Type,
+) -> Result<Layer<'a>, ProcessingError> {
+    if name.is_some() || dataset.layer_count() <= 1 {
+        return select_layer(dataset, name);
+    }
+    let TypeInfo { single, multi } = compatible_types(expected_type)?;
+    let mut matching = dataset.layers().filter(|layer| {
+        layer.defn().geom_fields().next().is_some_and(|field| {
+            let field_type = flatten_type(field.field_type());
+            field_type == single || field_type == multi
+        })
+    });
+    match (matching.next(), matching.next()) {
+        (Some(layer), None) => Ok(layer),
+        _ => select_layer(dataset, None),
+    }
+}
+
 pub fn merge_geometries(
     dataset: &gdal::Dataset,
     expected_type: OGRwkbGeometryType::Type,
 ) -> Result<Geometry, ProcessingError> {
     let mut layer = dataset.layers().next().ok_or(ProcessingError::NoLayers)?;
+    merge_layer_geometries(&mut layer, expected_type)
+}
+
+pub fn merge_layer_geometries(
+    layer: &mut Layer,
+    expected_type: OGRwkbGeometryType::Type,
+) -> Result<Geometry, ProcessingError> {
+    merge(layer, expected_type, None)
+}
+
+/// What was changed when repairing geometries during a merge.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct RepairReport {
+    /// Indices of the features whose geometry was invalid and has been repaired
+    pub repaired_features: Vec<usize>,
+    /// Whether the merged geometry had to be repaired, e.g. because parts overlapped
+    pub merged_repaired: bool,
+    pub area_before: f64,
+    pub area_after: f64,
+    pub area_delta: f64,
+}
+
+/// Like [`merge_layer_geometries`], but invalid geometries are repaired with GDAL's
+/// `MakeValid` (falling back to a zero-width buffer) instead of being rejected.
+///
+/// Only the parts of a repaired geometry that match the expected type family are kept,
+/// so e.g. a ring that `MakeValid` turns into a polygon and a stray line keeps just the polygon.
+pub fn repair_layer_geometries(
+    layer: &mut Layer,
+    expected_type: OGRwkbGeometryType::Type,
+) -> Result<(Geometry, RepairReport), ProcessingError> {
+    let mut report = RepairReport::default();
+    let merged = merge(layer, expected_type, Some(&mut report))?;
+    report.area_after = merged.area();
+    report.area_delta = report.area_after - report.area_before;
+    Ok((merged, report))
+}
+
+fn merge(
+    layer: &mut Layer,
+    expected_type: OGRwkbGeometryType::Type,
+    mut repair: Option<&mut RepairReport>,
+) -> Result<Geometry, ProcessingError> {
     let TypeInfo { single, multi } = compatible_types(expected_type)?;
     let is_single = expected_type == single;
     let mut merged = Geometry::empty(multi).context("failed to create empty multi-geometry")?;
@@ -41,28 +143,33 @@ pub fn merge_geometries(
         if geom.is_empty() {
             continue;
         }
-        if !geom.is_valid() {
-            return Err(ProcessingError::InvalidGeometry { index });
-        }
         let geom_type = geom.geometry_type();
-
-        if geom_type == single {
-            merged
-                .add_geometry(geom.clone())
-                .context("failed to add geometry")?;
-        } else if geom_type == multi {
-            for i in 0..geom.geometry_count() {
-                merged
-                    .add_geometry(geom.get_geometry(i).clone())
-                    .context("failed to add sub-geometry")?;
-            }
-        } else {
+        if flatten_type(geom_type) != single && flatten_type(geom_type) != multi {
             return Err(ProcessingError::IncompatibleType {
                 index,
                 expected: geometry_type_to_name(expected_type),
                 found: geometry_type_to_name(geom_type),
             });
         }
+        if let Some(report) = repair.as_deref_mut() {
+            report.area_before += geom.area();
+        }
+        let mut parts = Vec::new();
+        if geom.is_valid() {
+            collect_parts(geom, single, &mut parts);
+        } else {
+            let Some(report) = repair.as_deref_mut() else {
+                return Err(ProcessingError::InvalidGeometry { index });
+            };
+            parts = repair_geometry(geom, single)
+                .ok_or(ProcessingError::UnrepairableGeometry { index })?;
+            report.repaired_features.push(index);
+        }
+        for part in parts {
+            merged
+                .add_geometry(part)
+                .context("failed to add geometry")?;
+        }
     }
 
     if merged.is_empty() {
@@ -70,7 +177,18 @@ pub fn merge_geometries(
     }
 
     if !merged.is_valid() {
-        return Err(ProcessingError::InvalidMergedGeometry);
+        let Some(report) = repair else {
+            return Err(ProcessingError::InvalidMergedGeometry);
+        };
+        let parts =
+            repair_geometry(&merged, single).ok_or(ProcessingError::InvalidMergedGeometry)?;
+        merged = Geometry::empty(multi).context("failed to create empty multi-geometry")?;
+        for part in parts {
+            merged
+                .add_geometry(part)
+                .context("failed to add repaired geometry")?;
+        }
+        report.merged_repaired = true;
     }
 
     if is_single && merged.geometry_count() > 1 {
@@ -87,13 +205,265 @@ pub fn merge_geometries(
     Ok(merged)
 }
 
+/// One input feature of a layer imported as a feature of its own.
+pub struct SplitFeature {
+    /// Index of the feature in the layer
+    pub index: usize,
+    /// Value of the requested name field, if it is set on this feature
+    pub name: Option<String>,
+    pub geometry: Geometry,
+    pub properties: Map<String, Value>,
+}
+
+/// Read every feature with a geometry from a layer as a separate [`SplitFeature`],
+/// converting each geometry to `expected_type` (e.g. wrapping a polygon in a multipolygon).
+///
+/// When `repair` is given, invalid geometries are repaired as in [`repair_layer_geometries`]
+/// and the report totals the area of all features.
+pub fn split_layer_geometries(
+    layer: &mut Layer,
+    expected_type: OGRwkbGeometryType::Type,
+    name_field: Option<&str>,
+    mut repair: Option<&mut RepairReport>,
+) -> Result<Vec<SplitFeature>, ProcessingError> {
+    let TypeInfo { single, multi } = compatible_types(expected_type)?;
+    if let Some(name_field) = name_field
+        && !layer
+            .defn()
+            .fields()
+            .any(|field| field.name() == name_field)
+    {
+        return Err(ProcessingError::FieldNotFound(name_field.to_string()));
+    }
+
+    let mut features = Vec::new();
+    for (index, feature) in layer.features().enumerate() {
+        let Some(geom) = feature.geometry() else {
+            continue;
+        };
+        if geom.is_empty() {
+            continue;
+        }
+        let geom_type = geom.geometry_type();
+        if flatten_type(geom_type) != single && flatten_type(geom_type) != multi {
+            return Err(ProcessingError::IncompatibleType {
+                index,
+                expected: geometry_type_to_name(expected_type),
+                found: geometry_type_to_name(geom_type),
+            });
+        }
+        let mut parts = Vec::new();
+        if geom.is_valid() {
+            collect_parts(geom, single, &mut parts);
+        } else {
+            let Some(report) = repair.as_deref_mut() else {
+                return Err(ProcessingError::InvalidGeometry { index });
+            };
+            parts = repair_geometry(geom, single)
+                .ok_or(ProcessingError::UnrepairableGeometry { index })?;
+            report.repaired_features.push(index);
+        }
+
+        let geometry = if expected_type == single {
+            if parts.len() > 1 {
+                return Err(ProcessingError::MultipleGeometries {
+                    expected: geometry_type_to_name(expected_type),
+                    count: parts.len(),
+                });
+            }
+            parts.remove(0)
+        } else {
+            let mut collected =
+                Geometry::empty(multi).context("failed to create empty multi-geometry")?;
+            for part in parts {
+                collected
+                    .add_geometry(part)
+                    .context("failed to add geometry")?;
+            }
+            collected
+        };
+        if let Some(report) = repair.as_deref_mut() {
+            report.area_before += geom.area();
+            report.area_after += geometry.area();
+        }
+
+        let properties = feature_properties(&feature);
+        let name = name_field
+            .and_then(|field| properties.get(field))
+            .and_then(|value| match value {
+                Value::Null => None,
+                Value::String(name) => Some(name.clone()),
+                value => Some(value.to_string()),
+            })
+            .filter(|name| !name.trim().is_empty());
+        features.push(SplitFeature {
+            index,
+            name,
+            geometry,
+            properties,
+        });
+    }
+
+    if features.is_empty() {
+        return Err(ProcessingError::NoFeaturesWithGeometry);
+    }
+    if let Some(report) = repair {
+        report.area_delta = report.area_after - report.area_before;
+    }
+    Ok(features)
+}
+
+/// A feature, or the layer as a whole when `index` is `None`, that would fail an upload.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FeatureProblem {
+    pub index: Option<usize>,
+    pub reason: String,
+}
+
+/// Check every feature of a layer against the expected geometry type and for validity,
+/// reporting all problems rather than stopping at the first like [`merge_layer_geometries`].
+pub fn validate_layer_geometries(
+    layer: &mut Layer,
+    expected_type: OGRwkbGeometryType::Type,
+) -> Result<Vec<FeatureProblem>, ProcessingError> {
+    let TypeInfo { single, multi } = compatible_types(expected_type)?;
+    let mut problems = Vec::new();
+    for (index, feature) in layer.features().enumerate() {
+        let Some(geom) = feature.geometry() else {
+            continue;
+        };
+        if geom.is_empty() {
+            continue;
+        }
+        let geom_type = geom.geometry_type();
+        let error = if flatten_type(geom_type) != single && flatten_type(geom_type) != multi {
+            ProcessingError::IncompatibleType {
+                index,
+                expected: geometry_type_to_name(expected_type),
+                found: geometry_type_to_name(geom_type),
+            }
+        } else if !geom.is_valid() {
+            ProcessingError::InvalidGeometry { index }
+        } else {
+            continue;
+        };
+        problems.push(FeatureProblem {
+            index: Some(index),
+            reason: error.to_string(),
+        });
+    }
+    Ok(problems)
+}
+
+/// Make a geometry valid and return its parts of type `single`, or `None` if nothing
+/// valid of that type is left.
+fn repair_geometry(geom: &Geometry, single: OGRwkbGeometryType::Type) -> Option<Vec<Geometry>> {
+    let mut candidates = Vec::new();
+    // The structure method keeps overlapping or self-intersecting areas as a union,
+    // where the default linework method would drop overlaps
+    let mut options = CslStringList::new();
+    if options.set_name_value("METHOD", "STRUCTURE").is_ok()
+        && let Ok(valid) = geom.make_valid(&options)
+    {
+        candidates.push(valid);
+    }
+    // buffer(0) only makes sense for areas, for other types it returns an empty polygon
+    if single == OGRwkbGeometryType::wkbPolygon
+        && let Ok(buffered) = geom.buffer(0.0, 8)
+    {
+        candidates.push(buffered);
+    }
+    candidates.into_iter().find_map(|candidate| {
+        let mut parts = Vec::new();
+        collect_parts(&candidate, single, &mut parts);
+        (!parts.is_empty() && parts.iter().all(Geometry::is_valid)).then_some(parts)
+    })
+}
+
+/// Collect the members of type `single` from a geometry and any nested collections.
+fn collect_parts(geom: &Geometry, single: OGRwkbGeometryType::Type, parts: &mut Vec<Geometry>) {
+    let geom_type = flatten_type(geom.geometry_type());
+    if geom_type == single {
+        if !geom.is_empty() {
+            parts.push(geom.clone());
+        }
+    } else if geom.geometry_count() > 0 && geom_type != OGRwkbGeometryType::wkbPolygon {
+        for i in 0..geom.geometry_count() {
+            collect_parts(&geom.get_geometry(i), single, parts);
+        }
+    }
+}
+
+/// Flag set on the 2.5D variant of a geometry type, e.g. `wkbPolygon25D`
+pub(crate) const WKB_25D_BIT: OGRwkbGeometryType::Type = 0x8000_0000;
+
+/// The 2D type of a geometry type, so `wkbPolygon25D`, `wkbPolygonZ`, `wkbPolygonM` and
+/// `wkbPolygonZM` are all `wkbPolygon`.
+pub fn flatten_type(ty: OGRwkbGeometryType::Type) -> OGRwkbGeometryType::Type {
+    (ty & !WKB_25D_BIT) % 1000
+}
+
+/// Whether a geometry type has Z values, either as a 2.5D or an ISO `Z`/`ZM` type
+pub fn has_z(ty: OGRwkbGeometryType::Type) -> bool {
+    ty & WKB_25D_BIT != 0 || matches!(ty / 1000, 1 | 3)
+}
+
+/// Whether a geometry type has M values, i.e. is an ISO `M` or `ZM` type
+pub fn has_m(ty: OGRwkbGeometryType::Type) -> bool {
+    ty & WKB_25D_BIT == 0 && matches!(ty / 1000, 2 | 3)
+}
+
+/// Drop the M values of a geometry, and its Z values too unless `keep_z` is set.
+///
+/// Merged and split geometries keep the dimensions of their input, so this is applied
+/// before they are stored, according to the collection's coordinate dimension.
+pub fn conform_dimension(geom: &Geometry, keep_z: bool) -> Result<Geometry, ProcessingError> {
+    let geom_type = geom.geometry_type();
+    if !has_m(geom_type) && (keep_z || !has_z(geom_type)) {
+        return Ok(geom.clone());
+    }
+    if keep_z && has_z(geom_type) {
+        return without_m(geom, false);
+    }
+    let mut flattened = geom.clone();
+    flattened.flatten_to_2d();
+    Ok(flattened)
+}
+
+/// Rebuild a ZM geometry from its XYZ coordinates; `ring` is set for the rings of a polygon.
+fn without_m(geom: &Geometry, ring: bool) -> Result<Geometry, ProcessingError> {
+    let geom_type = flatten_type(geom.geometry_type());
+    let rebuilt_type = if ring {
+        OGRwkbGeometryType::wkbLinearRing
+    } else {
+        geom_type
+    };
+    let mut rebuilt = Geometry::empty(rebuilt_type).context("failed to create geometry")?;
+    if geom.geometry_count() == 0 {
+        for point in geom.get_point_vec() {
+            rebuilt.add_point(point);
+        }
+        return Ok(rebuilt);
+    }
+    for i in 0..geom.geometry_count() {
+        let part = without_m(
+            &geom.get_geometry(i),
+            geom_type == OGRwkbGeometryType::wkbPolygon,
+        )?;
+        rebuilt
+            .add_geometry(part)
+            .context("failed to add geometry part")?;
+    }
+    Ok(rebuilt)
+}
+
 struct TypeInfo {
     single: OGRwkbGeometryType::Type,
     multi: OGRwkbGeometryType::Type,
 }
 
 fn compatible_types(expected_type: OGRwkbGeometryType::Type) -> Result<TypeInfo, ProcessingError> {
-    match expected_type {
+    match flatten_type(expected_type) {
         OGRwkbGeometryType::wkbPoint | OGRwkbGeometryType::wkbMultiPoint => Ok(TypeInfo {
             single: OGRwkbGeometryType::wkbPoint,
             multi: OGRwkbGeometryType::wkbMultiPoint,
@@ -141,6 +511,79 @@ mod tests {
         (dataset, filename)
     }
 
+    fn create_test_gpkg() -> (gdal::Dataset, String) {
+        let filename = format!("/vsimem/{}.gpkg", uuid::Uuid::new_v4());
+        let dataset = gdal::DriverManager::get_driver_by_name("GPKG")
+            .expect("failed to get geopackage driver")
+            .create_vector_only(&filename)
+            .expect("failed to create dataset");
+        (dataset, filename)
+    }
+
+    #[test]
+    fn select_layer_picks_named_layer() {
+        let (mut dataset, _) = create_test_gpkg();
+        for name in ["boundary", "access"] {
+            dataset
+                .create_layer(LayerOptions {
+                    name,
+                    ty: OGRwkbGeometryType::wkbPolygon,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let layer = select_layer(&dataset, Some("access")).unwrap();
+        assert_eq!(layer.name(), "access");
+    }
+
+    #[test]
+    fn select_layer_lists_layers_when_ambiguous() {
+        let (mut dataset, _) = create_test_gpkg();
+        for name in ["boundary", "access"] {
+            dataset
+                .create_layer(LayerOptions {
+                    name,
+                    ty: OGRwkbGeometryType::wkbPolygon,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        match select_layer(&dataset, None) {
+            Err(ProcessingError::MultipleLayers(layers)) => {
+                assert_eq!(layers, vec!["boundary", "access"])
+            }
+            _ => panic!("expected multiple layers error"),
+        }
+        assert!(matches!(
+            select_layer(&dataset, Some("missing")),
+            Err(ProcessingError::LayerNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn select_layer_for_type_picks_matching_layer() {
+        let (mut dataset, _) = create_test_gpkg();
+        for (name, ty) in [
+            ("boundary", OGRwkbGeometryType::wkbPolygon),
+            ("turbines", OGRwkbGeometryType::wkbPoint),
+        ] {
+            dataset
+                .create_layer(LayerOptions {
+                    name,
+                    ty,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let layer =
+            select_layer_for_type(&dataset, None, OGRwkbGeometryType::wkbMultiPoint).unwrap();
+        assert_eq!(layer.name(), "turbines");
+        assert!(matches!(
+            select_layer_for_type(&dataset, None, OGRwkbGeometryType::wkbLineString),
+            Err(ProcessingError::MultipleLayers(_))
+        ));
+    }
+
     #[test]
     fn compatible_types_returns_correct_types_for_multipoint() {
         let info = compatible_types(OGRwkbGeometryType::wkbMultiPoint).unwrap();
@@ -322,4 +765,294 @@ mod tests {
         let result = merge_geometries(&dataset, OGRwkbGeometryType::wkbPoint).unwrap();
         assert_eq!(result.geometry_type(), OGRwkbGeometryType::wkbPoint);
     }
+
+    const BOWTIE: &str = "POLYGON((0 0, 10 10, 10 0, 0 10, 0 0))";
+
+    #[test]
+    fn rejects_invalid_geometry_without_repair() {
+        let (dataset, _) = create_test_dataset(OGRwkbGeometryType::wkbPolygon, 27700);
+        {
+            let mut layer = dataset.layer(0).unwrap();
+            layer
+                .create_feature(Geometry::from_wkt(BOWTIE).unwrap())
+                .unwrap();
+        }
+
+        let result = merge_geometries(&dataset, OGRwkbGeometryType::wkbMultiPolygon);
+        assert!(matches!(
+            result,
+            Err(ProcessingError::InvalidGeometry { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_geometry_of_the_wrong_type_as_incompatible() {
+        let (dataset, _) = create_test_dataset(OGRwkbGeometryType::wkbLineString, 27700);
+        {
+            let mut layer = dataset.layer(0).unwrap();
+            layer
+                // Invalid, as a line needs two distinct points
+                .create_feature(Geometry::from_wkt("LINESTRING(1 1, 1 1)").unwrap())
+                .unwrap();
+        }
+
+        let mut layer = dataset.layer(0).unwrap();
+        let result = merge_layer_geometries(&mut layer, OGRwkbGeometryType::wkbMultiPolygon);
+        assert!(matches!(
+            result,
+            Err(ProcessingError::IncompatibleType { index: 0, .. })
+        ));
+        let result = repair_layer_geometries(&mut layer, OGRwkbGeometryType::wkbMultiPolygon);
+        assert!(matches!(
+            result,
+            Err(ProcessingError::IncompatibleType { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn repairs_self_intersecting_polygon() {
+        let (dataset, _) = create_test_dataset(OGRwkbGeometryType::wkbPolygon, 27700);
+        {
+            let mut layer = dataset.layer(0).unwrap();
+            layer
+                .create_feature(
+                    Geometry::from_wkt("POLYGON((20 0, 21 0, 21 1, 20 1, 20 0))").unwrap(),
+                )
+                .unwrap();
+            layer
+                .create_feature(Geometry::from_wkt(BOWTIE).unwrap())
+                .unwrap();
+        }
+
+        let mut layer = dataset.layer(0).unwrap();
+        let (result, report) =
+            repair_layer_geometries(&mut layer, OGRwkbGeometryType::wkbMultiPolygon).unwrap();
+        assert!(result.is_valid());
+        assert_eq!(result.geometry_type(), OGRwkbGeometryType::wkbMultiPolygon);
+        // The bow tie is split into two triangles
+        assert_eq!(result.geometry_count(), 3);
+        assert_eq!(report.repaired_features, vec![1]);
+        assert!(!report.merged_repaired);
+        assert!((report.area_after - 51.0).abs() < 1e-9);
+        assert!((report.area_delta - (report.area_after - report.area_before)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn repair_dissolves_overlapping_parts() {
+        let (dataset, _) = create_test_dataset(OGRwkbGeometryType::wkbPolygon, 27700);
+        {
+            let mut layer = dataset.layer(0).unwrap();
+            layer
+                .create_feature(Geometry::from_wkt("POLYGON((0 0, 2 0, 2 2, 0 2, 0 0))").unwrap())
+                .unwrap();
+            layer
+                .create_feature(Geometry::from_wkt("POLYGON((1 1, 3 1, 3 3, 1 3, 1 1))").unwrap())
+                .unwrap();
+        }
+
+        let mut layer = dataset.layer(0).unwrap();
+        let (result, report) =
+            repair_layer_geometries(&mut layer, OGRwkbGeometryType::wkbMultiPolygon).unwrap();
+        assert!(result.is_valid());
+        assert!(report.repaired_features.is_empty());
+        assert!(report.merged_repaired);
+        assert!((report.area_before - 8.0).abs() < 1e-9);
+        assert!((report.area_after - 7.0).abs() < 1e-9);
+        assert!((report.area_delta + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn validation_reports_every_problem_feature() {
+        // Shapefiles can't mix geometry types, so use a geopackage layer
+        let (mut dataset, _) = create_test_gpkg();
+        {
+            let mut layer = dataset
+                .create_layer(LayerOptions {
+                    name: "mixed",
+                    ty: OGRwkbGeometryType::wkbUnknown,
+                    ..Default::default()
+                })
+                .unwrap();
+            for wkt in [
+                BOWTIE,
+                "POLYGON((20 0, 21 0, 21 1, 20 1, 20 0))",
+                "POINT(1 1)",
+                BOWTIE,
+            ] {
+                layer
+                    .create_feature(Geometry::from_wkt(wkt).unwrap())
+                    .unwrap();
+            }
+        }
+
+        let mut layer = dataset.layer(0).unwrap();
+        let problems =
+            validate_layer_geometries(&mut layer, OGRwkbGeometryType::wkbMultiPolygon).unwrap();
+        let indices: Vec<_> = problems.iter().map(|p| p.index).collect();
+        assert_eq!(indices, vec![Some(0), Some(2), Some(3)]);
+        assert!(problems[1].reason.contains("incompatible geometry type"));
+    }
+
+    fn create_named_points(names: &[&str]) -> gdal::Dataset {
+        let (dataset, _) = create_test_dataset(OGRwkbGeometryType::wkbPoint, 27700);
+        {
+            let mut layer = dataset.layer(0).unwrap();
+            layer
+                .create_defn_fields(&[("site", gdal::vector::OGRFieldType::OFTString)])
+                .unwrap();
+            for (index, name) in names.iter().enumerate() {
+                layer
+                    .create_feature_fields(
+                        Geometry::from_wkt(&format!("POINT({index} {index})")).unwrap(),
+                        &["site"],
+                        &[gdal::vector::FieldValue::StringValue(name.to_string())],
+                    )
+                    .unwrap();
+            }
+        }
+        dataset
+    }
+
+    #[test]
+    fn split_returns_one_feature_per_row() {
+        let dataset = create_named_points(&["north", "", "south"]);
+        let mut layer = dataset.layer(0).unwrap();
+        let features = split_layer_geometries(
+            &mut layer,
+            OGRwkbGeometryType::wkbMultiPoint,
+            Some("site"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(features.len(), 3);
+        let names: Vec<_> = features.iter().map(|f| f.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("north"), None, Some("south")]);
+        for feature in &features {
+            assert_eq!(
+                feature.geometry.geometry_type(),
+                OGRwkbGeometryType::wkbMultiPoint
+            );
+            assert!(feature.properties.contains_key("site"));
+        }
+    }
+
+    #[test]
+    fn split_rejects_unknown_name_field() {
+        let dataset = create_named_points(&["north"]);
+        let mut layer = dataset.layer(0).unwrap();
+        let result = split_layer_geometries(
+            &mut layer,
+            OGRwkbGeometryType::wkbPoint,
+            Some("missing"),
+            None,
+        );
+        assert!(matches!(result, Err(ProcessingError::FieldNotFound(field)) if field == "missing"));
+    }
+
+    #[test]
+    fn split_repairs_each_feature() {
+        let (dataset, _) = create_test_dataset(OGRwkbGeometryType::wkbPolygon, 27700);
+        {
+            let mut layer = dataset.layer(0).unwrap();
+            for wkt in [BOWTIE, "POLYGON((20 0, 21 0, 21 1, 20 1, 20 0))"] {
+                layer
+                    .create_feature(Geometry::from_wkt(wkt).unwrap())
+                    .unwrap();
+            }
+        }
+        let mut layer = dataset.layer(0).unwrap();
+        let mut report = RepairReport::default();
+        let features = split_layer_geometries(
+            &mut layer,
+            OGRwkbGeometryType::wkbMultiPolygon,
+            None,
+            Some(&mut report),
+        )
+        .unwrap();
+        assert_eq!(features.len(), 2);
+        assert!(features.iter().all(|f| f.geometry.is_valid()));
+        assert_eq!(report.repaired_features, vec![0]);
+    }
+
+    #[test]
+    fn flatten_type_strips_z_and_m_variants() {
+        for ty in [
+            OGRwkbGeometryType::wkbPolygon25D,
+            OGRwkbGeometryType::wkbPolygonZM,
+            OGRwkbGeometryType::wkbPolygonM,
+        ] {
+            assert_eq!(flatten_type(ty), OGRwkbGeometryType::wkbPolygon);
+        }
+        assert!(has_z(OGRwkbGeometryType::wkbPoint25D));
+        assert!(has_z(OGRwkbGeometryType::wkbPointZM));
+        assert!(!has_z(OGRwkbGeometryType::wkbPointM));
+        assert!(has_m(OGRwkbGeometryType::wkbPointZM));
+        assert!(!has_m(OGRwkbGeometryType::wkbPoint25D));
+    }
+
+    #[test]
+    fn merges_polygon_z_shapefile_keeping_or_dropping_z() {
+        let (dataset, _) = create_test_dataset(OGRwkbGeometryType::wkbPolygon25D, 27700);
+        {
+            let mut layer = dataset.layer(0).unwrap();
+            let polygon =
+                Geometry::from_wkt("POLYGON Z ((0 0 10, 1 0 11, 1 1 12, 0 1 11, 0 0 10))").unwrap();
+            layer.create_feature(polygon).unwrap();
+        }
+
+        let merged = merge_geometries(&dataset, OGRwkbGeometryType::wkbMultiPolygon).unwrap();
+        assert_eq!(
+            flatten_type(merged.geometry_type()),
+            OGRwkbGeometryType::wkbMultiPolygon
+        );
+
+        let kept = conform_dimension(&merged, true).unwrap();
+        assert_eq!(kept.geometry_type(), OGRwkbGeometryType::wkbMultiPolygon25D);
+        let ring = kept.get_geometry(0).get_geometry(0).clone();
+        assert_eq!(ring.get_point(2), (1.0, 1.0, 12.0));
+
+        let dropped = conform_dimension(&merged, false).unwrap();
+        assert_eq!(dropped.geometry_type(), OGRwkbGeometryType::wkbMultiPolygon);
+    }
+
+    #[test]
+    fn merges_point_zm_shapefile_dropping_m() {
+        let (dataset, _) = create_test_dataset(OGRwkbGeometryType::wkbPointZM, 27700);
+        {
+            let mut layer = dataset.layer(0).unwrap();
+            for wkt in ["POINT ZM (1 2 3 4)", "POINT ZM (5 6 7 8)"] {
+                layer
+                    .create_feature(Geometry::from_wkt(wkt).unwrap())
+                    .unwrap();
+            }
+        }
+
+        let merged = merge_geometries(&dataset, OGRwkbGeometryType::wkbMultiPoint).unwrap();
+        assert_eq!(merged.geometry_count(), 2);
+        assert!(has_m(merged.geometry_type()));
+
+        let kept = conform_dimension(&merged, true).unwrap();
+        assert_eq!(kept.geometry_type(), OGRwkbGeometryType::wkbMultiPoint25D);
+        assert_eq!(kept.get_geometry(1).get_point(0), (5.0, 6.0, 7.0));
+
+        let dropped = conform_dimension(&merged, false).unwrap();
+        assert_eq!(dropped.geometry_type(), OGRwkbGeometryType::wkbMultiPoint);
+        assert_eq!(dropped.get_geometry(0).get_point(0), (1.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn split_accepts_z_variants_of_expected_type() {
+        let (dataset, _) = create_test_dataset(OGRwkbGeometryType::wkbPointZM, 27700);
+        {
+            let mut layer = dataset.layer(0).unwrap();
+            layer
+                .create_feature(Geometry::from_wkt("POINT ZM (1 2 3 4)").unwrap())
+                .unwrap();
+        }
+        let mut layer = dataset.layer(0).unwrap();
+        let features =
+            split_layer_geometries(&mut layer, OGRwkbGeometryType::wkbPoint, None, None).unwrap();
+        assert_eq!(features.len(), 1);
+        assert!(has_z(features[0].geometry.geometry_type()));
+    }
 }
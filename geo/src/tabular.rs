@@ -0,0 +1,301 @@
+use anyhow::{Context, anyhow};
+use gdal::{
+    Dataset, DatasetOptions,
+    vector::{Geometry, LayerAccess, LayerOptions, OGRwkbGeometryType},
+};
+
+use crate::{shapefile_processor::select_layer, virtual_shapefile::ShapefileError};
+
+const LAYER_NAME: &str = "points";
+/// How many bad rows are listed in an error message
+const REPORTED_ROWS: usize = 10;
+
+/// The columns of a table holding point coordinates, e.g. `Easting`/`Northing` or `Lon`/`Lat`.
+#[derive(Debug, Clone)]
+pub struct PointColumns {
+    pub x_field: String,
+    pub y_field: String,
+}
+
+/// A row whose coordinates are missing or can't be parsed as numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoordinateProblem {
+    /// Spreadsheet row number, counting the header as row 1
+    pub row: usize,
+    pub x: Option<String>,
+    pub y: Option<String>,
+}
+
+impl std::fmt::Display for CoordinateProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "row {} (x: '{}', y: '{}')",
+            self.row,
+            self.x.as_deref().unwrap_or_default(),
+            self.y.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+/// List the first few problems of a failed import
+pub(crate) fn summarise_problems(problems: &[CoordinateProblem]) -> String {
+    let mut summary = problems
+        .iter()
+        .take(REPORTED_ROWS)
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    if problems.len() > REPORTED_ROWS {
+        summary.push_str(&format!(" and {} more", problems.len() - REPORTED_ROWS));
+    }
+    summary
+}
+
+/// Open a `.csv` file of points as a single point layer without a spatial reference.
+///
+/// GDAL builds the geometries from the coordinate columns, which are then dropped so every
+/// other column ends up in the feature's attributes. Column types are detected from the
+/// whole file. Any row with missing or unparseable coordinates fails the import with
+/// [`ShapefileError::InvalidCoordinates`] rather than being skipped.
+pub fn open_csv(path: &str, columns: &PointColumns) -> Result<Dataset, ShapefileError> {
+    let open_options = [
+        format!("X_POSSIBLE_NAMES={}", columns.x_field),
+        format!("Y_POSSIBLE_NAMES={}", columns.y_field),
+        "KEEP_GEOM_COLUMNS=YES".to_string(),
+        "AUTODETECT_TYPE=YES".to_string(),
+        // Scan the whole file, so a bad coordinate always makes its column text
+        "AUTODETECT_SIZE_LIMIT=0".to_string(),
+    ];
+    let open_options: Vec<&str> = open_options.iter().map(String::as_str).collect();
+    let source = Dataset::open_ex(
+        path,
+        DatasetOptions {
+            allowed_drivers: Some(&["CSV"]),
+            open_options: Some(&open_options),
+            ..Default::default()
+        },
+    )
+    .context("failed to open csv file")
+    .map_err(ShapefileError::InvalidData)?;
+    table_points(&source, None, columns)
+}
+
+/// Open a sheet of an `.xlsx` workbook of points, see [`open_csv`].
+///
+/// The first row of the sheet must hold the column names. `sheet` is required when the
+/// workbook has more than one sheet.
+pub fn open_xlsx(
+    path: &str,
+    sheet: Option<&str>,
+    columns: &PointColumns,
+) -> Result<Dataset, ShapefileError> {
+    let source = Dataset::open_ex(
+        path,
+        DatasetOptions {
+            allowed_drivers: Some(&["XLSX"]),
+            open_options: Some(&["HEADERS=FORCE", "FIELD_TYPES=AUTO"]),
+            ..Default::default()
+        },
+    )
+    .context("failed to open xlsx file")
+    .map_err(ShapefileError::InvalidData)?;
+    table_points(&source, sheet, columns)
+}
+
+/// Copy every row of a table into an in-memory point layer, without the coordinate columns.
+fn table_points(
+    source: &Dataset,
+    sheet: Option<&str>,
+    columns: &PointColumns,
+) -> Result<Dataset, ShapefileError> {
+    let mut source_layer =
+        select_layer(source, sheet).map_err(|e| ShapefileError::InvalidData(e.into()))?;
+    let fields: Vec<_> = source_layer
+        .defn()
+        .fields()
+        .map(|field| (field.name(), field.field_type()))
+        .collect();
+    // GDAL matches column names case-insensitively, so do the same
+    let find_column = |name: &str| {
+        fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .or_else(|| {
+                fields
+                    .iter()
+                    .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            })
+            .map(|(field, _)| field.clone())
+            .ok_or_else(|| ShapefileError::IncorrectFiles(format!("no '{name}' column in table")))
+    };
+    let x_field = find_column(&columns.x_field)?;
+    let y_field = find_column(&columns.y_field)?;
+    let attribute_fields: Vec<_> = fields
+        .iter()
+        .filter(|(field, _)| *field != x_field && *field != y_field)
+        .cloned()
+        .collect();
+
+    let mut target = gdal::DriverManager::get_driver_by_name("Memory")
+        .context("failed to get memory driver")
+        .map_err(ShapefileError::UnexpectedError)?
+        .create_vector_only("")
+        .context("failed to create memory dataset")
+        .map_err(ShapefileError::UnexpectedError)?;
+    let mut target_layer = target
+        .create_layer(LayerOptions {
+            name: LAYER_NAME,
+            srs: None,
+            ty: OGRwkbGeometryType::wkbPoint,
+            options: None,
+        })
+        .context("failed to create points layer")
+        .map_err(ShapefileError::UnexpectedError)?;
+    let field_defs: Vec<_> = attribute_fields
+        .iter()
+        .map(|(field, field_type)| (field.as_str(), *field_type))
+        .collect();
+    target_layer
+        .create_defn_fields(&field_defs)
+        .context("failed to create point fields")
+        .map_err(ShapefileError::UnexpectedError)?;
+
+    let mut problems = Vec::new();
+    for (index, feature) in source_layer.features().enumerate() {
+        let raw = |field: &str| {
+            feature
+                .field_as_string_by_name(field)
+                .ok()
+                .flatten()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let (x, y) = (raw(&x_field), raw(&y_field));
+        let coordinates = x
+            .as_deref()
+            .and_then(|x| x.parse::<f64>().ok())
+            .zip(y.as_deref().and_then(|y| y.parse::<f64>().ok()))
+            .filter(|(x, y)| x.is_finite() && y.is_finite());
+        let Some((x_value, y_value)) = coordinates else {
+            problems.push(CoordinateProblem {
+                row: index + 2,
+                x,
+                y,
+            });
+            continue;
+        };
+        if !problems.is_empty() {
+            // The import will fail, keep looking for bad rows without copying
+            continue;
+        }
+        let geometry = match feature.geometry() {
+            Some(geometry) => geometry.clone(),
+            None => {
+                let mut point = Geometry::empty(OGRwkbGeometryType::wkbPoint)
+                    .context("failed to create point")
+                    .map_err(ShapefileError::UnexpectedError)?;
+                point.add_point_2d((x_value, y_value));
+                point
+            }
+        };
+        let (names, values): (Vec<_>, Vec<_>) = feature
+            .fields()
+            .filter(|(field, _)| *field != x_field && *field != y_field)
+            .filter_map(|(field, value)| value.map(|value| (field, value)))
+            .unzip();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        target_layer
+            .create_feature_fields(geometry, &names, &values)
+            .context("failed to copy row")
+            .map_err(ShapefileError::UnexpectedError)?;
+    }
+    drop(target_layer);
+
+    if !problems.is_empty() {
+        return Err(ShapefileError::InvalidCoordinates(problems));
+    }
+    if target
+        .layer(0)
+        .is_ok_and(|layer| layer.feature_count() == 0)
+    {
+        return Err(ShapefileError::InvalidData(anyhow!(
+            "no rows found in table"
+        )));
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{attributes::merge_attributes, shapefile_processor::merge_geometries};
+    use gdal::vsi;
+    use serde_json::json;
+
+    fn open_test_csv(csv: &str, columns: &PointColumns) -> Result<Dataset, ShapefileError> {
+        let path = format!("/vsimem/{}.csv", uuid::Uuid::new_v4());
+        vsi::create_mem_file(&path, csv.as_bytes().to_vec()).unwrap();
+        let result = open_csv(&path, columns);
+        let _ = vsi::unlink_mem_file(&path);
+        result
+    }
+
+    fn easting_northing() -> PointColumns {
+        PointColumns {
+            x_field: "Easting".to_string(),
+            y_field: "Northing".to_string(),
+        }
+    }
+
+    #[test]
+    fn csv_rows_become_points_with_remaining_columns_as_attributes() {
+        let csv = "site,Easting,Northing,count\nA1,400000,100000,3\nA2,400100.5,100050,7\n";
+        let ds = open_test_csv(csv, &easting_northing()).expect("failed to read csv");
+        let merged = merge_geometries(&ds, OGRwkbGeometryType::wkbMultiPoint).unwrap();
+        assert_eq!(merged.geometry_count(), 2);
+        assert_eq!(
+            merged.get_geometry(1).get_point(0),
+            (400100.5, 100050.0, 0.0)
+        );
+        let properties =
+            merge_attributes(&ds, crate::attributes::AttributePolicy::Collect).unwrap();
+        assert_eq!(properties.get("site"), Some(&json!(["A1", "A2"])));
+        assert_eq!(properties.get("count"), Some(&json!([3, 7])));
+        assert!(!properties.contains_key("Easting"));
+    }
+
+    #[test]
+    fn column_names_match_case_insensitively() {
+        let csv = "lon,lat\n-3.1,52.4\n";
+        let columns = PointColumns {
+            x_field: "Lon".to_string(),
+            y_field: "LAT".to_string(),
+        };
+        let ds = open_test_csv(csv, &columns).expect("failed to read csv");
+        assert_eq!(ds.layer(0).unwrap().feature_count(), 1);
+    }
+
+    #[test]
+    fn unparseable_coordinates_are_reported() {
+        let csv = "site,Easting,Northing\nA1,400000,100000\nA2,4OO1OO,100050\nA3,,100100\n";
+        match open_test_csv(csv, &easting_northing()) {
+            Err(ShapefileError::InvalidCoordinates(problems)) => {
+                let rows: Vec<_> = problems.iter().map(|p| p.row).collect();
+                assert_eq!(rows, vec![3, 4]);
+                assert_eq!(problems[0].x.as_deref(), Some("4OO1OO"));
+                assert_eq!(problems[1].x, None);
+            }
+            _ => panic!("expected InvalidCoordinates error"),
+        }
+    }
+
+    #[test]
+    fn missing_column_is_rejected() {
+        let csv = "site,x,y\nA1,1,2\n";
+        assert!(matches!(
+            open_test_csv(csv, &easting_northing()),
+            Err(ShapefileError::IncorrectFiles(_))
+        ));
+    }
+}
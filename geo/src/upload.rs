@@ -0,0 +1,147 @@
+use actix_multipart::form::tempfile::TempFile;
+use anyhow::Context;
+use gdal::{Dataset, DatasetOptions, vector::LayerAccess};
+use std::{
+    fs::File,
+    io::{self, Seek},
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+use crate::virtual_shapefile::ShapefileError;
+
+/// A private temporary directory that uploaded files are moved into, so GDAL can open
+/// them straight from disk under names its drivers recognise.
+///
+/// Files are renamed rather than read, so an upload is never held in memory. The
+/// directory and everything in it is removed on drop.
+pub struct UploadDir {
+    dir: TempDir,
+}
+
+impl UploadDir {
+    pub fn new() -> Result<Self, ShapefileError> {
+        // actix-multipart writes temp files to the system temp dir, so keeping ours
+        // there too means files can be renamed rather than copied
+        let dir = tempfile::Builder::new()
+            .prefix("geoman-upload-")
+            .tempdir()
+            .context("failed to create upload directory")
+            .map_err(ShapefileError::UnexpectedError)?;
+        Ok(Self { dir })
+    }
+
+    /// Move an uploaded file into the directory as `upload.{extension}`.
+    pub fn add(&self, file: TempFile, extension: &str) -> Result<PathBuf, ShapefileError> {
+        let path = self.dir.path().join(format!("upload.{extension}"));
+        if let Err(e) = file.file.persist(&path) {
+            // Renaming fails across filesystems, fall back to a streamed copy
+            let mut source = e.file;
+            source
+                .rewind()
+                .context(format!("failed to read {extension} file"))
+                .map_err(ShapefileError::UnexpectedError)?;
+            let mut target = File::create(&path)
+                .context(format!("failed to create {extension} file"))
+                .map_err(ShapefileError::UnexpectedError)?;
+            io::copy(&mut source, &mut target)
+                .context(format!("failed to copy {extension} file"))
+                .map_err(ShapefileError::UnexpectedError)?;
+        }
+        Ok(path)
+    }
+
+    /// Open a file in the directory with GDAL. The directory is kept alive for as long
+    /// as the returned dataset.
+    pub fn open(
+        self,
+        path: &Path,
+        open_options: &[String],
+    ) -> Result<UploadedDataset, ShapefileError> {
+        let open_options: Vec<&str> = open_options.iter().map(String::as_str).collect();
+        let dataset = Dataset::open_ex(
+            path,
+            DatasetOptions {
+                open_options: Some(&open_options),
+                ..Default::default()
+            },
+        )
+        .context("failed to open uploaded dataset")
+        .map_err(ShapefileError::InvalidData)?;
+        if dataset.layer_count() == 0 {
+            return Err(ShapefileError::InvalidData(anyhow::anyhow!(
+                "no layers in uploaded file"
+            )));
+        }
+        Ok(UploadedDataset::new(dataset, Some(self)))
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+/// A dataset together with the upload directory it was opened from, if any.
+pub struct UploadedDataset {
+    // Declared first so GDAL closes its files before the directory is removed
+    dataset: Dataset,
+    _dir: Option<UploadDir>,
+}
+
+impl UploadedDataset {
+    pub fn new(dataset: Dataset, dir: Option<UploadDir>) -> Self {
+        Self { dataset, _dir: dir }
+    }
+}
+
+impl From<Dataset> for UploadedDataset {
+    fn from(dataset: Dataset) -> Self {
+        Self::new(dataset, None)
+    }
+}
+
+impl Deref for UploadedDataset {
+    type Target = Dataset;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dataset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_file(data: &[u8]) -> TempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(data).unwrap();
+        TempFile {
+            file,
+            content_type: None,
+            file_name: None,
+            size: data.len(),
+        }
+    }
+
+    #[test]
+    fn shapefile_components_open_from_disk() {
+        let dir = UploadDir::new().unwrap();
+        let fixture = "../test-data/shapefiles/3_valid_polygon_osgb36";
+        let mut shp = None;
+        for extension in ["shp", "shx", "dbf", "prj"] {
+            let data = std::fs::read(format!("{fixture}.{extension}")).unwrap();
+            let path = dir.add(temp_file(&data), extension).unwrap();
+            if extension == "shp" {
+                shp = Some(path);
+            }
+        }
+        let root = dir.path().to_path_buf();
+        let dataset = dir.open(&shp.unwrap(), &[]).unwrap();
+        assert_eq!(dataset.layers().next().unwrap().feature_count(), 3);
+        assert!(root.exists());
+        drop(dataset);
+        assert!(!root.exists());
+    }
+}
@@ -3,6 +3,7 @@
 use actix_multipart::form::{MultipartForm, tempfile::TempFile};
 use anyhow::{Context, anyhow};
 use gdal::{
+    DatasetOptions,
     vector::LayerAccess,
     vsi::{self, get_vsi_mem_file_bytes_owned},
 };
@@ -15,6 +16,8 @@ use uuid::Uuid;
 
 use utils::error_chain_fmt;
 
+use crate::tabular::{CoordinateProblem, summarise_problems};
+
 #[derive(thiserror::Error)]
 pub enum ShapefileError {
     #[error(transparent)]
@@ -23,6 +26,21 @@ pub enum ShapefileError {
     IncorrectFiles(String),
     #[error(transparent)]
     InvalidData(anyhow::Error),
+    #[error("archive contains multiple shapefiles, select one of: {}", .0.join(", "))]
+    MultipleShapefiles(Vec<String>),
+    #[error("unsupported encoding '{0}'")]
+    UnsupportedEncoding(String),
+    #[error("CAD layers not found: {}, select from: {}", .missing.join(", "), .available.join(", "))]
+    CadLayersNotFound {
+        missing: Vec<String>,
+        available: Vec<String>,
+    },
+    #[error(
+        "file CRS is EPSG:{detected} but srid {srid} was given, set force_srid=true to override it"
+    )]
+    CrsMismatch { detected: i32, srid: i32 },
+    #[error("{} rows have missing or unparseable coordinates: {}", .0.len(), summarise_problems(.0))]
+    InvalidCoordinates(Vec<CoordinateProblem>),
 }
 
 #[derive(MultipartForm)]
@@ -30,7 +48,9 @@ pub struct ShapefileForm {
     pub shp: TempFile,
     pub dbf: TempFile,
     pub shx: TempFile,
-    pub prj: TempFile,
+    /// Optional when the upload gives an SRID
+    pub prj: Option<TempFile>,
+    pub cpg: Option<TempFile>,
 }
 
 #[derive(Debug)]
@@ -39,6 +59,7 @@ pub struct VirtualShapefile {
     _dbf: VirtualFile,
     _shx: VirtualFile,
     _prj: VirtualFile,
+    _cpg: Option<VirtualFile>,
 }
 
 impl VirtualShapefile {
@@ -48,6 +69,10 @@ impl VirtualShapefile {
             _dbf: VirtualFile::new(&format!("{filename}.dbf"), data.dbf)?,
             _shx: VirtualFile::new(&format!("{filename}.shx"), data.shx)?,
             _prj: VirtualFile::new(&format!("{filename}.prj"), data.prj)?,
+            _cpg: data
+                .cpg
+                .map(|cpg| VirtualFile::new(&format!("{filename}.cpg"), cpg))
+                .transpose()?,
         })
     }
     pub fn path(&self) -> &String {
@@ -59,17 +84,7 @@ impl TryInto<gdal::Dataset> for ShapefileData {
     type Error = anyhow::Error;
 
     fn try_into(self) -> Result<gdal::Dataset, Self::Error> {
-        let file_id = Uuid::new_v4();
-        let _virtual_shapefile = VirtualShapefile::new(file_id.to_string(), self)
-            .context("failed to create virtual shapefile")?;
-
-        let ds = gdal::Dataset::open(format!("/vsimem/{}.shp", file_id))
-            .context("failed to open shapefile")
-            .context("failed to open data")?;
-        let layer = ds.layers().next().context("no layers on shapefile")?;
-        // Gotta leave this line in or things break! :/
-        let _srs = layer.spatial_ref().context("no spatial ref on layer!!!")?;
-        Ok(ds)
+        Ok(self.open(None)?)
     }
 }
 
@@ -78,6 +93,65 @@ pub struct ShapefileData {
     pub dbf: Vec<u8>,
     pub shx: Vec<u8>,
     pub prj: Vec<u8>,
+    /// Code page of the DBF, e.g. `UTF-8` or `1252`
+    pub cpg: Option<Vec<u8>>,
+}
+
+impl ShapefileData {
+    /// Open the shapefile, decoding the DBF with `encoding` when given instead of
+    /// the code page from the `.cpg` file or DBF header.
+    pub fn open(self, encoding: Option<&str>) -> Result<gdal::Dataset, ShapefileError> {
+        let file_id = Uuid::new_v4();
+        let _virtual_shapefile = VirtualShapefile::new(file_id.to_string(), self)
+            .context("failed to create virtual shapefile")
+            .map_err(ShapefileError::UnexpectedError)?;
+
+        let ds = open_shapefile(&format!("/vsimem/{}.shp", file_id), encoding)?;
+        let layer = ds
+            .layers()
+            .next()
+            .context("no layers on shapefile")
+            .map_err(ShapefileError::InvalidData)?;
+        // Gotta leave this line in or things break! :/
+        let _srs = layer
+            .spatial_ref()
+            .context("no spatial ref on layer!!!")
+            .map_err(ShapefileError::InvalidData)?;
+        Ok(ds)
+    }
+}
+
+/// Open a shapefile, overriding the DBF encoding when `encoding` is given.
+pub fn open_shapefile(path: &str, encoding: Option<&str>) -> Result<gdal::Dataset, ShapefileError> {
+    let options = encoding_open_options(encoding)?;
+    let options: Vec<&str> = options.iter().map(String::as_str).collect();
+    gdal::Dataset::open_ex(
+        path,
+        DatasetOptions {
+            open_options: Some(&options),
+            ..Default::default()
+        },
+    )
+    .context("failed to open shapefile")
+    .map_err(ShapefileError::InvalidData)
+}
+
+/// Shapefile driver open options for an explicit DBF encoding, e.g. `ENCODING=CP1252`.
+/// Encodings are passed to iconv, so only names made of alphanumerics, `-`, `_`, `.`
+/// and `:` are accepted.
+pub fn encoding_open_options(encoding: Option<&str>) -> Result<Vec<String>, ShapefileError> {
+    match encoding.map(str::trim) {
+        None => Ok(Vec::new()),
+        Some(encoding)
+            if !encoding.is_empty()
+                && encoding
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':')) =>
+        {
+            Ok(vec![format!("ENCODING={encoding}")])
+        }
+        Some(encoding) => Err(ShapefileError::UnsupportedEncoding(encoding.to_string())),
+    }
 }
 
 impl std::fmt::Debug for ShapefileError {
@@ -98,6 +172,8 @@ impl ShapefileData {
         dbf.set_extension("dbf");
         let mut prj = PathBuf::from(path.as_ref());
         prj.set_extension("prj");
+        let mut cpg = PathBuf::from(path.as_ref());
+        cpg.set_extension("cpg");
 
         Ok(ShapefileData {
             shp: get_vsi_mem_file_bytes_owned(shp)
@@ -108,6 +184,7 @@ impl ShapefileData {
                 .context("failed to read im mem file to bytes")?,
             prj: get_vsi_mem_file_bytes_owned(prj)
                 .context("failed to read im mem file to bytes")?,
+            cpg: get_vsi_mem_file_bytes_owned(cpg).ok(),
         })
     }
 }
@@ -129,6 +206,7 @@ impl TryFrom<HashMap<String, Vec<u8>>> for ShapefileData {
             prj: data.remove("prj").ok_or(ShapefileError::IncorrectFiles(
                 "missing .prj file".to_string(),
             ))?,
+            cpg: data.remove("cpg"),
         })
     }
 }
@@ -139,6 +217,7 @@ impl ShapefileData {
         mut dbf: TempFile,
         mut shx: TempFile,
         mut prj: TempFile,
+        cpg: Option<TempFile>,
     ) -> Result<Self, anyhow::Error> {
         let mut shp_data = Vec::new();
         let mut dbf_data = Vec::new();
@@ -156,11 +235,21 @@ impl ShapefileData {
         shp.file
             .read_to_end(&mut shp_data)
             .context("failed to read shp")?;
+        let cpg_data = cpg
+            .map(|mut cpg| {
+                let mut cpg_data = Vec::new();
+                cpg.file
+                    .read_to_end(&mut cpg_data)
+                    .context("failed to read cpg")
+                    .map(|_| cpg_data)
+            })
+            .transpose()?;
         Ok(ShapefileData {
             shp: shp_data,
             dbf: dbf_data,
             shx: shx_data,
             prj: prj_data,
+            cpg: cpg_data,
         })
     }
 }
@@ -171,7 +260,6 @@ impl TryFrom<ShapefileForm> for ShapefileData {
         let mut shp = Vec::new();
         let mut dbf = Vec::new();
         let mut shx = Vec::new();
-        let mut prj = Vec::new();
         form.dbf
             .file
             .read_to_end(&mut dbf)
@@ -180,15 +268,38 @@ impl TryFrom<ShapefileForm> for ShapefileData {
             .file
             .read_to_end(&mut shx)
             .context("failed to read shx")?;
-        form.prj
-            .file
-            .read_to_end(&mut prj)
-            .context("failed to read prj")?;
+        let prj = form
+            .prj
+            .map(|mut file| {
+                let mut prj = Vec::new();
+                file.file
+                    .read_to_end(&mut prj)
+                    .context("failed to read prj")
+                    .map(|_| prj)
+            })
+            .transpose()?
+            .unwrap_or_default();
         form.shp
             .file
             .read_to_end(&mut shp)
             .context("failed to read shp")?;
-        Ok(ShapefileData { shp, dbf, shx, prj })
+        let cpg = form
+            .cpg
+            .map(|mut file| {
+                let mut cpg = Vec::new();
+                file.file
+                    .read_to_end(&mut cpg)
+                    .context("failed to read cpg")
+                    .map(|_| cpg)
+            })
+            .transpose()?;
+        Ok(ShapefileData {
+            shp,
+            dbf,
+            shx,
+            prj,
+            cpg,
+        })
     }
 }
 
@@ -219,6 +330,124 @@ impl Drop for VirtualFile {
     }
 }
 
+/// A zipped shapefile, opened through `/vsizip/` without extracting the archive.
+///
+/// The archive may hold the shapefile in nested folders and include sidecar files
+/// (`.cpg`, `.qix`, `.sbn`...), which GDAL picks up alongside the `.shp`.
+pub struct ZippedShapefile {
+    archive: String,
+    _file: Option<VirtualFile>,
+    candidates: Vec<PathBuf>,
+}
+
+impl ZippedShapefile {
+    pub fn new(data: Vec<u8>) -> Result<Self, ShapefileError> {
+        let file = VirtualFile::new(&format!("{}.zip", Uuid::new_v4()), data)?;
+        let mut zipped = Self::from_path(&file.0)?;
+        zipped._file = Some(file);
+        Ok(zipped)
+    }
+
+    /// Read a zip archive from disk (or any path GDAL can read) without loading it.
+    pub fn from_path(path: &str) -> Result<Self, ShapefileError> {
+        let archive = format!("/vsizip/{path}");
+        let entries = vsi::read_dir(&archive, true)
+            .context("failed to read zip archive")
+            .map_err(ShapefileError::InvalidData)?;
+        let candidates = entries
+            .into_iter()
+            .filter(|entry| {
+                // Skip resource forks added by macOS when zipping
+                !entry.starts_with("__MACOSX") && has_extension(entry, "shp")
+            })
+            .collect();
+        Ok(Self {
+            archive,
+            _file: None,
+            candidates,
+        })
+    }
+
+    /// Candidate shapefiles in the archive, as paths without the `.shp` extension
+    pub fn candidates(&self) -> Vec<String> {
+        self.candidates
+            .iter()
+            .map(|path| path.with_extension("").to_string_lossy().to_string())
+            .collect()
+    }
+
+    /// Open the shapefile named `layer` (either its path in the archive or its file name),
+    /// or the only shapefile in the archive when no layer is given.
+    pub fn open(
+        &self,
+        layer: Option<&str>,
+        encoding: Option<&str>,
+    ) -> Result<gdal::Dataset, ShapefileError> {
+        let shp = match layer {
+            Some(layer) => self
+                .candidates
+                .iter()
+                .find(|path| {
+                    path.with_extension("").as_os_str() == layer
+                        || path.file_stem().is_some_and(|stem| stem == layer)
+                })
+                .ok_or_else(|| {
+                    ShapefileError::IncorrectFiles(format!("no shapefile '{layer}' in archive"))
+                })?,
+            None => match self.candidates.as_slice() {
+                [] => {
+                    return Err(ShapefileError::IncorrectFiles(
+                        "no .shp file found in archive".to_string(),
+                    ));
+                }
+                [shp] => shp,
+                _ => return Err(ShapefileError::MultipleShapefiles(self.candidates())),
+            },
+        };
+        let archive = &self.archive;
+        let folder = match shp.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => {
+                format!("{archive}/{}", parent.display())
+            }
+            _ => archive.clone(),
+        };
+        let siblings = vsi::read_dir(folder, false)
+            .context("failed to read zip archive")
+            .map_err(ShapefileError::InvalidData)?;
+        let stem = shp.file_stem().unwrap_or_default();
+        let missing: Vec<&str> = ["shx", "dbf", "prj"]
+            .into_iter()
+            .filter(|ext| {
+                !siblings
+                    .iter()
+                    .any(|s| s.file_stem() == Some(stem) && has_extension(s, ext))
+            })
+            .collect();
+        if !missing.is_empty() {
+            return Err(ShapefileError::IncorrectFiles(format!(
+                "missing .{} file for '{}'",
+                missing.join(", ."),
+                shp.display()
+            )));
+        }
+
+        let ds = open_shapefile(&format!("{archive}/{}", shp.display()), encoding)?;
+        let layer = ds
+            .layers()
+            .next()
+            .context("no layers on shapefile")
+            .map_err(ShapefileError::InvalidData)?;
+        // Force GDAL to read the spatial ref before the archive is unlinked
+        let _srs = layer.spatial_ref();
+        Ok(ds)
+    }
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
 /// Extract the EPSG code from a .prj WKT string.
 /// Creates a minimal virtual shapefile with the .prj content so GDAL's shapefile driver
 /// can apply its full CRS matching logic (including FindMatches), which handles
@@ -339,10 +568,37 @@ mod tests {
             dbf: Vec::from([1]),
             shx: Vec::from([1]),
             prj: Vec::from([1]),
+            cpg: None,
         };
         assert_ok!(VirtualShapefile::new("test_file".to_string(), data));
     }
 
+    #[test]
+    fn zipped_shapefile_in_nested_folder_works() {
+        let zip = ZippedShapefile::new(fs::read("../test-data/zips/nested_shapefile.zip").unwrap())
+            .unwrap();
+        assert_eq!(zip.candidates(), vec!["export/site/boundary"]);
+        let ds = zip
+            .open(None, None)
+            .expect("failed to open zipped shapefile");
+        assert_eq!(ds.layers().next().unwrap().feature_count(), 3);
+    }
+
+    #[test]
+    fn zipped_shapefile_with_multiple_candidates_requires_selection() {
+        let zip =
+            ZippedShapefile::new(fs::read("../test-data/zips/multiple_shapefiles.zip").unwrap())
+                .unwrap();
+        match zip.open(None, None) {
+            Err(ShapefileError::MultipleShapefiles(candidates)) => {
+                assert_eq!(candidates.len(), 2)
+            }
+            _ => panic!("expected multiple shapefiles error"),
+        }
+        assert_ok!(zip.open(Some("tracks"), None));
+        assert_ok!(zip.open(Some("boundary"), None));
+    }
+
     #[test]
     fn virtual_shapefile_works_with_real_data() {
         let path = "../test-data/shapefiles/3_valid_polygon_osgb36";
@@ -351,6 +607,7 @@ mod tests {
             dbf: fs::read(format!("{path}.dbf")).unwrap(),
             shx: fs::read(format!("{path}.shx")).unwrap(),
             prj: fs::read(format!("{path}.prj")).unwrap(),
+            cpg: None,
         };
         let v_shapefile = VirtualShapefile::new("test_file".to_string(), data)
             .expect("failed to create virtual shapefile");
@@ -358,4 +615,62 @@ mod tests {
         let first_layer = ds.layers().next().expect("couldn't access layer");
         assert_eq!(first_layer.feature_count(), 3);
     }
+
+    fn read_fixture(name: &str) -> ShapefileData {
+        let path = format!("../test-data/shapefiles/{name}");
+        ShapefileData {
+            shp: fs::read(format!("{path}.shp")).unwrap(),
+            dbf: fs::read(format!("{path}.dbf")).unwrap(),
+            shx: fs::read(format!("{path}.shx")).unwrap(),
+            prj: fs::read(format!("{path}.prj")).unwrap(),
+            cpg: fs::read(format!("{path}.cpg")).ok(),
+        }
+    }
+
+    fn place_names(ds: &Dataset) -> Vec<String> {
+        ds.layers()
+            .next()
+            .unwrap()
+            .features()
+            .filter_map(|feature| feature.field_as_string_by_name("name").unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn cpg_code_page_is_used_to_decode_dbf() {
+        let data = read_fixture("welsh_places_cp1252");
+        assert!(data.cpg.is_some());
+        let ds = data.open(None).expect("failed to open shapefile");
+        let names = place_names(&ds);
+        assert!(names.contains(&"Ynys Môn".to_string()));
+        assert!(names.contains(&"Aberdâr".to_string()));
+    }
+
+    #[test]
+    fn encoding_override_decodes_dbf_without_cpg() {
+        let ds = read_fixture("gaelic_places_utf8_no_cpg")
+            .open(Some("UTF-8"))
+            .expect("failed to open shapefile");
+        let names = place_names(&ds);
+        assert!(names.contains(&"Dùn Èideann".to_string()));
+        assert!(names.contains(&"Steòrnabhagh".to_string()));
+
+        // Without a .cpg GDAL falls back to ISO-8859-1 and mangles the UTF-8 bytes
+        let ds = read_fixture("gaelic_places_utf8_no_cpg")
+            .open(None)
+            .expect("failed to open shapefile");
+        assert!(!place_names(&ds).contains(&"Dùn Èideann".to_string()));
+    }
+
+    #[test]
+    fn invalid_encoding_is_rejected() {
+        assert!(matches!(
+            encoding_open_options(Some("UTF-8\nSHPT=POINT")),
+            Err(ShapefileError::UnsupportedEncoding(_))
+        ));
+        assert_eq!(
+            encoding_open_options(Some("CP1252")).unwrap(),
+            vec!["ENCODING=CP1252"]
+        );
+    }
 }
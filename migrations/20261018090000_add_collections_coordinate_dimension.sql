@@ -0,0 +1,4 @@
+CREATE TYPE app.coordinate_dimension AS ENUM ('XY', 'XYZ');
+
+ALTER TABLE app.collections
+    ADD COLUMN coordinate_dimension app.coordinate_dimension NOT NULL DEFAULT 'XY';
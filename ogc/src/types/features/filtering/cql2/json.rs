@@ -0,0 +1,154 @@
+use geojson::Geometry;
+use serde_json::{Map, Value};
+
+use super::{
+    ComparisonOp, Cql2Error, Expression, Operand, SpatialOp, bbox, parse_date, parse_timestamp,
+};
+
+pub(super) fn parse(filter: &str) -> Result<Expression, Cql2Error> {
+    let value: Value = serde_json::from_str(filter)
+        .map_err(|error| Cql2Error::new(format!("invalid JSON: {error}")))?;
+    expression(&value)
+}
+
+fn expression(value: &Value) -> Result<Expression, Cql2Error> {
+    let object = match value {
+        Value::Bool(value) => return Ok(Expression::Literal(*value)),
+        Value::Object(object) => object,
+        other => {
+            return Err(Cql2Error::new(format!(
+                "expected an expression, found {other}"
+            )));
+        }
+    };
+    let op = object
+        .get("op")
+        .and_then(Value::as_str)
+        .ok_or_else(|| Cql2Error::new("expected an object with an 'op'"))?;
+    let args = object
+        .get("args")
+        .and_then(Value::as_array)
+        .ok_or_else(|| Cql2Error::new(format!("'{op}' needs an 'args' array")))?;
+    let arity = |count: usize| match args.len() == count {
+        true => Ok(()),
+        false => Err(Cql2Error::new(format!("'{op}' takes {count} arguments"))),
+    };
+
+    if let Some(op) = ComparisonOp::from_symbol(op) {
+        arity(2)?;
+        return Ok(Expression::Comparison {
+            op,
+            left: operand(&args[0])?,
+            right: operand(&args[1])?,
+        });
+    }
+    if let Some(op) = SpatialOp::from_name(op) {
+        arity(2)?;
+        return Ok(Expression::Spatial {
+            op,
+            left: operand(&args[0])?,
+            right: operand(&args[1])?,
+        });
+    }
+    match op.to_ascii_lowercase().as_str() {
+        "and" | "or" => {
+            if args.len() < 2 {
+                return Err(Cql2Error::new(format!("'{op}' takes at least 2 arguments")));
+            }
+            let args = args.iter().map(expression).collect::<Result<_, _>>()?;
+            Ok(match op.eq_ignore_ascii_case("and") {
+                true => Expression::And(args),
+                false => Expression::Or(args),
+            })
+        }
+        "not" => {
+            arity(1)?;
+            Ok(Expression::Not(Box::new(expression(&args[0])?)))
+        }
+        "isnull" => {
+            arity(1)?;
+            Ok(Expression::IsNull(operand(&args[0])?))
+        }
+        _ => Err(Cql2Error::new(format!("unsupported operator '{op}'"))),
+    }
+}
+
+fn operand(value: &Value) -> Result<Operand, Cql2Error> {
+    match value {
+        Value::String(value) => Ok(Operand::String(value.clone())),
+        Value::Number(number) => number
+            .as_f64()
+            .map(Operand::Number)
+            .ok_or_else(|| Cql2Error::new(format!("invalid number {number}"))),
+        Value::Bool(value) => Ok(Operand::Boolean(*value)),
+        Value::Object(object) => object_operand(object),
+        other => Err(Cql2Error::new(format!("expected a value, found {other}"))),
+    }
+}
+
+fn object_operand(object: &Map<String, Value>) -> Result<Operand, Cql2Error> {
+    let string = |key: &str| {
+        object[key]
+            .as_str()
+            .ok_or_else(|| Cql2Error::new(format!("'{key}' must be a string")))
+    };
+    if object.contains_key("property") {
+        Ok(Operand::Property(string("property")?.to_string()))
+    } else if object.contains_key("timestamp") {
+        Ok(Operand::Timestamp(parse_timestamp(string("timestamp")?)?))
+    } else if object.contains_key("date") {
+        Ok(Operand::Date(parse_date(string("date")?)?))
+    } else if let Some(values) = object.get("bbox") {
+        let values = values
+            .as_array()
+            .and_then(|values| values.iter().map(Value::as_f64).collect::<Option<Vec<_>>>())
+            .ok_or_else(|| Cql2Error::new("'bbox' must be an array of numbers"))?;
+        Ok(Operand::Bbox(bbox(&values)?))
+    } else if object.contains_key("type") {
+        Geometry::from_json_object(object.clone())
+            .map(Operand::Geometry)
+            .map_err(|error| Cql2Error::new(format!("invalid geometry: {error}")))
+    } else {
+        Err(Cql2Error::new("expected a property, literal or geometry"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::text;
+    use super::*;
+
+    #[test]
+    fn json_and_text_encodings_parse_to_the_same_expression() {
+        let json = r#"{
+            "op": "and",
+            "args": [
+                {"op": "=", "args": [{"property": "status"}, "ACTIVE"]},
+                {"op": "not", "args": [{"op": "isNull", "args": [{"property": "model"}]}]},
+                {"op": "<", "args": [{"property": "added"}, {"date": "2024-01-31"}]},
+                {"op": "s_intersects", "args": [
+                    {"property": "geometry"},
+                    {"type": "Point", "coordinates": [-3.0, 52.0]}
+                ]},
+                {"op": "s_within", "args": [{"property": "geometry"}, {"bbox": [-4, 51, -2, 53]}]}
+            ]
+        }"#;
+        let text = "status = 'ACTIVE' AND model IS NOT NULL AND added < DATE('2024-01-31') \
+                    AND S_INTERSECTS(geometry, POINT(-3 52)) \
+                    AND S_WITHIN(geometry, BBOX(-4, 51, -2, 53))";
+        assert_eq!(parse(json).unwrap(), text::parse(text).unwrap());
+    }
+
+    #[test]
+    fn invalid_json_filters_are_rejected() {
+        for filter in [
+            r#"{"op": "=", "args": [{"property": "status"}]}"#,
+            r#"{"op": "like", "args": [{"property": "name"}, "T%"]}"#,
+            r#"{"op": "and", "args": [true]}"#,
+            r#"{"args": []}"#,
+            r#"{"op": "s_intersects", "args": [{"property": "geometry"}, {"bbox": [1, 2]}]}"#,
+        ] {
+            assert!(parse(filter).is_err(), "{filter} should not parse");
+        }
+    }
+}
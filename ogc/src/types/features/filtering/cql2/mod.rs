@@ -0,0 +1,173 @@
+//! Common Query Language (CQL2) filters for OGC API Features Part 3.
+//!
+//! Filters in either encoding are parsed into the same [`Expression`] tree, covering the
+//! Basic CQL2 and spatial functions conformance classes.
+mod json;
+mod text;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Encoding of a `filter` query parameter
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum FilterLang {
+    #[default]
+    Cql2Text,
+    Cql2Json,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+#[error("{0}")]
+pub struct Cql2Error(pub String);
+
+impl Cql2Error {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+/// A boolean filter expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    And(Vec<Expression>),
+    Or(Vec<Expression>),
+    Not(Box<Expression>),
+    Comparison {
+        op: ComparisonOp,
+        left: Operand,
+        right: Operand,
+    },
+    IsNull(Operand),
+    Spatial {
+        op: SpatialOp,
+        left: Operand,
+        right: Operand,
+    },
+    Literal(bool),
+}
+
+/// A property or literal value an expression is evaluated on
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Property(String),
+    String(String),
+    Number(f64),
+    Boolean(bool),
+    Timestamp(DateTime<Utc>),
+    Date(NaiveDate),
+    /// A geometry in the filter CRS
+    Geometry(geojson::Geometry),
+    /// Minimum x, minimum y, maximum x and maximum y in the filter CRS
+    Bbox([f64; 4]),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComparisonOp {
+    Eq,
+    NotEq,
+    Lt,
+    Gt,
+    LtEq,
+    GtEq,
+}
+
+impl ComparisonOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ComparisonOp::Eq => "=",
+            ComparisonOp::NotEq => "<>",
+            ComparisonOp::Lt => "<",
+            ComparisonOp::Gt => ">",
+            ComparisonOp::LtEq => "<=",
+            ComparisonOp::GtEq => ">=",
+        }
+    }
+
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        [
+            ComparisonOp::Eq,
+            ComparisonOp::NotEq,
+            ComparisonOp::Lt,
+            ComparisonOp::Gt,
+            ComparisonOp::LtEq,
+            ComparisonOp::GtEq,
+        ]
+        .into_iter()
+        .find(|op| op.as_str() == symbol)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpatialOp {
+    Intersects,
+    Equals,
+    Disjoint,
+    Touches,
+    Within,
+    Overlaps,
+    Crosses,
+    Contains,
+}
+
+impl SpatialOp {
+    const ALL: [SpatialOp; 8] = [
+        SpatialOp::Intersects,
+        SpatialOp::Equals,
+        SpatialOp::Disjoint,
+        SpatialOp::Touches,
+        SpatialOp::Within,
+        SpatialOp::Overlaps,
+        SpatialOp::Crosses,
+        SpatialOp::Contains,
+    ];
+
+    /// CQL2 name of the function, e.g. `S_INTERSECTS`
+    pub fn name(self) -> &'static str {
+        match self {
+            SpatialOp::Intersects => "S_INTERSECTS",
+            SpatialOp::Equals => "S_EQUALS",
+            SpatialOp::Disjoint => "S_DISJOINT",
+            SpatialOp::Touches => "S_TOUCHES",
+            SpatialOp::Within => "S_WITHIN",
+            SpatialOp::Overlaps => "S_OVERLAPS",
+            SpatialOp::Crosses => "S_CROSSES",
+            SpatialOp::Contains => "S_CONTAINS",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|op| op.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Parse a `filter` query parameter in the given encoding
+pub fn parse(filter: &str, lang: FilterLang) -> Result<Expression, Cql2Error> {
+    match lang {
+        FilterLang::Cql2Text => text::parse(filter),
+        FilterLang::Cql2Json => json::parse(filter),
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, Cql2Error> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.to_utc())
+        .map_err(|_| Cql2Error::new(format!("invalid timestamp '{value}'")))
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, Cql2Error> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| Cql2Error::new(format!("invalid date '{value}'")))
+}
+
+/// The 2D part of a 4 or 6 value bounding box
+fn bbox(values: &[f64]) -> Result<[f64; 4], Cql2Error> {
+    match *values {
+        [min_x, min_y, max_x, max_y] => Ok([min_x, min_y, max_x, max_y]),
+        [min_x, min_y, _, max_x, max_y, _] => Ok([min_x, min_y, max_x, max_y]),
+        _ => Err(Cql2Error::new("a bbox needs 4 or 6 numbers")),
+    }
+}
@@ -0,0 +1,579 @@
+use geojson::{Geometry, Value};
+
+use super::{
+    ComparisonOp, Cql2Error, Expression, Operand, SpatialOp, bbox, parse_date, parse_timestamp,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// An unquoted identifier or keyword
+    Word(String),
+    /// A double quoted identifier
+    QuotedWord(String),
+    String(String),
+    Number(f64),
+    Op(ComparisonOp),
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+const WKT_TYPES: [&str; 7] = [
+    "POINT",
+    "LINESTRING",
+    "POLYGON",
+    "MULTIPOINT",
+    "MULTILINESTRING",
+    "MULTIPOLYGON",
+    "GEOMETRYCOLLECTION",
+];
+
+/// Deepest nesting of parentheses, `NOT`s and geometry collections a filter may have,
+/// so that parsing it recursively cannot overflow the stack
+const MAX_DEPTH: usize = 64;
+
+const KEYWORDS: [&str; 10] = [
+    "AND",
+    "OR",
+    "NOT",
+    "IS",
+    "NULL",
+    "TRUE",
+    "FALSE",
+    "TIMESTAMP",
+    "DATE",
+    "BBOX",
+];
+
+pub(super) fn parse(filter: &str) -> Result<Expression, Cql2Error> {
+    let mut parser = Parser {
+        tokens: tokenize(filter)?,
+        position: 0,
+        depth: 0,
+    };
+    let expression = parser.expression()?;
+    match parser.peek() {
+        None => Ok(expression),
+        Some(token) => Err(Cql2Error::new(format!("unexpected {token:?}"))),
+    }
+}
+
+fn tokenize(filter: &str) -> Result<Vec<Token>, Cql2Error> {
+    let mut tokens = Vec::new();
+    let mut chars = filter.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::LeftParen,
+                    ')' => Token::RightParen,
+                    _ => Token::Comma,
+                });
+            }
+            '=' | '<' | '>' => {
+                let mut symbol = String::from(c);
+                chars.next();
+                if let Some(&next) = chars.peek()
+                    && matches!((c, next), ('<', '=' | '>') | ('>', '='))
+                {
+                    symbol.push(next);
+                    chars.next();
+                }
+                tokens.push(Token::Op(ComparisonOp::from_symbol(&symbol).unwrap()));
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        // A doubled quote is an escaped quote
+                        Some(quote) if quote == c && chars.peek() == Some(&c) => {
+                            value.push(c);
+                            chars.next();
+                        }
+                        Some(quote) if quote == c => break,
+                        Some(other) => value.push(other),
+                        None => return Err(Cql2Error::new("unterminated quote")),
+                    }
+                }
+                tokens.push(match c {
+                    '\'' => Token::String(value),
+                    _ => Token::QuotedWord(value),
+                });
+            }
+            c if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    let exponent_sign = matches!(c, '-' | '+') && number.ends_with(['e', 'E']);
+                    if c.is_ascii_digit()
+                        || matches!(c, '.' | 'e' | 'E')
+                        || exponent_sign
+                        || (number.is_empty() && matches!(c, '-' | '+'))
+                    {
+                        number.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let value = number
+                    .parse()
+                    .map_err(|_| Cql2Error::new(format!("invalid number '{number}'")))?;
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || matches!(c, '_' | '.' | ':') {
+                        word.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+            other => return Err(Cql2Error::new(format!("unexpected character '{other}'"))),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// How deeply the current token is nested
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, Cql2Error> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| Cql2Error::new("unexpected end of filter"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), Cql2Error> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(Cql2Error::new(format!(
+                "expected {expected:?}, found {token:?}"
+            ))),
+        }
+    }
+
+    /// Whether the next token is the keyword `keyword`
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Consume the keyword `keyword` if it's next
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    /// Parse something nested one level deeper than the current token
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, Cql2Error>,
+    ) -> Result<T, Cql2Error> {
+        if self.depth == MAX_DEPTH {
+            return Err(Cql2Error::new(format!(
+                "filters cannot be nested more than {MAX_DEPTH} deep"
+            )));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn expression(&mut self) -> Result<Expression, Cql2Error> {
+        let mut terms = vec![self.term()?];
+        while self.keyword("OR") {
+            terms.push(self.term()?);
+        }
+        Ok(match terms.len() {
+            1 => terms.remove(0),
+            _ => Expression::Or(terms),
+        })
+    }
+
+    fn term(&mut self) -> Result<Expression, Cql2Error> {
+        let mut factors = vec![self.factor()?];
+        while self.keyword("AND") {
+            factors.push(self.factor()?);
+        }
+        Ok(match factors.len() {
+            1 => factors.remove(0),
+            _ => Expression::And(factors),
+        })
+    }
+
+    fn factor(&mut self) -> Result<Expression, Cql2Error> {
+        if self.keyword("NOT") {
+            return Ok(Expression::Not(Box::new(self.nested(Self::factor)?)));
+        }
+        if self.peek() == Some(&Token::LeftParen) {
+            self.next()?;
+            let expression = self.nested(Self::expression)?;
+            self.expect(Token::RightParen)?;
+            return Ok(expression);
+        }
+        if let Some(Token::Word(word)) = self.peek()
+            && let Some(op) = SpatialOp::from_name(word)
+        {
+            self.next()?;
+            self.expect(Token::LeftParen)?;
+            let left = self.geometry_operand()?;
+            self.expect(Token::Comma)?;
+            let right = self.geometry_operand()?;
+            self.expect(Token::RightParen)?;
+            return Ok(Expression::Spatial { op, left, right });
+        }
+        self.predicate()
+    }
+
+    fn predicate(&mut self) -> Result<Expression, Cql2Error> {
+        let left = self.scalar()?;
+        if self.keyword("IS") {
+            let negated = self.keyword("NOT");
+            if !self.keyword("NULL") {
+                return Err(Cql2Error::new("expected NULL after IS"));
+            }
+            let is_null = Expression::IsNull(left);
+            return Ok(match negated {
+                true => Expression::Not(Box::new(is_null)),
+                false => is_null,
+            });
+        }
+        if let Some(&Token::Op(op)) = self.peek() {
+            self.next()?;
+            let right = self.scalar()?;
+            return Ok(Expression::Comparison { op, left, right });
+        }
+        match left {
+            // A boolean literal can stand on its own as a predicate
+            Operand::Boolean(value) => Ok(Expression::Literal(value)),
+            _ => Err(Cql2Error::new(format!(
+                "expected a comparison, found {:?}",
+                self.peek()
+            ))),
+        }
+    }
+
+    fn scalar(&mut self) -> Result<Operand, Cql2Error> {
+        match self.next()? {
+            Token::String(value) => Ok(Operand::String(value)),
+            Token::Number(value) => Ok(Operand::Number(value)),
+            Token::QuotedWord(name) => Ok(Operand::Property(name)),
+            Token::Word(word) if word.eq_ignore_ascii_case("TRUE") => Ok(Operand::Boolean(true)),
+            Token::Word(word) if word.eq_ignore_ascii_case("FALSE") => Ok(Operand::Boolean(false)),
+            Token::Word(word) if word.eq_ignore_ascii_case("TIMESTAMP") => Ok(Operand::Timestamp(
+                parse_timestamp(&self.string_argument()?)?,
+            )),
+            Token::Word(word) if word.eq_ignore_ascii_case("DATE") => {
+                Ok(Operand::Date(parse_date(&self.string_argument()?)?))
+            }
+            Token::Word(word) if is_reserved(&word) => {
+                Err(Cql2Error::new(format!("unexpected keyword {word}")))
+            }
+            Token::Word(name) => Ok(Operand::Property(name)),
+            token => Err(Cql2Error::new(format!("expected a value, found {token:?}"))),
+        }
+    }
+
+    /// The quoted string of e.g. `TIMESTAMP('2024-01-01T00:00:00Z')`
+    fn string_argument(&mut self) -> Result<String, Cql2Error> {
+        self.expect(Token::LeftParen)?;
+        let Token::String(value) = self.next()? else {
+            return Err(Cql2Error::new("expected a quoted string"));
+        };
+        self.expect(Token::RightParen)?;
+        Ok(value)
+    }
+
+    fn geometry_operand(&mut self) -> Result<Operand, Cql2Error> {
+        if self.keyword("BBOX") {
+            self.expect(Token::LeftParen)?;
+            let mut values = vec![self.number()?];
+            while self.peek() == Some(&Token::Comma) {
+                self.next()?;
+                values.push(self.number()?);
+            }
+            self.expect(Token::RightParen)?;
+            return Ok(Operand::Bbox(bbox(&values)?));
+        }
+        if matches!(self.peek(), Some(Token::Word(word)) if is_wkt_type(word)) {
+            return Ok(Operand::Geometry(Geometry::new(self.wkt()?)));
+        }
+        match self.peek() {
+            Some(Token::Word(_) | Token::QuotedWord(_)) => match self.scalar()? {
+                property @ Operand::Property(_) => Ok(property),
+                _ => Err(Cql2Error::new("expected a geometry")),
+            },
+            token => Err(Cql2Error::new(format!(
+                "expected a geometry, found {token:?}"
+            ))),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, Cql2Error> {
+        match self.next()? {
+            Token::Number(value) => Ok(value),
+            token => Err(Cql2Error::new(format!(
+                "expected a number, found {token:?}"
+            ))),
+        }
+    }
+
+    /// A WKT geometry, e.g. `POLYGON((0 0, 1 0, 1 1, 0 0))`
+    fn wkt(&mut self) -> Result<Value, Cql2Error> {
+        let Token::Word(kind) = self.next()? else {
+            return Err(Cql2Error::new("expected a geometry type"));
+        };
+        let kind = kind.to_ascii_uppercase();
+        // Measures have no GeoJSON equivalent so are dropped
+        let dimensions = if self.keyword("M") {
+            2
+        } else {
+            if !self.keyword("Z") {
+                self.keyword("ZM");
+            }
+            3
+        };
+        if self.peek_keyword("EMPTY") {
+            return Err(Cql2Error::new("empty geometries are not supported"));
+        }
+        Ok(match kind.as_str() {
+            "POINT" => {
+                self.expect(Token::LeftParen)?;
+                let position = self.position_of(dimensions)?;
+                self.expect(Token::RightParen)?;
+                Value::Point(position)
+            }
+            "LINESTRING" => Value::LineString(self.positions(dimensions)?),
+            "POLYGON" => Value::Polygon(self.list(|parser| parser.positions(dimensions))?),
+            "MULTIPOINT" => Value::MultiPoint(self.list(|parser| {
+                // Points may or may not be wrapped in their own parentheses
+                if parser.peek() == Some(&Token::LeftParen) {
+                    parser.next()?;
+                    let position = parser.position_of(dimensions)?;
+                    parser.expect(Token::RightParen)?;
+                    Ok(position)
+                } else {
+                    parser.position_of(dimensions)
+                }
+            })?),
+            "MULTILINESTRING" => {
+                Value::MultiLineString(self.list(|parser| parser.positions(dimensions))?)
+            }
+            "MULTIPOLYGON" => Value::MultiPolygon(
+                self.list(|parser| parser.list(|parser| parser.positions(dimensions)))?,
+            ),
+            "GEOMETRYCOLLECTION" => Value::GeometryCollection(
+                self.nested(|parser| parser.list(|parser| parser.wkt().map(Geometry::new)))?,
+            ),
+            _ => return Err(Cql2Error::new(format!("unknown geometry type {kind}"))),
+        })
+    }
+
+    /// A parenthesised, comma separated list
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, Cql2Error>,
+    ) -> Result<Vec<T>, Cql2Error> {
+        self.expect(Token::LeftParen)?;
+        let mut items = vec![item(self)?];
+        while self.peek() == Some(&Token::Comma) {
+            self.next()?;
+            items.push(item(self)?);
+        }
+        self.expect(Token::RightParen)?;
+        Ok(items)
+    }
+
+    fn positions(&mut self, dimensions: usize) -> Result<Vec<Vec<f64>>, Cql2Error> {
+        self.list(|parser| parser.position_of(dimensions))
+    }
+
+    fn position_of(&mut self, dimensions: usize) -> Result<Vec<f64>, Cql2Error> {
+        let mut position = Vec::new();
+        while let Some(Token::Number(value)) = self.peek() {
+            position.push(*value);
+            self.next()?;
+        }
+        if !(2..=4).contains(&position.len()) {
+            return Err(Cql2Error::new("a position needs 2 to 4 numbers"));
+        }
+        position.truncate(dimensions);
+        Ok(position)
+    }
+}
+
+fn is_wkt_type(word: &str) -> bool {
+    WKT_TYPES.iter().any(|kind| kind.eq_ignore_ascii_case(word))
+}
+
+fn is_reserved(word: &str) -> bool {
+    KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+        || SpatialOp::from_name(word).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn property(name: &str) -> Operand {
+        Operand::Property(name.to_string())
+    }
+
+    #[test]
+    fn comparisons_are_combined_with_and_before_or() {
+        let expression =
+            parse("status = 'ACTIVE' AND hub_height >= 90 OR NOT is_primary = TRUE").unwrap();
+        assert_eq!(
+            expression,
+            Expression::Or(vec![
+                Expression::And(vec![
+                    Expression::Comparison {
+                        op: ComparisonOp::Eq,
+                        left: property("status"),
+                        right: Operand::String("ACTIVE".to_string()),
+                    },
+                    Expression::Comparison {
+                        op: ComparisonOp::GtEq,
+                        left: property("hub_height"),
+                        right: Operand::Number(90.0),
+                    },
+                ]),
+                Expression::Not(Box::new(Expression::Comparison {
+                    op: ComparisonOp::Eq,
+                    left: property("is_primary"),
+                    right: Operand::Boolean(true),
+                })),
+            ])
+        );
+    }
+
+    #[test]
+    fn quoted_strings_and_identifiers_unescape_doubled_quotes() {
+        let expression = parse(r#""land owner" <> 'Tŷ''r Mynydd'"#).unwrap();
+        assert_eq!(
+            expression,
+            Expression::Comparison {
+                op: ComparisonOp::NotEq,
+                left: property("land owner"),
+                right: Operand::String("Tŷ'r Mynydd".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn is_not_null_and_temporal_literals_are_parsed() {
+        let expression =
+            parse("model IS NOT NULL AND last_updated > TIMESTAMP('2024-05-01T12:00:00Z')")
+                .unwrap();
+        assert_eq!(
+            expression,
+            Expression::And(vec![
+                Expression::Not(Box::new(Expression::IsNull(property("model")))),
+                Expression::Comparison {
+                    op: ComparisonOp::Gt,
+                    left: property("last_updated"),
+                    right: Operand::Timestamp(Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn spatial_functions_take_wkt_and_bboxes() {
+        let expression = parse(
+            "S_INTERSECTS(geometry, POLYGON((-3 52, -2.5 52, -2.5 52.5, -3 52))) \
+             AND s_within(geometry, BBOX(-4, 51, -2, 53))",
+        )
+        .unwrap();
+        assert_eq!(
+            expression,
+            Expression::And(vec![
+                Expression::Spatial {
+                    op: SpatialOp::Intersects,
+                    left: property("geometry"),
+                    right: Operand::Geometry(Geometry::new(Value::Polygon(vec![vec![
+                        vec![-3.0, 52.0],
+                        vec![-2.5, 52.0],
+                        vec![-2.5, 52.5],
+                        vec![-3.0, 52.0],
+                    ]]))),
+                },
+                Expression::Spatial {
+                    op: SpatialOp::Within,
+                    left: property("geometry"),
+                    right: Operand::Bbox([-4.0, 51.0, -2.0, 53.0]),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn multipoints_with_and_without_parentheses_match() {
+        let with = parse("S_INTERSECTS(geometry, MULTIPOINT((1 2), (3 4)))").unwrap();
+        let without = parse("S_INTERSECTS(geometry, MULTIPOINT(1 2, 3 4))").unwrap();
+        assert_eq!(with, without);
+    }
+
+    #[test]
+    fn invalid_filters_are_rejected() {
+        for filter in [
+            "status =",
+            "status = 'ACTIVE",
+            "(status = 'ACTIVE'",
+            "status = 'ACTIVE' extra",
+            "S_INTERSECTS(geometry, POINT(1))",
+            "AND = 1",
+        ] {
+            assert!(parse(filter).is_err(), "{filter} should not parse");
+        }
+    }
+
+    #[test]
+    fn deeply_nested_filters_are_rejected() {
+        let nested = |depth: usize| {
+            format!(
+                "{}status = 'ACTIVE'{}",
+                "(".repeat(depth),
+                ")".repeat(depth)
+            )
+        };
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert!(parse(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(parse(&nested(100_000)).is_err());
+        assert!(parse(&format!("{}status = 'ACTIVE'", "NOT ".repeat(100_000))).is_err());
+        let collections = format!(
+            "S_INTERSECTS(geometry, {}POINT(1 2){})",
+            "GEOMETRYCOLLECTION(".repeat(100_000),
+            ")".repeat(100_000)
+        );
+        assert!(parse(&collections).is_err());
+    }
+}
@@ -1,2 +1,3 @@
+pub mod cql2;
 mod queryables;
 pub use queryables::{QueryableProperty, Queryables};